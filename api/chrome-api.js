@@ -12,12 +12,45 @@ function sendNative(request) {
 	});
 }
 
+// Long-lived alternative to sendNative: one host process serves every request sent
+// through `request()`, and keeps the vault loaded. Responses arrive in request order.
+function connect() {
+	const port = chrome.runtime.connectNative('com.binqbit.svpi_chrome_app');
+	const pending = [];
+	port.onMessage.addListener((message) => {
+		if (message.command === 'chrome.event') {
+			return;
+		}
+		const next = pending.shift();
+		if (next) {
+			next.resolve(message);
+		}
+	});
+	port.onDisconnect.addListener(() => {
+		const error = chrome.runtime.lastError ? chrome.runtime.lastError.message : 'disconnected';
+		pending.splice(0).forEach((p) => p.reject(error));
+	});
+	return {
+		port,
+		request(request) {
+			return new Promise((resolve, reject) => {
+				pending.push({ resolve, reject });
+				port.postMessage(request);
+			});
+		},
+		close() {
+			port.disconnect();
+		},
+	};
+}
+
 async function get_status() {
 	return await sendNative({ status: {} });
 }
 
-async function get_list() {
-	return await sendNative({ list: {} });
+// Optional filter: { pattern, regex, data_type, encrypted, key, min_size, max_size, sort, desc }
+async function get_list(filter = {}) {
+	return await sendNative({ list: filter });
 }
 
 // { "get_data": { "name": "name", "password": "password?" } }
@@ -26,3 +59,70 @@ async function get_data(name, password = undefined) {
 	const request = password ? { get_data: { name, password } } : { get_data: { name } };
 	return await sendNative(request);
 }
+
+async function get_keys() {
+	return await sendNative({ list_keys: {} });
+}
+
+// { "get_data": { "name": "name", "password": "password?", "field": "password" } }
+// Returns a single field of a record.
+async function get_field(name, field, password = undefined) {
+	return await sendNative({ get_data: { name, password, field } });
+}
+
+// { "set_data": { "name": "name", "data": "data", "password": "password?" } }
+async function set_data(name, data, password = undefined) {
+	return await sendNative({ set_data: { name, data, password } });
+}
+
+// { "set_data": { "name": "name", "fields": { "username": "u" }, "password": "password?" } }
+// Replaces the whole record.
+async function set_record(name, fields, password = undefined) {
+	return await sendNative({ set_data: { name, fields, password } });
+}
+
+// { "remove_data": { "name": "name", "confirm": true } }
+// Without confirm the host answers with `confirmation_required`.
+async function remove_data(name, confirm = false) {
+	return await sendNative({ remove_data: { name, confirm } });
+}
+
+// { "rename_data": { "old_name": "old", "new_name": "new" } }
+async function rename_data(old_name, new_name) {
+	return await sendNative({ rename_data: { old_name, new_name } });
+}
+
+// { "change_data_type": { "name": "name", "data_type": "hex" } }
+async function change_data_type(name, data_type) {
+	return await sendNative({ change_data_type: { name, data_type } });
+}
+
+// { "change_password": { "name": "name", "old_password": "a?", "new_password": "b?" } }
+async function change_password(name, old_password = undefined, new_password = undefined) {
+	return await sendNative({ change_password: { name, old_password, new_password } });
+}
+
+// { "set_urls": { "name": "name", "urls": ["github.com"] } }
+async function set_urls(name, urls) {
+	return await sendNative({ set_urls: { name, urls } });
+}
+
+// { "find_by_url": { "url": "https://github.com/login" } }
+// Returns candidate entry names (no data) for the page.
+async function find_by_url(url) {
+	return await sendNative({ find_by_url: { url } });
+}
+
+// { "subscribe_events": {} } over a long-lived port.
+// The first message confirms the subscription (`chrome.subscribe_events`); each later
+// `chrome.event` message carries an event in `result`. Call port.disconnect() to stop.
+function subscribe_events(onEvent) {
+	const port = chrome.runtime.connectNative('com.binqbit.svpi_chrome_app');
+	port.onMessage.addListener((message) => {
+		if (message.command === 'chrome.event') {
+			onEvent(message.result);
+		}
+	});
+	port.postMessage({ subscribe_events: {} });
+	return port;
+}
@@ -1,6 +1,10 @@
 const axios = require('axios');
 
-const api = axios.create({ baseURL: 'http://localhost:3333' });
+// Create a token with `svpi token create <name> --scope=...`.
+const api = axios.create({
+	baseURL: 'http://localhost:3333',
+	headers: { Authorization: `Bearer ${process.env.SVPI_TOKEN}` },
+});
 
 // All endpoints return SvpiResponse:
 // {
@@ -23,18 +27,111 @@ async function get_status() {
 	return (await api.get('/status')).data;
 }
 
-async function get_list() {
-	return (await api.get('/list')).data;
+// Optional filter: { pattern, regex, data_type, encrypted, key, min_size, max_size, sort, desc }
+async function get_list(filter = {}) {
+	return (await api.get('/list', { params: filter })).data;
 }
 
-// GET /get?name=... [&password=...]
+// POST /api with any CommandRequest, e.g. { get_data: { name, password } }.
+// Works with `--post-only`.
+async function command(request) {
+	return (await api.post('/api', request)).data;
+}
+
+// Sent through POST /api so the password stays out of the URL.
 // Password is required only for encrypted segments.
 async function get_data(name, password = undefined) {
-	const params = { name };
-	if (password) {
-		params.password = password;
-	}
-	return (await api.get('/get', { params })).data;
+	return command({ get_data: { name, password } });
+}
+
+// Returns a single field of a record.
+async function get_field(name, field, password = undefined) {
+	return command({ get_data: { name, password, field } });
+}
+
+async function get_keys() {
+	return (await api.get('/keys')).data;
+}
+
+// POST /set { name, data, password? }
+async function set_data(name, data, password = undefined) {
+	return (await api.post('/set', { name, data, password })).data;
+}
+
+// POST /set { name, fields, password? }; replaces the whole record.
+async function set_record(name, fields, password = undefined) {
+	return (await api.post('/set', { name, fields, password })).data;
+}
+
+// POST /remove { name, confirm }
+// Without confirm the server answers with `confirmation_required`.
+async function remove_data(name, confirm = false) {
+	return (await api.post('/remove', { name, confirm })).data;
+}
+
+// POST /rename { old_name, new_name }
+async function rename_data(old_name, new_name) {
+	return (await api.post('/rename', { old_name, new_name })).data;
+}
+
+// POST /change-data-type { name, data_type }
+async function change_data_type(name, data_type) {
+	return (await api.post('/change-data-type', { name, data_type })).data;
+}
+
+// POST /change-password { name, old_password?, new_password? }
+async function change_password(name, old_password = undefined, new_password = undefined) {
+	return (await api.post('/change-password', { name, old_password, new_password })).data;
+}
+
+// POST /api { set_urls: { name, urls } }; an empty list clears them.
+async function set_urls(name, urls) {
+	return command({ set_urls: { name, urls } });
+}
+
+// POST /api { find_by_url: { url } }; returns candidate entry names for a page.
+async function find_by_url(url) {
+	return command({ find_by_url: { url } });
+}
+
+// GET /events (Server-Sent Events); calls onEvent for each event object.
+// Returns the response stream; call destroy() on it to unsubscribe.
+async function subscribe_events(onEvent) {
+	const res = await api.get('/events', { responseType: 'stream' });
+	let buffer = '';
+	res.data.on('data', (chunk) => {
+		buffer += chunk.toString();
+		let end;
+		while ((end = buffer.indexOf('\n\n')) !== -1) {
+			const block = buffer.slice(0, end);
+			buffer = buffer.slice(end + 2);
+			const data = block
+				.split('\n')
+				.filter((line) => line.startsWith('data:'))
+				.map((line) => line.slice(5))
+				.join('\n');
+			if (data) {
+				onEvent(JSON.parse(data));
+			}
+		}
+	});
+	return res.data;
 }
 
-module.exports = { get_status, get_list, get_data };
+module.exports = {
+	command,
+	get_status,
+	get_list,
+	get_data,
+	get_field,
+	get_keys,
+	set_data,
+	set_record,
+	remove_data,
+	rename_data,
+	change_data_type,
+	change_password,
+	set_urls,
+	find_by_url,
+	subscribe_events,
+};
@@ -1,29 +1,52 @@
-use std::io::{self, Read, Write};
+use std::{
+    collections::hash_map::DefaultHasher,
+    fs,
+    hash::{Hash, Hasher},
+    io::{self, Read, Write},
+    sync::mpsc::{self, RecvTimeoutError},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde_json::{json, Value};
 
 use crate::{
     data_mgr::DataInterfaceType,
-    protocol::api::{self, ApiTransport, CommandRequest},
+    pass_mgr::{PasswordManager, RateLimitPolicy},
+    protocol::{
+        api::{self, ApiTransport, CommandRequest},
+        events::{diff_entries, ApiEvent},
+        segments::SegmentSummary,
+    },
+    seg_mgr::DataManagerError,
     utils::response::SvpiResponse,
 };
 
 const MAX_CHROME_MESSAGE_SIZE: usize = 1024 * 1024;
+const SUBSCRIBE_EVENTS: &str = "subscribe_events";
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, PartialEq, Eq)]
+enum Incoming {
+    Message(Vec<u8>),
+    /// The message body was skipped, so the stream stays in sync.
+    TooLarge(usize),
+}
 
-fn read_message() -> io::Result<CommandRequest> {
+fn read_incoming(input: &mut impl Read) -> io::Result<Incoming> {
     let mut length_buf = [0u8; 4];
-    io::stdin().read_exact(&mut length_buf)?;
+    input.read_exact(&mut length_buf)?;
     let length = u32::from_le_bytes(length_buf) as usize;
     if length > MAX_CHROME_MESSAGE_SIZE {
-        return Err(io::Error::new(
-            io::ErrorKind::InvalidData,
-            format!("Message too large ({length} bytes)"),
-        ));
+        let skipped = io::copy(&mut input.take(length as u64), &mut io::sink())?;
+        if skipped < length as u64 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        return Ok(Incoming::TooLarge(length));
     }
 
     let mut message_buf = vec![0u8; length];
-    io::stdin().read_exact(&mut message_buf)?;
-
-    serde_json::from_slice(&message_buf)
-        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    input.read_exact(&mut message_buf)?;
+    Ok(Incoming::Message(message_buf))
 }
 
 fn send_message(response: &SvpiResponse) -> io::Result<()> {
@@ -36,11 +59,312 @@ fn send_message(response: &SvpiResponse) -> io::Result<()> {
     Ok(())
 }
 
-pub fn run_chrome_app(interface_type: DataInterfaceType) -> io::Result<()> {
-    let request = read_message()?;
-    let response = api::handle(ApiTransport::Chrome, interface_type, request);
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Hash of a vault file's contents (timestamps miss same-second writes of the
+/// same size); `None` for devices that only this process can open while it
+/// holds them (serial port).
+fn vault_stamp(interface_type: &DataInterfaceType) -> Option<u64> {
+    let DataInterfaceType::FileSystem(path) = interface_type else {
+        return None;
+    };
+    let mut hasher = DefaultHasher::new();
+    fs::read(path).ok()?.hash(&mut hasher);
+    Some(hasher.finish())
+}
 
-    send_message(&response)?;
+/// State kept for the lifetime of a native messaging port.
+struct Session {
+    interface_type: DataInterfaceType,
+    rate_limit: RateLimitPolicy,
+    pass_mgr: Option<PasswordManager>,
+    stamp: Option<u64>,
+    subscribed: bool,
+    connected: bool,
+    entries: Option<Vec<SegmentSummary>>,
+}
 
-    Ok(())
+impl Session {
+    fn new(interface_type: DataInterfaceType, rate_limit: RateLimitPolicy) -> Self {
+        Self {
+            interface_type,
+            rate_limit,
+            pass_mgr: None,
+            stamp: None,
+            subscribed: false,
+            connected: true,
+            entries: None,
+        }
+    }
+
+    /// The loaded vault, reloaded when the file was changed by another process.
+    fn manager(&mut self) -> Result<&mut PasswordManager, DataManagerError> {
+        let stamp = vault_stamp(&self.interface_type);
+        if stamp != self.stamp {
+            self.pass_mgr = None;
+        }
+        let pass_mgr = match self.pass_mgr.take() {
+            Some(mgr) => mgr,
+            None => PasswordManager::try_load(self.interface_type.clone())?,
+        };
+        self.stamp = stamp;
+        Ok(self.pass_mgr.insert(pass_mgr))
+    }
+
+    fn handle(&mut self, request: CommandRequest) -> SvpiResponse {
+        let command = api::cmd(ApiTransport::Chrome, request.command_name());
+        let policy = self.rate_limit;
+        let resp = match self.manager() {
+            Ok(pass_mgr) => api::handle_limited_with(
+                pass_mgr,
+                ApiTransport::Chrome,
+                request,
+                &policy,
+                unix_now(),
+            ),
+            Err(err) => SvpiResponse::data_manager_error_public(command, err),
+        };
+
+        // Our own writes do not make the loaded vault stale; anything else
+        // that changed the file still does.
+        let wrote = self
+            .pass_mgr
+            .as_mut()
+            .is_some_and(|pass_mgr| pass_mgr.get_data_manager().data_mgr.take_written());
+        if wrote {
+            self.stamp = vault_stamp(&self.interface_type);
+        }
+
+        let device_failed = resp
+            .error
+            .as_ref()
+            .is_some_and(|err| matches!(err.code.as_str(), "device_error" | "device_not_found"));
+        if device_failed {
+            self.pass_mgr = None;
+        }
+        resp
+    }
+
+    fn handle_message(&mut self, incoming: Incoming) -> SvpiResponse {
+        let message = match incoming {
+            Incoming::TooLarge(size) => {
+                return SvpiResponse::err(
+                    None,
+                    "invalid_command",
+                    format!("Message too large ({size} bytes)"),
+                    Some(json!({ "size": size, "max_size": MAX_CHROME_MESSAGE_SIZE })),
+                )
+            }
+            Incoming::Message(bytes) => match serde_json::from_slice::<Value>(&bytes) {
+                Ok(v) => v,
+                Err(err) => {
+                    return SvpiResponse::err(
+                        None,
+                        "invalid_command",
+                        format!("Malformed message: {err}"),
+                        None,
+                    )
+                }
+            },
+        };
+
+        if message.get(SUBSCRIBE_EVENTS).is_some() {
+            self.subscribed = true;
+            self.entries = self.listing();
+            return SvpiResponse::ok(
+                api::cmd(ApiTransport::Chrome, SUBSCRIBE_EVENTS),
+                json!({ "subscribed": true }),
+            );
+        }
+
+        match serde_json::from_value::<CommandRequest>(message) {
+            Ok(request) => self.handle(request),
+            Err(err) => SvpiResponse::err(
+                None,
+                "invalid_command",
+                format!("Unknown request: {err}"),
+                None,
+            ),
+        }
+    }
+
+    fn listing(&mut self) -> Option<Vec<SegmentSummary>> {
+        self.manager().ok().map(api::list_entries)
+    }
+
+    /// Events since the last poll: device connect/disconnect (serial device
+    /// only) and entry changes.
+    fn poll_events(&mut self, now: u64) -> Vec<ApiEvent> {
+        let serial = matches!(self.interface_type, DataInterfaceType::SerialPort);
+        if serial {
+            // Nothing else changes the device while we hold it, but we have to
+            // open it again to notice that it went away.
+            self.pass_mgr = None;
+        }
+
+        let mut events = Vec::new();
+        let Some(listing) = self.listing() else {
+            if serial && self.connected {
+                self.connected = false;
+                events.push(ApiEvent::DeviceDisconnected { at: now });
+            }
+            return events;
+        };
+        if serial && !self.connected {
+            events.push(ApiEvent::DeviceConnected { at: now });
+        }
+        self.connected = true;
+
+        if let Some(prev) = &self.entries {
+            events.extend(diff_entries(prev, &listing, now));
+        }
+        self.entries = Some(listing);
+        events
+    }
+}
+
+/// Serves messages until the browser closes the port (stdin). Malformed and
+/// oversized messages get an error response; the vault stays loaded between
+/// messages. After `subscribe_events`, `ApiEvent`s are sent as `chrome.event`
+/// responses in between.
+pub fn run_chrome_app(
+    interface_type: DataInterfaceType,
+    rate_limit: RateLimitPolicy,
+) -> io::Result<()> {
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let mut stdin = io::stdin().lock();
+        while let Ok(incoming) = read_incoming(&mut stdin) {
+            if tx.send(incoming).is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut session = Session::new(interface_type, rate_limit);
+    loop {
+        let incoming = if session.subscribed {
+            match rx.recv_timeout(POLL_INTERVAL) {
+                Ok(incoming) => Some(incoming),
+                Err(RecvTimeoutError::Timeout) => None,
+                Err(RecvTimeoutError::Disconnected) => return Ok(()),
+            }
+        } else {
+            match rx.recv() {
+                Ok(incoming) => Some(incoming),
+                Err(_) => return Ok(()),
+            }
+        };
+
+        match incoming {
+            Some(incoming) => send_message(&session.handle_message(incoming))?,
+            None => {
+                for event in session.poll_events(unix_now()) {
+                    let Ok(result) = serde_json::to_value(&event) else {
+                        continue;
+                    };
+                    send_message(&SvpiResponse::ok(
+                        api::cmd(ApiTransport::Chrome, "event"),
+                        result,
+                    ))?;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn frame(body: &[u8]) -> Vec<u8> {
+        let mut out = (body.len() as u32).to_le_bytes().to_vec();
+        out.extend_from_slice(body);
+        out
+    }
+
+    #[test]
+    fn oversized_messages_are_skipped_without_losing_sync() {
+        let mut input = frame(&vec![b' '; MAX_CHROME_MESSAGE_SIZE + 1]);
+        input.extend(frame(br#"{"status":{}}"#));
+        let mut input = Cursor::new(input);
+
+        assert_eq!(
+            read_incoming(&mut input).unwrap(),
+            Incoming::TooLarge(MAX_CHROME_MESSAGE_SIZE + 1)
+        );
+        assert_eq!(
+            read_incoming(&mut input).unwrap(),
+            Incoming::Message(br#"{"status":{}}"#.to_vec())
+        );
+        assert!(read_incoming(&mut input).is_err());
+    }
+
+    #[test]
+    fn session_answers_bad_messages_and_keeps_serving() {
+        let mut session = Session::new(
+            DataInterfaceType::Memory(Vec::new()),
+            RateLimitPolicy::default(),
+        );
+
+        let resp = session.handle_message(Incoming::Message(b"{not json".to_vec()));
+        assert_eq!(resp.error.unwrap().code, "invalid_command");
+
+        let resp = session.handle_message(Incoming::Message(br#"{"nope":{}}"#.to_vec()));
+        assert_eq!(resp.error.unwrap().code, "invalid_command");
+
+        let resp = session.handle_message(Incoming::TooLarge(MAX_CHROME_MESSAGE_SIZE + 1));
+        assert_eq!(resp.error.unwrap().code, "invalid_command");
+
+        let resp = session.handle_message(Incoming::Message(br#"{"list":{}}"#.to_vec()));
+        assert_eq!(resp.command.as_deref(), Some("chrome.list"));
+    }
+
+    #[test]
+    fn session_reloads_vault_changed_by_another_process() {
+        let dir = std::env::temp_dir().join(format!("svpi-chrome-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("vault.bin").to_string_lossy().into_owned();
+        let _ = fs::remove_file(&path);
+        let interface_type = DataInterfaceType::FileSystem(path.clone());
+        PasswordManager::from_device_type(interface_type.clone())
+            .unwrap()
+            .get_data_manager()
+            .init_device(2048, crate::seg_mgr::EncryptionLevel::Low)
+            .unwrap();
+
+        let mut session = Session::new(interface_type.clone(), RateLimitPolicy::default());
+        let list = |session: &mut Session| {
+            let resp = session.handle_message(Incoming::Message(br#"{"list":{}}"#.to_vec()));
+            resp.result.unwrap()["segments"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|seg| seg["name"].as_str().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        let resp = session.handle_message(Incoming::Message(
+            br#"{"set_data":{"name":"own","data":"a"}}"#.to_vec(),
+        ));
+        assert!(resp.ok);
+        // Our own write is no reason to reload.
+        assert_eq!(session.stamp, vault_stamp(&interface_type));
+        assert_eq!(list(&mut session), ["own"]);
+
+        // Same size, most likely the same second: only the contents differ.
+        PasswordManager::try_load(interface_type)
+            .unwrap()
+            .save_password("other", "b", None)
+            .unwrap();
+        assert_eq!(list(&mut session), ["other", "own"]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
 }
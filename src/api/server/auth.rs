@@ -0,0 +1,190 @@
+use rocket::{
+    http::Status,
+    request::{FromRequest, Outcome},
+    Request,
+};
+
+use crate::{
+    api::server::ApiState,
+    pass_mgr::{ApiToken, PasswordManager},
+    protocol::api::CommandRequest,
+    utils::response::SvpiResponse,
+};
+
+/// Request guard: a valid `Authorization: Bearer <token>` for the vault being served.
+pub struct ApiAuth(pub ApiToken);
+
+#[derive(Debug)]
+pub enum ApiAuthError {
+    MissingToken,
+    InvalidToken,
+    Vault(SvpiResponse),
+}
+
+fn bearer_token<'r>(request: &'r Request<'_>) -> Option<&'r str> {
+    let value = request.headers().get_one("Authorization")?;
+    let (scheme, token) = value.trim().split_once(' ')?;
+    if !scheme.eq_ignore_ascii_case("bearer") {
+        return None;
+    }
+    Some(token.trim()).filter(|t| !t.is_empty())
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for ApiAuth {
+    type Error = ApiAuthError;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let Some(token) = bearer_token(request) else {
+            return Outcome::Error((Status::Unauthorized, ApiAuthError::MissingToken));
+        };
+
+        let Some(state) = request.rocket().state::<ApiState>() else {
+            return Outcome::Error((
+                Status::InternalServerError,
+                ApiAuthError::Vault(SvpiResponse::err(
+                    None,
+                    "internal_error",
+                    "API state missing",
+                    None,
+                )),
+            ));
+        };
+
+        let _guard = state.lock.lock().expect("Failed to lock API mutex");
+        let mut pass_mgr = match PasswordManager::try_load(state.interface_type.clone()) {
+            Ok(mgr) => mgr,
+            Err(err) => {
+                return Outcome::Error((
+                    Status::ServiceUnavailable,
+                    ApiAuthError::Vault(SvpiResponse::data_manager_error_public(None, err)),
+                ))
+            }
+        };
+
+        match pass_mgr.find_api_token(token) {
+            Ok(Some(record)) => Outcome::Success(ApiAuth(record)),
+            Ok(None) => Outcome::Error((Status::Unauthorized, ApiAuthError::InvalidToken)),
+            Err(err) => Outcome::Error((
+                Status::InternalServerError,
+                ApiAuthError::Vault(SvpiResponse::password_manager_error(None, err)),
+            )),
+        }
+    }
+}
+
+/// Scope check for requests sent as a JSON `CommandRequest` (`POST /api`).
+pub fn request_allowed(token: &ApiToken, request: &CommandRequest) -> bool {
+    match request {
+        CommandRequest::Status {} => true,
+        CommandRequest::List(_) | CommandRequest::ListKeys {} => token.allows_list(),
+        CommandRequest::GetData(req) => token.allows_read(&req.name),
+        CommandRequest::SetData(req) => token.allows_write(&req.name),
+        CommandRequest::RemoveData(req) => token.allows_write(&req.name),
+        CommandRequest::RenameData(req) => {
+            token.allows_write(&req.old_name) && token.allows_write(&req.new_name)
+        }
+        CommandRequest::ChangeDataType(req) => token.allows_write(&req.name),
+        CommandRequest::ChangePassword(req) => token.allows_write(&req.name),
+        CommandRequest::SetUrls(req) => token.allows_write(&req.name),
+        CommandRequest::FindByUrl(_) => token.allows_list(),
+    }
+}
+
+/// Turns the guard result into the route's error response when the token is
+/// missing, invalid or lacks the scope checked by `allowed`.
+pub fn authorize<F>(
+    auth: Result<ApiAuth, ApiAuthError>,
+    command: Option<String>,
+    allowed: F,
+) -> Result<(), Box<SvpiResponse>>
+where
+    F: FnOnce(&ApiToken) -> bool,
+{
+    let err = match auth {
+        Ok(ApiAuth(token)) if allowed(&token) => return Ok(()),
+        Ok(ApiAuth(token)) => SvpiResponse::err(
+            command,
+            "forbidden",
+            "Token scope does not allow this request",
+            Some(serde_json::json!({ "token": token.id })),
+        ),
+        Err(ApiAuthError::MissingToken) => {
+            SvpiResponse::err(command, "unauthorized", "Missing bearer token", None)
+        }
+        Err(ApiAuthError::InvalidToken) => {
+            SvpiResponse::err(command, "unauthorized", "Invalid bearer token", None)
+        }
+        Err(ApiAuthError::Vault(mut resp)) => {
+            resp.command = command;
+            resp
+        }
+    };
+    Err(Box::new(err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pass_mgr::TokenScope;
+
+    fn token(scopes: Vec<TokenScope>) -> ApiToken {
+        ApiToken {
+            id: "abcd1234".to_string(),
+            name: "test".to_string(),
+            hash: String::new(),
+            scopes,
+            created_at: 0,
+        }
+    }
+
+    #[test]
+    fn authorize_reports_missing_and_forbidden() {
+        let command = Some("api.get".to_string());
+
+        let resp =
+            authorize(Err(ApiAuthError::MissingToken), command.clone(), |_| true).unwrap_err();
+        assert_eq!(resp.error.as_ref().unwrap().code, "unauthorized");
+
+        let auth = Ok(ApiAuth(token(vec![TokenScope::List])));
+        let resp = authorize(auth, command.clone(), |t| t.allows_read("mail")).unwrap_err();
+        assert_eq!(resp.error.as_ref().unwrap().code, "forbidden");
+        assert_eq!(resp.command, command);
+
+        let auth = Ok(ApiAuth(token(vec![TokenScope::Read("mail*".to_string())])));
+        assert!(authorize(auth, command, |t| t.allows_read("mail-work")).is_ok());
+    }
+
+    #[test]
+    fn request_scopes_follow_the_command() {
+        let token = token(vec![
+            TokenScope::Read("mail*".to_string()),
+            TokenScope::Write("mail-work".to_string()),
+        ]);
+        let request =
+            |json: serde_json::Value| -> CommandRequest { serde_json::from_value(json).unwrap() };
+
+        assert!(request_allowed(
+            &token,
+            &request(serde_json::json!({ "status": {} }))
+        ));
+        assert!(!request_allowed(
+            &token,
+            &request(serde_json::json!({ "list": {} }))
+        ));
+        assert!(request_allowed(
+            &token,
+            &request(serde_json::json!({ "get_data": { "name": "mail-home" } }))
+        ));
+        assert!(!request_allowed(
+            &token,
+            &request(serde_json::json!({ "set_data": { "name": "mail-home", "data": "x" } }))
+        ));
+        assert!(!request_allowed(
+            &token,
+            &request(serde_json::json!({
+                "rename_data": { "old_name": "mail-work", "new_name": "other" }
+            }))
+        ));
+    }
+}
@@ -0,0 +1,114 @@
+use rocket::http::Method;
+use rocket_cors::{AllowedHeaders, AllowedOrigins, Cors, CorsOptions};
+
+use crate::cli::CorsPolicy;
+
+/// Schemes whose origins `rocket_cors` can match exactly; other schemes
+/// (`chrome-extension://`, `moz-extension://`, ...) are opaque origins and
+/// have to be matched with an anchored regex instead.
+const TUPLE_ORIGIN_SCHEMES: &[&str] = &["http", "https"];
+
+/// Checks that `origin` is a bare `scheme://host[:port]` origin, as sent by
+/// browsers in the `Origin` header.
+pub fn validate_origin(origin: &str) -> Result<(), String> {
+    let invalid = |reason: &str| {
+        Err(format!(
+            "invalid CORS origin '{origin}': {reason} (expected scheme://host[:port])"
+        ))
+    };
+
+    let Some((scheme, host)) = origin.split_once("://") else {
+        return invalid("missing scheme");
+    };
+    if scheme.is_empty()
+        || !scheme
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || "+-.".contains(c))
+    {
+        return invalid("scheme must be lowercase");
+    }
+    if host.is_empty() {
+        return invalid("missing host");
+    }
+    if host.contains(['/', '?', '#']) {
+        return invalid("path, query and trailing '/' are not allowed");
+    }
+    if host.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return invalid("whitespace is not allowed");
+    }
+    Ok(())
+}
+
+fn is_tuple_origin(origin: &str) -> bool {
+    origin
+        .split_once("://")
+        .is_some_and(|(scheme, _)| TUPLE_ORIGIN_SCHEMES.contains(&scheme))
+}
+
+/// Builds the CORS fairing for the server, or `None` when no CORS headers
+/// should be sent.
+pub fn cors_fairing(policy: CorsPolicy, origins: &[String]) -> Result<Option<Cors>, String> {
+    let options = match (policy, origins.is_empty()) {
+        (CorsPolicy::None, true) => return Ok(None),
+        (CorsPolicy::AllowAll, true) => CorsOptions::default(),
+        (CorsPolicy::AllowAll, false) => {
+            return Err("--cors=allow-all cannot be combined with --cors-origin".to_string())
+        }
+        (CorsPolicy::None, false) => {
+            for origin in origins {
+                validate_origin(origin)?;
+            }
+            let (exact, opaque): (Vec<&String>, Vec<&String>) =
+                origins.iter().partition(|origin| is_tuple_origin(origin));
+            let regex: Vec<String> = opaque
+                .iter()
+                .map(|origin| format!("^{}$", regex::escape(origin)))
+                .collect();
+
+            CorsOptions {
+                allowed_origins: AllowedOrigins::some(&exact, &regex),
+                allowed_methods: [Method::Get, Method::Post]
+                    .into_iter()
+                    .map(From::from)
+                    .collect(),
+                allowed_headers: AllowedHeaders::some(&["Authorization", "Content-Type"]),
+                allow_credentials: false,
+                ..Default::default()
+            }
+        }
+    };
+
+    options
+        .to_cors()
+        .map(Some)
+        .map_err(|err| format!("invalid CORS configuration: {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn origins_are_validated() {
+        assert!(validate_origin("http://localhost:5173").is_ok());
+        assert!(validate_origin("chrome-extension://abcdefghijklmnopabcdefghijklmnop").is_ok());
+        assert!(validate_origin("localhost:5173").is_err());
+        assert!(validate_origin("http://localhost:5173/").is_err());
+        assert!(validate_origin("HTTP://localhost").is_err());
+        assert!(validate_origin("https://").is_err());
+    }
+
+    #[test]
+    fn fairing_follows_policy_and_origins() {
+        let origins = vec![
+            "http://localhost:5173".to_string(),
+            "chrome-extension://abcdefghijklmnopabcdefghijklmnop".to_string(),
+        ];
+
+        assert!(cors_fairing(CorsPolicy::None, &[]).unwrap().is_none());
+        assert!(cors_fairing(CorsPolicy::AllowAll, &[]).unwrap().is_some());
+        assert!(cors_fairing(CorsPolicy::None, &origins).unwrap().is_some());
+        assert!(cors_fairing(CorsPolicy::AllowAll, &origins).is_err());
+        assert!(cors_fairing(CorsPolicy::None, &["localhost".to_string()]).is_err());
+    }
+}
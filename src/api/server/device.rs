@@ -0,0 +1,192 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use tokio::sync::broadcast;
+
+use crate::{
+    data_mgr::{DataInterfaceType, DataManager},
+    pass_mgr::PasswordManager,
+    protocol::{
+        api,
+        events::{diff_entries, ApiEvent},
+        segments::SegmentSummary,
+    },
+    utils::response::SvpiResponse,
+};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeviceState {
+    Connected,
+    /// Unix seconds of the first failed check.
+    Disconnected {
+        since: u64,
+    },
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn device_answers(interface_type: &DataInterfaceType) -> bool {
+    let Ok(mut data_mgr) = interface_type.load_data_manager() else {
+        return false;
+    };
+
+    match &mut data_mgr {
+        DataManager::SerialPort(spdm) => spdm
+            .test(b"test")
+            .is_ok_and(|res| res.as_slice() == b"test"),
+        DataManager::FileSystem(_) | DataManager::Memory(_) => true,
+    }
+}
+
+/// Error reported by `status` while the device is known to be gone.
+pub fn disconnected_response(
+    state: &Mutex<DeviceState>,
+    command: Option<String>,
+) -> Option<SvpiResponse> {
+    let DeviceState::Disconnected { since } = *state.lock().expect("Failed to lock device state")
+    else {
+        return None;
+    };
+    Some(SvpiResponse::err(
+        command,
+        "device_disconnected",
+        "Device disconnected; waiting for it to reconnect",
+        Some(serde_json::json!({ "since": since })),
+    ))
+}
+
+/// Applies one check result; returns the event to publish on a change.
+fn transition(state: &mut DeviceState, answers: bool, now: u64) -> Option<ApiEvent> {
+    match (*state, answers) {
+        (DeviceState::Connected, false) => {
+            *state = DeviceState::Disconnected { since: now };
+            Some(ApiEvent::DeviceDisconnected { at: now })
+        }
+        (DeviceState::Disconnected { .. }, true) => {
+            *state = DeviceState::Connected;
+            Some(ApiEvent::DeviceConnected { at: now })
+        }
+        _ => None,
+    }
+}
+
+fn list_entries(interface_type: &DataInterfaceType) -> Option<Vec<SegmentSummary>> {
+    PasswordManager::try_load(interface_type.clone())
+        .ok()
+        .map(|mut pass_mgr| api::list_entries(&mut pass_mgr))
+}
+
+/// Polls the serial device and keeps `state` up to date. Every request finds
+/// the device again, so nothing else is needed to reconnect once it answers.
+/// With `auto_exit` the process exits on the first failed check instead.
+///
+/// While anyone is subscribed to `events`, the entry listing is compared
+/// between checks as well, so changes made by any client (including the CLI)
+/// are published.
+pub fn start_monitor(
+    interface_type: DataInterfaceType,
+    lock: Arc<Mutex<()>>,
+    state: Arc<Mutex<DeviceState>>,
+    events: broadcast::Sender<ApiEvent>,
+    auto_exit: bool,
+    print_device_events: bool,
+) {
+    let serial = matches!(interface_type, DataInterfaceType::SerialPort);
+
+    std::thread::spawn(move || {
+        let mut entries: Option<Vec<SegmentSummary>> = None;
+        loop {
+            std::thread::sleep(CHECK_INTERVAL);
+
+            let watching = events.receiver_count() > 0;
+            if !watching {
+                entries = None;
+                if !serial {
+                    continue;
+                }
+            }
+
+            let (answers, listing) = {
+                let _guard = lock.lock().expect("Failed to lock API mutex");
+                let answers = !serial || device_answers(&interface_type);
+                let listing = if answers && watching {
+                    list_entries(&interface_type)
+                } else {
+                    None
+                };
+                (answers, listing)
+            };
+
+            if !answers && auto_exit {
+                std::process::exit(1);
+            }
+
+            let now = unix_now();
+            let device_event = if serial {
+                let mut state = state.lock().expect("Failed to lock device state");
+                transition(&mut state, answers, now)
+            } else {
+                None
+            };
+
+            if let Some(event) = &device_event {
+                match event {
+                    ApiEvent::DeviceDisconnected { .. } => {
+                        eprintln!("warning: device disconnected; waiting for it to reconnect")
+                    }
+                    _ => eprintln!("Device reconnected"),
+                }
+                if print_device_events {
+                    if let Ok(line) = serde_json::to_string(event) {
+                        println!("{line}");
+                    }
+                }
+            }
+
+            let mut published: Vec<ApiEvent> = device_event.into_iter().collect();
+            if let Some(listing) = listing {
+                if let Some(prev) = &entries {
+                    published.extend(diff_entries(prev, &listing, now));
+                }
+                entries = Some(listing);
+            }
+
+            for event in published {
+                // No subscribers is not an error.
+                let _ = events.send(event);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transitions_publish_once_per_change() {
+        let mut state = DeviceState::Connected;
+
+        assert_eq!(transition(&mut state, true, 1), None);
+        assert_eq!(
+            transition(&mut state, false, 2),
+            Some(ApiEvent::DeviceDisconnected { at: 2 })
+        );
+        assert_eq!(transition(&mut state, false, 3), None);
+        assert_eq!(state, DeviceState::Disconnected { since: 2 });
+        assert_eq!(
+            transition(&mut state, true, 4),
+            Some(ApiEvent::DeviceConnected { at: 4 })
+        );
+        assert_eq!(state, DeviceState::Connected);
+    }
+}
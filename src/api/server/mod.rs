@@ -1,56 +1,167 @@
-use std::sync::{Arc, Mutex};
+use std::{
+    net::IpAddr,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
 
-use rocket::{config::Config, figment::Profile, Build, Rocket};
-use rocket_cors::CorsOptions;
+use rocket::{
+    config::{Config, TlsConfig},
+    figment::Profile,
+    Build, Rocket,
+};
 
-use crate::cli::CorsPolicy;
-use crate::data_mgr::{DataInterfaceType, DataManager};
+use crate::cli::{CliArgs, CorsPolicy};
+use tokio::sync::broadcast;
 
+use crate::data_mgr::DataInterfaceType;
+use crate::pass_mgr::{PasswordManager, RateLimitPolicy};
+use crate::protocol::events::ApiEvent;
+
+mod auth;
+mod cors;
+mod device;
 mod routes;
+#[cfg(unix)]
+mod unix;
+
+pub use cors::validate_origin;
+use device::DeviceState;
+
+const EVENT_CHANNEL_CAPACITY: usize = 64;
 
 pub struct ApiState {
     pub interface_type: DataInterfaceType,
     pub lock: Arc<Mutex<()>>,
+    pub rate_limit: RateLimitPolicy,
+    pub device: Arc<Mutex<DeviceState>>,
+    pub events: broadcast::Sender<ApiEvent>,
 }
 
-fn start_connection_checking(interface_type: DataInterfaceType, lock: Arc<Mutex<()>>) {
-    std::thread::spawn(move || loop {
-        std::thread::sleep(std::time::Duration::from_secs(1));
-        let _guard = lock.lock().expect("Failed to lock API mutex");
+pub struct ServerOptions {
+    pub auto_exit: bool,
+    /// Print device connect/disconnect events to stdout as JSON lines.
+    pub device_events: bool,
+    pub bind: IpAddr,
+    pub port: u16,
+    pub cors_policy: CorsPolicy,
+    /// Exact origins allowed by CORS (`--cors-origin` or the `.svpi` config).
+    pub cors_origins: Vec<String>,
+    /// PEM certificate chain and private key paths.
+    pub tls: Option<(String, String)>,
+    pub insecure: bool,
+    /// Serve on this Unix socket instead of `bind:port`.
+    pub unix_socket: Option<PathBuf>,
+    pub allowed_uids: Vec<u32>,
+    /// Do not mount the GET routes (which take passwords in the query string).
+    pub post_only: bool,
+    pub rate_limit: RateLimitPolicy,
+}
 
-        if !matches!(interface_type, DataInterfaceType::SerialPort) {
-            continue;
+impl ServerOptions {
+    pub fn from_cli(cli: &CliArgs) -> Result<Self, String> {
+        let unix_socket = match cli.listen.as_deref() {
+            None => None,
+            Some(addr) => match addr.strip_prefix("unix:") {
+                Some(path) if !path.is_empty() => Some(PathBuf::from(path)),
+                _ => {
+                    return Err(format!(
+                        "invalid --listen value '{addr}' (expected unix:/path/to/socket)"
+                    ))
+                }
+            },
+        };
+        if !cli.allowed_uid.is_empty() && unix_socket.is_none() {
+            return Err("--allowed-uid requires --listen=unix:/path".to_string());
         }
 
-        let Ok(mut data_mgr) = interface_type.load_data_manager() else {
-            std::process::exit(1);
+        let cors_origins = if cli.cors_origin.is_empty() {
+            crate::config::SvpiConfig::load_from_cwd()
+                .ok()
+                .flatten()
+                .map(|cfg| cfg.cors_origins)
+                .unwrap_or_default()
+        } else {
+            cli.cors_origin.clone()
         };
 
-        match &mut data_mgr {
-            DataManager::SerialPort(spdm) => {
-                if let Ok(res) = spdm.test(b"test") {
-                    if res.as_slice() == b"test" {
-                        continue;
-                    }
-                }
-                std::process::exit(1);
-            }
-            DataManager::FileSystem(_) | DataManager::Memory(_) => continue,
-        }
-    });
+        Ok(Self {
+            auto_exit: cli.auto_exit,
+            device_events: cli.device_events,
+            bind: cli.bind,
+            port: cli.port,
+            cors_policy: cli.cors,
+            cors_origins,
+            tls: cli.tls_cert.clone().zip(cli.tls_key.clone()),
+            insecure: cli.insecure,
+            unix_socket,
+            allowed_uids: cli.allowed_uid.clone(),
+            post_only: cli.post_only,
+            rate_limit: cli.rate_limit_policy(),
+        })
+    }
+}
+
+/// Plain HTTP is only served on loopback unless explicitly allowed with `--insecure`.
+fn check_transport_security(bind: IpAddr, tls: bool, insecure: bool) -> Result<(), String> {
+    if tls || bind.is_loopback() {
+        return Ok(());
+    }
+    if insecure {
+        eprintln!("warning: serving plain HTTP on {bind}; tokens and secrets are sent unencrypted");
+        return Ok(());
+    }
+    Err(format!(
+        "refusing to serve plain HTTP on non-loopback address {bind}; pass --tls-cert/--tls-key (see `svpi gen-cert`) or --insecure"
+    ))
 }
 
 pub fn api_server(
     interface_type: DataInterfaceType,
-    auto_exit: bool,
-    bind: std::net::IpAddr,
-    port: u16,
-    cors_policy: CorsPolicy,
-) -> Rocket<Build> {
-    println!("Starting SVPI Server on {bind}:{port}");
+    options: ServerOptions,
+) -> Result<Rocket<Build>, String> {
+    let ServerOptions {
+        auto_exit,
+        device_events,
+        bind,
+        port,
+        cors_policy,
+        cors_origins,
+        tls,
+        insecure,
+        unix_socket,
+        allowed_uids: _,
+        post_only,
+        rate_limit,
+    } = options;
+
+    if unix_socket.is_none() {
+        check_transport_security(bind, tls.is_some(), insecure)?;
+    }
+
+    let tls = match tls {
+        Some((cert, key)) => {
+            for path in [&cert, &key] {
+                if !Path::new(path).is_file() {
+                    return Err(format!("TLS file not found: {path}"));
+                }
+            }
+            Some(TlsConfig::from_paths(cert, key))
+        }
+        None => None,
+    };
+
+    let cors = cors::cors_fairing(cors_policy, &cors_origins)?;
+
+    if unix_socket.is_none() {
+        let scheme = if tls.is_some() { "https" } else { "http" };
+        println!("Starting SVPI Server on {scheme}://{bind}:{port}");
+    }
+    if !cors_origins.is_empty() {
+        println!("CORS origins: {}", cors_origins.join(", "));
+    }
     if matches!(cors_policy, CorsPolicy::AllowAll) {
-        if matches!(bind, std::net::IpAddr::V4(ip) if ip.is_unspecified())
-            || matches!(bind, std::net::IpAddr::V6(ip) if ip.is_unspecified())
+        if matches!(bind, IpAddr::V4(ip) if ip.is_unspecified())
+            || matches!(bind, IpAddr::V6(ip) if ip.is_unspecified())
         {
             eprintln!("warning: --cors=allow-all with an unspecified bind address exposes your vault over the network");
         } else if bind.is_loopback() {
@@ -68,28 +179,86 @@ pub fn api_server(
         port,
         workers: 1,
         max_blocking: 1,
+        tls,
         ..Default::default()
     };
 
+    match PasswordManager::try_load(interface_type.clone())
+        .map(|mut mgr| mgr.load_api_tokens().map(|tokens| tokens.is_empty()))
+    {
+        Ok(Ok(false)) => {}
+        Ok(Ok(true)) => eprintln!(
+            "warning: no API tokens in this vault; every request will be rejected (create one with `svpi token create <name> --scope=...`)"
+        ),
+        _ => eprintln!("warning: could not read API tokens from the vault"),
+    }
+
     let lock = Arc::new(Mutex::new(()));
+    let device = Arc::new(Mutex::new(DeviceState::Connected));
+    let (events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
 
-    if auto_exit {
-        start_connection_checking(interface_type.clone(), lock.clone());
-    }
+    device::start_monitor(
+        interface_type.clone(),
+        lock.clone(),
+        device.clone(),
+        events.clone(),
+        auto_exit,
+        device_events,
+    );
 
     let mut rocket = rocket::custom(config)
         .manage(ApiState {
             interface_type,
             lock,
+            rate_limit,
+            device,
+            events,
         })
-        .mount("/", routes::route());
+        .mount("/", routes::route(post_only));
 
-    if matches!(cors_policy, CorsPolicy::AllowAll) {
-        let cors = CorsOptions::default()
-            .to_cors()
-            .expect("Error creating CORS options");
+    if let Some(cors) = cors {
         rocket = rocket.attach(cors);
     }
 
-    rocket
+    Ok(rocket)
+}
+
+pub async fn run_server(
+    interface_type: DataInterfaceType,
+    options: ServerOptions,
+) -> Result<(), String> {
+    let unix_socket = options.unix_socket.clone();
+    let allowed_uids = options.allowed_uids.clone();
+    let rocket = api_server(interface_type, options)?;
+
+    match unix_socket {
+        #[cfg(unix)]
+        Some(path) => unix::serve_unix(rocket, path, allowed_uids).await,
+        #[cfg(not(unix))]
+        Some(_) => {
+            let _ = (rocket, allowed_uids);
+            Err("Unix sockets are not supported on this platform".to_string())
+        }
+        None => rocket
+            .launch()
+            .await
+            .map(|_| ())
+            .map_err(|err| format!("Failed to start API server: {err}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_http_only_on_loopback_unless_insecure() {
+        let loopback: IpAddr = "127.0.0.1".parse().unwrap();
+        let any: IpAddr = "0.0.0.0".parse().unwrap();
+
+        assert!(check_transport_security(loopback, false, false).is_ok());
+        assert!(check_transport_security(any, false, false).is_err());
+        assert!(check_transport_security(any, false, true).is_ok());
+        assert!(check_transport_security(any, true, false).is_ok());
+    }
 }
@@ -0,0 +1,33 @@
+use rocket::{post, serde::json::Json, State};
+
+use crate::{
+    api::server::{
+        auth::{authorize, ApiAuth, ApiAuthError},
+        ApiState,
+    },
+    protocol::api::{self, ChangeDataTypeRequest},
+    utils::response::SvpiResponse,
+};
+
+#[post("/change-data-type", format = "json", data = "<req>")]
+pub fn change_data_type(
+    state: &State<ApiState>,
+    auth: Result<ApiAuth, ApiAuthError>,
+    req: Json<ChangeDataTypeRequest>,
+) -> Json<SvpiResponse> {
+    if let Err(resp) = authorize(
+        auth,
+        api::cmd(api::ApiTransport::Server, "change-data-type"),
+        |token| token.allows_write(&req.name),
+    ) {
+        return Json(*resp);
+    }
+
+    let state = state.inner();
+    let _guard = state.lock.lock().expect("Failed to lock API mutex");
+    Json(api::change_data_type(
+        api::ApiTransport::Server,
+        state.interface_type.clone(),
+        req.into_inner(),
+    ))
+}
@@ -0,0 +1,34 @@
+use rocket::{post, serde::json::Json, State};
+
+use crate::{
+    api::server::{
+        auth::{authorize, ApiAuth, ApiAuthError},
+        ApiState,
+    },
+    protocol::api::{self, ChangePasswordRequest, CommandRequest},
+    utils::response::SvpiResponse,
+};
+
+#[post("/change-password", format = "json", data = "<req>")]
+pub fn change_password(
+    state: &State<ApiState>,
+    auth: Result<ApiAuth, ApiAuthError>,
+    req: Json<ChangePasswordRequest>,
+) -> Json<SvpiResponse> {
+    if let Err(resp) = authorize(
+        auth,
+        api::cmd(api::ApiTransport::Server, "change-password"),
+        |token| token.allows_write(&req.name),
+    ) {
+        return Json(*resp);
+    }
+
+    let state = state.inner();
+    let _guard = state.lock.lock().expect("Failed to lock API mutex");
+    Json(api::handle_limited(
+        api::ApiTransport::Server,
+        state.interface_type.clone(),
+        CommandRequest::ChangePassword(req.into_inner()),
+        &state.rate_limit,
+    ))
+}
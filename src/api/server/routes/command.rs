@@ -0,0 +1,52 @@
+use rocket::{post, serde::json::Json, State};
+
+use crate::{
+    api::server::{
+        auth::{authorize, request_allowed, ApiAuth, ApiAuthError},
+        device::disconnected_response,
+        ApiState,
+    },
+    protocol::api::{self, CommandRequest},
+    utils::response::SvpiResponse,
+};
+
+#[post("/api", format = "json", data = "<req>")]
+pub fn command(
+    state: &State<ApiState>,
+    auth: Result<ApiAuth, ApiAuthError>,
+    req: Json<CommandRequest>,
+) -> Json<SvpiResponse> {
+    let req = req.into_inner();
+    let state = state.inner();
+    let _guard = state.lock.lock().expect("Failed to lock API mutex");
+
+    // A token that may read an alias must also be allowed to read its target.
+    let target = match &req {
+        CommandRequest::GetData(get) => api::alias_target(state.interface_type.clone(), &get.name),
+        _ => None,
+    };
+    if let Err(resp) = authorize(
+        auth,
+        api::cmd(api::ApiTransport::Server, req.command_name()),
+        |token| {
+            request_allowed(token, &req) && target.as_deref().is_none_or(|t| token.allows_read(t))
+        },
+    ) {
+        return Json(*resp);
+    }
+
+    if matches!(req, CommandRequest::Status {}) {
+        if let Some(resp) =
+            disconnected_response(&state.device, api::cmd(api::ApiTransport::Server, "status"))
+        {
+            return Json(resp);
+        }
+    }
+
+    Json(api::handle_limited(
+        api::ApiTransport::Server,
+        state.interface_type.clone(),
+        req,
+        &state.rate_limit,
+    ))
+}
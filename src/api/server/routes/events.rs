@@ -0,0 +1,47 @@
+use rocket::{
+    get,
+    response::stream::{Event, EventStream},
+    serde::json::Json,
+    tokio::{select, sync::broadcast::error::RecvError},
+    Shutdown, State,
+};
+
+use crate::{
+    api::server::{
+        auth::{authorize, ApiAuth, ApiAuthError},
+        ApiState,
+    },
+    protocol::api,
+    utils::response::SvpiResponse,
+};
+
+/// Server-Sent Events stream of `ApiEvent`s; each SSE event is named after
+/// the `event` field of its JSON data.
+#[get("/events")]
+pub fn events(
+    state: &State<ApiState>,
+    auth: Result<ApiAuth, ApiAuthError>,
+    mut shutdown: Shutdown,
+) -> Result<EventStream![], Json<Box<SvpiResponse>>> {
+    authorize(
+        auth,
+        api::cmd(api::ApiTransport::Server, "events"),
+        |token| token.allows_list(),
+    )
+    .map_err(Json)?;
+
+    let mut rx = state.events.subscribe();
+    Ok(EventStream! {
+        loop {
+            let event = select! {
+                msg = rx.recv() => match msg {
+                    Ok(event) => event,
+                    Err(RecvError::Closed) => break,
+                    Err(RecvError::Lagged(_)) => continue,
+                },
+                _ = &mut shutdown => break,
+            };
+            yield Event::json(&event).event(event.name());
+        }
+    })
+}
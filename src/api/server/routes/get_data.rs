@@ -1,8 +1,11 @@
 use rocket::{form::FromForm, get, serde::json::Json, State};
 
 use crate::{
-    api::server::ApiState,
-    protocol::api::{self, GetDataRequest},
+    api::server::{
+        auth::{authorize, ApiAuth, ApiAuthError},
+        ApiState,
+    },
+    protocol::api::{self, CommandRequest, GetDataRequest},
     utils::response::SvpiResponse,
 };
 
@@ -10,18 +13,35 @@ use crate::{
 pub struct GetQueryParams {
     name: Option<String>,
     password: Option<String>,
+    field: Option<String>,
+    allow_expired: Option<bool>,
 }
 
 #[get("/get?<params..>")]
-pub fn get_data(state: &State<ApiState>, params: GetQueryParams) -> Json<SvpiResponse> {
+pub fn get_data(
+    state: &State<ApiState>,
+    auth: Result<ApiAuth, ApiAuthError>,
+    params: GetQueryParams,
+) -> Json<SvpiResponse> {
     let state = state.inner();
     let _guard = state.lock.lock().expect("Failed to lock API mutex");
-    Json(api::get_data(
+
+    let name = params.name.as_deref().unwrap_or_default();
+    let target = api::alias_target(state.interface_type.clone(), name);
+    if let Err(resp) = authorize(auth, api::cmd(api::ApiTransport::Server, "get"), |token| {
+        token.allows_read(name) && target.as_deref().is_none_or(|t| token.allows_read(t))
+    }) {
+        return Json(*resp);
+    }
+    Json(api::handle_limited(
         api::ApiTransport::Server,
         state.interface_type.clone(),
-        GetDataRequest {
+        CommandRequest::GetData(GetDataRequest {
             name: params.name.unwrap_or_default(),
             password: params.password,
-        },
+            field: params.field,
+            allow_expired: params.allow_expired.unwrap_or(false),
+        }),
+        &state.rate_limit,
     ))
 }
@@ -1,12 +1,91 @@
-use crate::{api::server::ApiState, protocol::api, utils::response::SvpiResponse};
-use rocket::{get, serde::json::Json, State};
+use std::str::FromStr;
+
+use crate::{
+    api::server::{
+        auth::{authorize, ApiAuth, ApiAuthError},
+        ApiState,
+    },
+    protocol::{
+        api,
+        segments::{ListFilter, ListSort},
+    },
+    seg_mgr::DataType,
+    utils::response::SvpiResponse,
+};
+use clap::ValueEnum;
+use rocket::{form::FromForm, get, serde::json::Json, State};
+
+#[derive(FromForm)]
+pub struct ListQueryParams {
+    pattern: Option<String>,
+    regex: Option<bool>,
+    data_type: Option<String>,
+    encrypted: Option<bool>,
+    key: Option<String>,
+    min_size: Option<u32>,
+    max_size: Option<u32>,
+    tag: Vec<String>,
+    folder: Option<String>,
+    sort: Option<String>,
+    desc: Option<bool>,
+}
+
+impl ListQueryParams {
+    /// Errors carry the name of the offending parameter.
+    fn into_filter(self) -> Result<ListFilter, (&'static str, String)> {
+        let data_type = match self.data_type.as_deref() {
+            Some(value) => Some(
+                DataType::from_str(value)
+                    .map_err(|_| ("data_type", format!("Unknown data type '{value}'")))?,
+            ),
+            None => None,
+        };
+        let sort = match self.sort.as_deref() {
+            Some(value) => Some(
+                ListSort::from_str(value, true)
+                    .map_err(|_| ("sort", format!("Unknown sort order '{value}'")))?,
+            ),
+            None => None,
+        };
+
+        Ok(ListFilter {
+            pattern: self.pattern,
+            regex: self.regex.unwrap_or_default(),
+            data_type,
+            encrypted: self.encrypted,
+            key: self.key,
+            min_size: self.min_size,
+            max_size: self.max_size,
+            tags: self.tag,
+            folder: self.folder,
+            sort,
+            desc: self.desc.unwrap_or_default(),
+        })
+    }
+}
+
+#[get("/list?<params..>")]
+pub fn list(
+    state: &State<ApiState>,
+    auth: Result<ApiAuth, ApiAuthError>,
+    params: ListQueryParams,
+) -> Json<SvpiResponse> {
+    let command = api::cmd(api::ApiTransport::Server, "list");
+    if let Err(resp) = authorize(auth, command.clone(), |token| token.allows_list()) {
+        return Json(*resp);
+    }
+    let filter = match params.into_filter() {
+        Ok(v) => v,
+        Err((name, message)) => {
+            return Json(SvpiResponse::invalid_argument(command, name, message))
+        }
+    };
 
-#[get("/list")]
-pub fn list(state: &State<ApiState>) -> Json<SvpiResponse> {
     let state = state.inner();
     let _guard = state.lock.lock().expect("Failed to lock API mutex");
     Json(api::list(
         api::ApiTransport::Server,
         state.interface_type.clone(),
+        filter,
     ))
 }
@@ -0,0 +1,30 @@
+use crate::{
+    api::server::{
+        auth::{authorize, ApiAuth, ApiAuthError},
+        ApiState,
+    },
+    protocol::api,
+    utils::response::SvpiResponse,
+};
+use rocket::{get, serde::json::Json, State};
+
+#[get("/keys")]
+pub fn list_keys(
+    state: &State<ApiState>,
+    auth: Result<ApiAuth, ApiAuthError>,
+) -> Json<SvpiResponse> {
+    if let Err(resp) = authorize(
+        auth,
+        api::cmd(api::ApiTransport::Server, "list-keys"),
+        |token| token.allows_list(),
+    ) {
+        return Json(*resp);
+    }
+
+    let state = state.inner();
+    let _guard = state.lock.lock().expect("Failed to lock API mutex");
+    Json(api::list_keys(
+        api::ApiTransport::Server,
+        state.interface_type.clone(),
+    ))
+}
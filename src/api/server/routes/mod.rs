@@ -1,9 +1,36 @@
 use rocket::{routes, Route};
 
+mod change_data_type;
+mod change_password;
+mod command;
+mod events;
 mod get_data;
 mod list;
+mod list_keys;
+mod remove_data;
+mod rename_data;
+mod set_data;
 mod status;
 
-pub fn route() -> Vec<Route> {
-    routes![status::status, list::list, get_data::get_data]
+/// `post_only` leaves out the GET routes that may carry a secret in their URL
+/// (`/events` stays: `EventSource` can only send GET).
+pub fn route(post_only: bool) -> Vec<Route> {
+    let mut routes = routes![
+        command::command,
+        events::events,
+        set_data::set_data,
+        remove_data::remove_data,
+        rename_data::rename_data,
+        change_data_type::change_data_type,
+        change_password::change_password,
+    ];
+    if !post_only {
+        routes.extend(routes![
+            status::status,
+            list::list,
+            list_keys::list_keys,
+            get_data::get_data,
+        ]);
+    }
+    routes
 }
@@ -0,0 +1,33 @@
+use rocket::{post, serde::json::Json, State};
+
+use crate::{
+    api::server::{
+        auth::{authorize, ApiAuth, ApiAuthError},
+        ApiState,
+    },
+    protocol::api::{self, RemoveDataRequest},
+    utils::response::SvpiResponse,
+};
+
+#[post("/remove", format = "json", data = "<req>")]
+pub fn remove_data(
+    state: &State<ApiState>,
+    auth: Result<ApiAuth, ApiAuthError>,
+    req: Json<RemoveDataRequest>,
+) -> Json<SvpiResponse> {
+    if let Err(resp) = authorize(
+        auth,
+        api::cmd(api::ApiTransport::Server, "remove"),
+        |token| token.allows_write(&req.name),
+    ) {
+        return Json(*resp);
+    }
+
+    let state = state.inner();
+    let _guard = state.lock.lock().expect("Failed to lock API mutex");
+    Json(api::remove_data(
+        api::ApiTransport::Server,
+        state.interface_type.clone(),
+        req.into_inner(),
+    ))
+}
@@ -0,0 +1,33 @@
+use rocket::{post, serde::json::Json, State};
+
+use crate::{
+    api::server::{
+        auth::{authorize, ApiAuth, ApiAuthError},
+        ApiState,
+    },
+    protocol::api::{self, RenameDataRequest},
+    utils::response::SvpiResponse,
+};
+
+#[post("/rename", format = "json", data = "<req>")]
+pub fn rename_data(
+    state: &State<ApiState>,
+    auth: Result<ApiAuth, ApiAuthError>,
+    req: Json<RenameDataRequest>,
+) -> Json<SvpiResponse> {
+    if let Err(resp) = authorize(
+        auth,
+        api::cmd(api::ApiTransport::Server, "rename"),
+        |token| token.allows_write(&req.old_name) && token.allows_write(&req.new_name),
+    ) {
+        return Json(*resp);
+    }
+
+    let state = state.inner();
+    let _guard = state.lock.lock().expect("Failed to lock API mutex");
+    Json(api::rename_data(
+        api::ApiTransport::Server,
+        state.interface_type.clone(),
+        req.into_inner(),
+    ))
+}
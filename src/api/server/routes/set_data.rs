@@ -0,0 +1,31 @@
+use rocket::{post, serde::json::Json, State};
+
+use crate::{
+    api::server::{
+        auth::{authorize, ApiAuth, ApiAuthError},
+        ApiState,
+    },
+    protocol::api::{self, SetDataRequest},
+    utils::response::SvpiResponse,
+};
+
+#[post("/set", format = "json", data = "<req>")]
+pub fn set_data(
+    state: &State<ApiState>,
+    auth: Result<ApiAuth, ApiAuthError>,
+    req: Json<SetDataRequest>,
+) -> Json<SvpiResponse> {
+    if let Err(resp) = authorize(auth, api::cmd(api::ApiTransport::Server, "set"), |token| {
+        token.allows_write(&req.name)
+    }) {
+        return Json(*resp);
+    }
+
+    let state = state.inner();
+    let _guard = state.lock.lock().expect("Failed to lock API mutex");
+    Json(api::set_data(
+        api::ApiTransport::Server,
+        state.interface_type.clone(),
+        req.into_inner(),
+    ))
+}
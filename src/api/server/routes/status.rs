@@ -1,10 +1,30 @@
 use rocket::{get, serde::json::Json, State};
 
-use crate::{api::server::ApiState, protocol::api, utils::response::SvpiResponse};
+use crate::{
+    api::server::{
+        auth::{authorize, ApiAuth, ApiAuthError},
+        device::disconnected_response,
+        ApiState,
+    },
+    protocol::api,
+    utils::response::SvpiResponse,
+};
 
 #[get("/status")]
-pub fn status(state: &State<ApiState>) -> Json<SvpiResponse> {
+pub fn status(state: &State<ApiState>, auth: Result<ApiAuth, ApiAuthError>) -> Json<SvpiResponse> {
+    if let Err(resp) = authorize(auth, api::cmd(api::ApiTransport::Server, "status"), |_| {
+        true
+    }) {
+        return Json(*resp);
+    }
+
     let state = state.inner();
+    if let Some(resp) =
+        disconnected_response(&state.device, api::cmd(api::ApiTransport::Server, "status"))
+    {
+        return Json(resp);
+    }
+
     let _guard = state.lock.lock().expect("Failed to lock API mutex");
     Json(api::status(
         api::ApiTransport::Server,
@@ -0,0 +1,301 @@
+use std::{
+    convert::Infallible,
+    fs,
+    io::ErrorKind,
+    os::unix::fs::{DirBuilderExt, FileTypeExt, MetadataExt, PermissionsExt},
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+};
+
+use hyper::{
+    body::{Bytes, HttpBody},
+    server::conn::Http,
+    service::service_fn,
+    Body, Request, Response, StatusCode,
+};
+use rocket::{
+    http::{Header, Method},
+    local::asynchronous::Client,
+    Build, Rocket,
+};
+use tokio::{
+    io::AsyncReadExt,
+    net::{UnixListener, UnixStream},
+    sync::oneshot,
+};
+
+const MAX_BODY_SIZE: usize = 1024 * 1024;
+const RESPONSE_CHUNK_SIZE: usize = 8 * 1024;
+
+/// Removes a socket file left behind by a previous server. A socket that still
+/// accepts connections belongs to a running server and is left alone.
+async fn remove_stale_socket(path: &Path) -> Result<(), String> {
+    let meta = match fs::symlink_metadata(path) {
+        Ok(v) => v,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(format!("{}: {err}", path.display())),
+    };
+
+    if !meta.file_type().is_socket() {
+        return Err(format!(
+            "{} exists and is not a socket; refusing to replace it",
+            path.display()
+        ));
+    }
+
+    match UnixStream::connect(path).await {
+        Ok(_) => Err(format!(
+            "{} is in use by another running server",
+            path.display()
+        )),
+        Err(_) => fs::remove_file(path).map_err(|err| format!("{}: {err}", path.display())),
+    }
+}
+
+fn plain_response(status: StatusCode, message: &'static str) -> Response<Body> {
+    let mut resp = Response::new(Body::from(message));
+    *resp.status_mut() = status;
+    resp
+}
+
+/// Forwards one HTTP request to the Rocket instance, so the socket serves
+/// exactly the same routes, guards and fairings as the TCP listener. The body
+/// is streamed, so long-lived responses such as `/events` work too.
+async fn dispatch(client: Arc<Client>, req: Request<Body>) -> Result<Response<Body>, Infallible> {
+    let (parts, mut body) = req.into_parts();
+
+    let mut data = Vec::new();
+    while let Some(chunk) = body.data().await {
+        let Ok(chunk) = chunk else {
+            return Ok(plain_response(StatusCode::BAD_REQUEST, "Bad request body"));
+        };
+        if data.len() + chunk.len() > MAX_BODY_SIZE {
+            return Ok(plain_response(
+                StatusCode::PAYLOAD_TOO_LARGE,
+                "Request body too large",
+            ));
+        }
+        data.extend_from_slice(&chunk);
+    }
+
+    let Ok(method) = Method::from_str(parts.method.as_str()) else {
+        return Ok(plain_response(
+            StatusCode::METHOD_NOT_ALLOWED,
+            "Method not allowed",
+        ));
+    };
+    let uri = parts
+        .uri
+        .path_and_query()
+        .map(|pq| pq.as_str().to_string())
+        .unwrap_or_else(|| "/".to_string());
+
+    let headers: Vec<(String, String)> = parts
+        .headers
+        .iter()
+        .filter_map(|(name, value)| {
+            let value = value.to_str().ok()?;
+            Some((name.as_str().to_string(), value.to_string()))
+        })
+        .collect();
+
+    let (head_tx, head_rx) = oneshot::channel();
+    let (mut body_tx, body) = Body::channel();
+    tokio::spawn(async move {
+        let mut local = client.req(method, uri.as_str());
+        for (name, value) in headers {
+            local.add_header(Header::new(name, value));
+        }
+
+        let mut local_resp = local.body(data).dispatch().await;
+        let head: Vec<(String, String)> = local_resp
+            .headers()
+            .iter()
+            .map(|h| (h.name().as_str().to_string(), h.value().to_string()))
+            .collect();
+        if head_tx.send((local_resp.status().code, head)).is_err() {
+            return;
+        }
+
+        let mut buf = vec![0u8; RESPONSE_CHUNK_SIZE];
+        loop {
+            match local_resp.read(&mut buf).await {
+                Ok(0) | Err(_) => break,
+                Ok(n) => {
+                    // The peer went away.
+                    if body_tx
+                        .send_data(Bytes::copy_from_slice(&buf[..n]))
+                        .await
+                        .is_err()
+                    {
+                        break;
+                    }
+                }
+            }
+        }
+    });
+
+    let Ok((status, head)) = head_rx.await else {
+        return Ok(plain_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Request failed",
+        ));
+    };
+    let mut builder = Response::builder().status(status);
+    for (name, value) in head {
+        builder = builder.header(name, value);
+    }
+
+    Ok(builder
+        .body(body)
+        .unwrap_or_else(|_| plain_response(StatusCode::INTERNAL_SERVER_ERROR, "Invalid response")))
+}
+
+/// Binds `path` with mode `0600` from the start: the socket is created inside
+/// a fresh `0700` directory, so nobody else can connect before the mode is set,
+/// and then renamed into place.
+fn bind_private(path: &Path) -> Result<UnixListener, String> {
+    let Some(file_name) = path.file_name() else {
+        return Err(format!("{}: not a socket path", path.display()));
+    };
+    let parent = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let staging = parent.join(format!(
+        ".{}.{}",
+        file_name.to_string_lossy(),
+        std::process::id()
+    ));
+    fs::DirBuilder::new()
+        .mode(0o700)
+        .create(&staging)
+        .map_err(|err| format!("{}: {err}", staging.display()))?;
+
+    let staged = staging.join(file_name);
+    let result = UnixListener::bind(&staged).and_then(|listener| {
+        fs::set_permissions(&staged, fs::Permissions::from_mode(0o600))?;
+        fs::rename(&staged, path)?;
+        Ok(listener)
+    });
+    let _ = fs::remove_file(&staged);
+    let _ = fs::remove_dir(&staging);
+    result.map_err(|err| format!("{}: {err}", path.display()))
+}
+
+/// UID the server runs as: the owner of the socket it just bound.
+fn socket_owner(path: &Path) -> Result<u32, String> {
+    fs::symlink_metadata(path)
+        .map(|meta| meta.uid())
+        .map_err(|err| format!("{}: {err}", path.display()))
+}
+
+fn peer_allowed(stream: &UnixStream, allowed_uids: &[u32]) -> bool {
+    match stream.peer_cred() {
+        Ok(cred) => allowed_uids.contains(&cred.uid()),
+        Err(_) => false,
+    }
+}
+
+pub async fn serve_unix(
+    rocket: Rocket<Build>,
+    path: PathBuf,
+    allowed_uids: Vec<u32>,
+) -> Result<(), String> {
+    remove_stale_socket(&path).await?;
+
+    let client = Client::untracked(rocket)
+        .await
+        .map_err(|err| format!("Failed to start API server: {err}"))?;
+    let client = Arc::new(client);
+
+    let listener = bind_private(&path)?;
+    // Without --allowed-uid only the server's own user may connect.
+    let allowed_uids = if allowed_uids.is_empty() {
+        match socket_owner(&path) {
+            Ok(uid) => vec![uid],
+            Err(err) => {
+                let _ = fs::remove_file(&path);
+                return Err(err);
+            }
+        }
+    } else {
+        allowed_uids
+    };
+
+    println!("Starting SVPI Server on unix:{}", path.display());
+
+    let result = loop {
+        let (stream, _) = tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok(v) => v,
+                Err(err) => break Err(format!("Accept error: {err}")),
+            },
+            _ = tokio::signal::ctrl_c() => break Ok(()),
+        };
+
+        if !peer_allowed(&stream, &allowed_uids) {
+            // Dropping the stream closes the connection without a response.
+            continue;
+        }
+
+        let client = client.clone();
+        tokio::spawn(async move {
+            let service = service_fn(move |req| dispatch(client.clone(), req));
+            let _ = Http::new()
+                .http1_only(true)
+                .serve_connection(stream, service)
+                .await;
+        });
+    };
+
+    let _ = fs::remove_file(&path);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn stale_socket_is_removed_but_live_socket_is_kept() {
+        let dir = std::env::temp_dir().join(format!("svpi-unix-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("api.sock");
+        let _ = fs::remove_file(&path);
+
+        let listener = UnixListener::bind(&path).unwrap();
+        assert!(remove_stale_socket(&path).await.is_err());
+
+        drop(listener);
+        assert!(remove_stale_socket(&path).await.is_ok());
+        assert!(!path.exists());
+
+        fs::write(&path, b"not a socket").unwrap();
+        assert!(remove_stale_socket(&path).await.is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn socket_is_bound_private() {
+        let dir = std::env::temp_dir().join(format!("svpi-unix-bind-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("api.sock");
+        let _ = fs::remove_file(&path);
+
+        let listener = bind_private(&path).unwrap();
+        let meta = fs::symlink_metadata(&path).unwrap();
+        assert!(meta.file_type().is_socket());
+        assert_eq!(meta.permissions().mode() & 0o777, 0o600);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+        let _client = UnixStream::connect(&path).await.unwrap();
+        let (stream, _) = listener.accept().await.unwrap();
+        assert!(peer_allowed(&stream, &[socket_owner(&path).unwrap()]));
+        assert!(!peer_allowed(&stream, &[]));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,9 +1,14 @@
 use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer};
 
 use crate::{
     data_mgr::DataInterfaceType,
-    seg_mgr::{DataType, EncryptionLevel},
-    utils::response::OutputFormat,
+    pass_mgr::{RateLimitPolicy, DEFAULT_EXPIRY_WARNING_DAYS, DEFAULT_STALE_DAYS},
+    protocol::segments::{ListFilter, ListSort},
+    seg_mgr::{DataType, EncryptionLevel, OtpAlgorithm},
+    utils::{generator::CharClass, native_host::Browser, response::OutputFormat},
 };
 
 /// Secure Vault Personal Information (SVPI)
@@ -45,10 +50,17 @@ pub struct CliArgs {
     #[arg(
         long = "auto-exit",
         global = true,
-        help = "Exit API server after device disconnection (server mode)"
+        help = "Exit API server after device disconnection instead of waiting for it to reconnect (server mode)"
     )]
     pub auto_exit: bool,
 
+    #[arg(
+        long = "device-events",
+        global = true,
+        help = "Print device connect/disconnect events as JSON lines (server mode)"
+    )]
+    pub device_events: bool,
+
     #[arg(
         long = "bind",
         global = true,
@@ -80,6 +92,88 @@ pub struct CliArgs {
     )]
     pub cors: CorsPolicy,
 
+    #[arg(
+        long = "cors-origin",
+        global = true,
+        require_equals = true,
+        value_name = "ORIGIN",
+        help = "Allow CORS requests from this exact origin, e.g. http://localhost:5173 (repeatable; server mode)"
+    )]
+    pub cors_origin: Vec<String>,
+
+    #[arg(
+        long = "tls-cert",
+        global = true,
+        require_equals = true,
+        requires = "tls_key",
+        value_name = "FILE",
+        help = "PEM certificate chain; serve HTTPS (server mode)"
+    )]
+    pub tls_cert: Option<String>,
+
+    #[arg(
+        long = "tls-key",
+        global = true,
+        require_equals = true,
+        requires = "tls_cert",
+        value_name = "FILE",
+        help = "PEM private key for --tls-cert (server mode)"
+    )]
+    pub tls_key: Option<String>,
+
+    #[arg(
+        long = "insecure",
+        global = true,
+        help = "Allow plain HTTP on a non-loopback --bind (server mode)"
+    )]
+    pub insecure: bool,
+
+    #[arg(
+        long = "listen",
+        global = true,
+        require_equals = true,
+        value_name = "ADDR",
+        help = "Serve on a Unix socket instead of TCP, e.g. unix:/run/user/1000/svpi.sock (server mode)"
+    )]
+    pub listen: Option<String>,
+
+    #[arg(
+        long = "allowed-uid",
+        global = true,
+        require_equals = true,
+        value_name = "UID",
+        help = "Only accept Unix socket peers with this UID (repeatable; default: the server's own UID; server mode)"
+    )]
+    pub allowed_uid: Vec<u32>,
+
+    #[arg(
+        long = "post-only",
+        global = true,
+        help = "Disable the GET routes; requests go through POST /api and the POST write routes (server mode)"
+    )]
+    pub post_only: bool,
+
+    #[arg(
+        long = "lockout-after",
+        global = true,
+        require_equals = true,
+        default_value_t = RateLimitPolicy::default().lockout_after,
+        value_parser = clap::value_parser!(u32).range(1..),
+        value_name = "N",
+        help = "Lock an entry out after N failed password attempts (server/chrome mode)"
+    )]
+    pub lockout_after: u32,
+
+    #[arg(
+        long = "lockout-secs",
+        global = true,
+        require_equals = true,
+        default_value_t = RateLimitPolicy::default().lockout_secs,
+        value_name = "SECS",
+        help = "Lockout duration in seconds (server/chrome mode)"
+    )]
+    pub lockout_secs: u64,
+
     #[command(subcommand)]
     pub command: Option<Command>,
 }
@@ -87,11 +181,18 @@ pub struct CliArgs {
 impl CliArgs {
     pub fn output_format(&self) -> OutputFormat {
         match self.mode {
-            Mode::Json => OutputFormat::Json,
+            Mode::Json | Mode::JsonLines => OutputFormat::Json,
             Mode::Cli | Mode::Server | Mode::Chrome => OutputFormat::Cli,
         }
     }
 
+    pub fn rate_limit_policy(&self) -> RateLimitPolicy {
+        RateLimitPolicy {
+            lockout_after: self.lockout_after,
+            lockout_secs: self.lockout_secs,
+        }
+    }
+
     pub fn interface_type(&self) -> DataInterfaceType {
         if let Some(path) = self.file.as_ref() {
             return DataInterfaceType::FileSystem(path.clone());
@@ -113,6 +214,8 @@ pub enum Mode {
     Cli,
     /// JSON responses, no interactive prompts
     Json,
+    /// Newline-delimited JSON requests on stdin, one JSON response per line
+    JsonLines,
     /// HTTP API server (Rocket)
     Server,
     /// Chrome Native Messaging app
@@ -127,7 +230,8 @@ pub enum CorsPolicy {
     AllowAll,
 }
 
-#[derive(Debug, Clone, Subcommand)]
+#[derive(Debug, Clone, Subcommand, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Command {
     #[command(name = "help", alias = "h", about = "Print help message")]
     Help,
@@ -155,6 +259,28 @@ pub enum Command {
         file_name: String,
     },
 
+    #[command(
+        name = "set-cors-origins",
+        about = "Set the API server CORS origins (writes .svpi config; no origins clears them)"
+    )]
+    SetCorsOrigins {
+        #[arg(
+            value_name = "ORIGIN",
+            help = "Exact origin, e.g. chrome-extension://<id>"
+        )]
+        #[serde(default)]
+        origins: Vec<String>,
+    },
+
+    #[command(
+        name = "set-default-type",
+        about = "Choose how `set` (CLI and API) stores text without a type (writes .svpi config)"
+    )]
+    SetDefaultType {
+        #[arg(value_enum, value_name = "TYPE")]
+        data_type: DefaultDataTypeArg,
+    },
+
     #[command(name = "init", alias = "i", about = "Initialize the device memory")]
     Init {
         #[arg(value_name = "MEMORY_SIZE", help = "Device memory size in bytes")]
@@ -166,6 +292,7 @@ pub enum Command {
             value_name = "PROTECTION",
             help = "Dump protection level: low, medium, strong, hardened"
         )]
+        #[serde(default)]
         protection: EncryptionLevelArg,
     },
 
@@ -218,6 +345,7 @@ pub enum Command {
             value_name = "PROTECTION",
             help = "Dump encryption level: low=1, medium=2, strong=3, hardened=4"
         )]
+        #[serde(default)]
         protection: EncryptionLevelArg,
 
         #[arg(
@@ -286,28 +414,502 @@ pub enum Command {
     SyncKeys(SyncKeysArgs),
 
     #[command(name = "list", alias = "l", about = "Print all data list")]
-    List,
+    List(#[serde(deserialize_with = "null_as_default")] ListArgs),
+
+    #[command(
+        name = "set-expiry",
+        about = "Set when an entry expires (\"never\" clears it)"
+    )]
+    SetExpiry {
+        name: String,
+        #[arg(value_name = "DATE", help = "YYYY-MM-DD (UTC) or never")]
+        expires: String,
+    },
+
+    #[command(
+        name = "lock",
+        about = "Protect an entry from overwrite, rename, removal, type and password changes"
+    )]
+    Lock { name: String },
+
+    #[command(
+        name = "unlock",
+        about = "Remove the protection added by lock (asks for the master password)"
+    )]
+    Unlock {
+        name: String,
+        #[arg(
+            long = "master-password",
+            require_equals = true,
+            value_name = "PASSWORD",
+            help = "Master password (required if set)"
+        )]
+        master_password: Option<String>,
+    },
 
     #[command(name = "set", alias = "s", about = "Set data")]
     Set(SetArgs),
 
+    #[command(
+        name = "set-alias",
+        about = "Store a name that reads another entry (follows renames of the target)"
+    )]
+    SetAlias {
+        name: String,
+        #[arg(value_name = "TARGET", help = "Entry name or entry fingerprint")]
+        target: String,
+        #[arg(
+            long = "master-password",
+            require_equals = true,
+            value_name = "PASSWORD",
+            help = "Master password (needed to overwrite a locked entry)"
+        )]
+        master_password: Option<String>,
+    },
+
     #[command(name = "get", alias = "g", about = "Get data")]
     Get(GetArgs),
 
+    #[command(
+        name = "generate",
+        alias = "gen",
+        about = "Generate a password or passphrase and store it (the value is not printed)"
+    )]
+    Generate(GenerateArgs),
+
+    #[command(
+        name = "set-otp",
+        about = "Store a TOTP/HOTP seed (get returns the current code)"
+    )]
+    SetOtp(SetOtpArgs),
+
     #[command(name = "remove", alias = "r", about = "Remove data")]
-    Remove { name: String },
+    Remove {
+        #[arg(value_name = "NAME", help = "Entry name, or a name glob with --match")]
+        name: String,
+        #[arg(
+            long = "match",
+            help = "Remove every entry whose name matches the glob"
+        )]
+        #[serde(default, rename = "match")]
+        matching: bool,
+        #[arg(
+            long = "dry-run",
+            requires = "matching",
+            help = "Only list the affected entries"
+        )]
+        #[serde(default)]
+        dry_run: bool,
+        #[arg(
+            long = "master-password",
+            require_equals = true,
+            value_name = "PASSWORD",
+            help = "Master password (needed to remove a locked entry)"
+        )]
+        master_password: Option<String>,
+    },
 
     #[command(name = "rename", alias = "rn", about = "Rename data")]
-    Rename { old_name: String, new_name: String },
+    Rename {
+        #[arg(
+            value_name = "OLD_NAME",
+            help = "Entry name, or s/pattern/replacement/[gi] with --regex"
+        )]
+        old_name: String,
+        #[arg(
+            value_name = "NEW_NAME",
+            required_unless_present = "regex",
+            conflicts_with = "regex"
+        )]
+        new_name: Option<String>,
+        #[arg(
+            long = "regex",
+            help = "Rename every entry the sed-style expression matches"
+        )]
+        #[serde(default)]
+        regex: bool,
+        #[arg(
+            long = "dry-run",
+            requires = "regex",
+            help = "Only list the affected entries"
+        )]
+        #[serde(default)]
+        dry_run: bool,
+        #[arg(
+            long = "master-password",
+            require_equals = true,
+            value_name = "PASSWORD",
+            help = "Master password (needed to rename a locked entry)"
+        )]
+        master_password: Option<String>,
+    },
 
     #[command(name = "change-data-type", alias = "cdt", about = "Change data type")]
     ChangeDataType(ChangeDataTypeArgs),
 
     #[command(name = "change-password", alias = "cp", about = "Change data password")]
     ChangePassword(ChangePasswordArgs),
+
+    #[command(
+        name = "set-urls",
+        about = "Set the sites an entry belongs to (no URLs clears them)"
+    )]
+    SetUrls {
+        name: String,
+        #[arg(value_name = "URL", help = "URL or domain, e.g. github.com")]
+        #[serde(default)]
+        urls: Vec<String>,
+    },
+
+    #[command(name = "tag", about = "Add tags to an entry")]
+    Tag {
+        name: String,
+        #[arg(value_name = "TAG", required = true)]
+        tags: Vec<String>,
+    },
+
+    #[command(
+        name = "untag",
+        about = "Remove tags from an entry (no tags removes all)"
+    )]
+    Untag {
+        name: String,
+        #[arg(value_name = "TAG")]
+        #[serde(default)]
+        tags: Vec<String>,
+    },
+
+    #[command(
+        name = "mv",
+        about = "Move an entry to a folder (\"/\" is the top level)"
+    )]
+    Mv {
+        #[arg(value_name = "NAME", help = "Entry name, or a name glob with --match")]
+        name: String,
+        #[arg(value_name = "FOLDER", help = "Folder path, e.g. work/github")]
+        folder: String,
+        #[arg(long = "match", help = "Move every entry whose name matches the glob")]
+        #[serde(default, rename = "match")]
+        matching: bool,
+        #[arg(
+            long = "dry-run",
+            requires = "matching",
+            help = "Only list the affected entries"
+        )]
+        #[serde(default)]
+        dry_run: bool,
+    },
+
+    #[command(
+        name = "find-by-url",
+        about = "List entries that belong to a site (names only)"
+    )]
+    FindByUrl { url: String },
+
+    #[command(
+        name = "audit",
+        about = "Report weak, reused and stale secrets and risky entries"
+    )]
+    Audit(AuditArgs),
+
+    #[command(
+        name = "gen-cert",
+        about = "Generate a self-signed TLS certificate for server mode"
+    )]
+    GenCert(GenCertArgs),
+
+    #[command(
+        name = "install-native-host",
+        about = "Register svpi as the browser extension's native messaging host"
+    )]
+    InstallNativeHost(InstallNativeHostArgs),
+
+    #[command(
+        name = "uninstall-native-host",
+        about = "Remove the native messaging host registration"
+    )]
+    UninstallNativeHost(UninstallNativeHostArgs),
+
+    #[command(name = "token", about = "Manage HTTP API tokens", subcommand)]
+    Token(TokenCommand),
+}
+
+#[derive(Debug, Clone, Args, Deserialize)]
+pub struct GenCertArgs {
+    #[arg(value_name = "CERT_FILE", help = "Output certificate path (PEM)")]
+    pub cert_file: String,
+
+    #[arg(value_name = "KEY_FILE", help = "Output private key path (PEM)")]
+    pub key_file: String,
+
+    #[arg(
+        long = "host",
+        require_equals = true,
+        value_name = "HOST",
+        help = "Subject alternative name (repeatable; default: localhost, 127.0.0.1, ::1)"
+    )]
+    #[serde(default)]
+    pub host: Vec<String>,
+}
+
+#[derive(Debug, Clone, Args, Deserialize)]
+pub struct InstallNativeHostArgs {
+    #[arg(long = "browser", require_equals = true, value_name = "BROWSER")]
+    pub browser: Browser,
+
+    #[arg(
+        long = "extension-id",
+        require_equals = true,
+        required = true,
+        value_name = "ID",
+        help = "Extension allowed to connect (repeatable)"
+    )]
+    pub extension_id: Vec<String>,
+}
+
+#[derive(Debug, Clone, Args, Deserialize)]
+pub struct UninstallNativeHostArgs {
+    #[arg(long = "browser", require_equals = true, value_name = "BROWSER")]
+    pub browser: Browser,
+}
+
+#[derive(Debug, Clone, Subcommand, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenCommand {
+    #[command(name = "create", about = "Create an API token (printed once)")]
+    Create(TokenCreateArgs),
+
+    #[command(name = "list", alias = "l", about = "List API tokens")]
+    List,
+
+    #[command(name = "revoke", about = "Revoke API tokens by id or name")]
+    Revoke(TokenRevokeArgs),
+}
+
+/// Lets argument-less requests be sent as `{"list": null}` as well as `{"list": {}}`.
+fn null_as_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de> + Default,
+{
+    Option::<T>::deserialize(deserializer).map(Option::unwrap_or_default)
+}
+
+fn parse_data_type(value: &str) -> Result<DataType, String> {
+    DataType::from_str(&value.to_lowercase())
+        .map_err(|_| "expected one of: binary, plain, hex, base58, base64, record, otp".to_string())
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ListFormat {
+    #[default]
+    Table,
+    Json,
+    Csv,
+}
+
+#[derive(Debug, Clone, Default, Args, Deserialize)]
+#[serde(default)]
+pub struct ListArgs {
+    #[arg(
+        value_name = "PATTERN",
+        help = "Name glob (* and ?), or a regular expression with --regex"
+    )]
+    pub pattern: Option<String>,
+
+    #[arg(long = "regex", help = "Treat the pattern as a regular expression")]
+    pub regex: bool,
+
+    #[arg(
+        long = "type",
+        require_equals = true,
+        value_name = "DATA_TYPE",
+        value_parser = parse_data_type,
+        help = "Only entries of this data type"
+    )]
+    pub data_type: Option<DataType>,
+
+    #[arg(
+        long = "encrypted",
+        conflicts_with = "unencrypted",
+        help = "Only encrypted entries"
+    )]
+    pub encrypted: bool,
+
+    #[arg(long = "unencrypted", help = "Only unencrypted entries")]
+    pub unencrypted: bool,
+
+    #[arg(
+        long = "key",
+        require_equals = true,
+        value_name = "KEY",
+        help = "Only entries encrypted with this key (name or fingerprint prefix)"
+    )]
+    pub key: Option<String>,
+
+    #[arg(
+        long = "min-size",
+        require_equals = true,
+        value_name = "BYTES",
+        help = "Minimum size"
+    )]
+    pub min_size: Option<u32>,
+
+    #[arg(
+        long = "max-size",
+        require_equals = true,
+        value_name = "BYTES",
+        help = "Maximum size"
+    )]
+    pub max_size: Option<u32>,
+
+    #[arg(
+        long = "tag",
+        require_equals = true,
+        value_name = "TAG",
+        help = "Only entries with this tag (repeat to require several)"
+    )]
+    pub tag: Vec<String>,
+
+    #[arg(
+        long = "folder",
+        require_equals = true,
+        value_name = "FOLDER",
+        help = "Only entries in this folder or its subfolders"
+    )]
+    pub folder: Option<String>,
+
+    #[arg(
+        long = "sort",
+        require_equals = true,
+        value_enum,
+        help = "Sort order (default: device order)"
+    )]
+    pub sort: Option<ListSort>,
+
+    #[arg(long = "desc", help = "Reverse the order")]
+    pub desc: bool,
+
+    #[arg(
+        long = "format",
+        require_equals = true,
+        value_enum,
+        default_value_t = ListFormat::Table,
+        help = "Output format in CLI mode"
+    )]
+    pub format: ListFormat,
+}
+
+impl ListArgs {
+    pub fn filter(&self) -> ListFilter {
+        let encrypted = match (self.encrypted, self.unencrypted) {
+            (true, _) => Some(true),
+            (false, true) => Some(false),
+            (false, false) => None,
+        };
+        ListFilter {
+            pattern: self.pattern.clone(),
+            regex: self.regex,
+            data_type: self.data_type,
+            encrypted,
+            key: self.key.clone(),
+            min_size: self.min_size,
+            max_size: self.max_size,
+            tags: self.tag.clone(),
+            folder: self.folder.clone(),
+            sort: self.sort,
+            desc: self.desc,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Args, Deserialize)]
+pub struct AuditArgs {
+    #[arg(
+        long = "password",
+        require_equals = true,
+        value_name = "PASSWORD",
+        help = "Password to unlock encrypted entries (repeatable)"
+    )]
+    #[serde(default)]
+    pub password: Vec<String>,
+
+    #[arg(
+        long = "stale-days",
+        require_equals = true,
+        value_name = "DAYS",
+        default_value_t = DEFAULT_STALE_DAYS,
+        help = "Report entries not modified for this many days"
+    )]
+    #[serde(default = "default_stale_days")]
+    pub stale_days: u64,
+
+    #[arg(
+        long = "expiry-days",
+        require_equals = true,
+        value_name = "DAYS",
+        default_value_t = DEFAULT_EXPIRY_WARNING_DAYS,
+        help = "Report entries that expire within this many days"
+    )]
+    #[serde(default = "default_expiry_days")]
+    pub expiry_days: u64,
+
+    #[arg(
+        long = "breach-db",
+        require_equals = true,
+        value_name = "PATH",
+        help = "Offline HIBP SHA-1 list: a file sorted by hash or a directory of range files"
+    )]
+    pub breach_db: Option<String>,
+}
+
+fn default_stale_days() -> u64 {
+    DEFAULT_STALE_DAYS
 }
 
-#[derive(Debug, Clone, Args)]
+fn default_expiry_days() -> u64 {
+    DEFAULT_EXPIRY_WARNING_DAYS
+}
+
+#[derive(Debug, Clone, Args, Deserialize)]
+pub struct TokenCreateArgs {
+    #[arg(value_name = "NAME", help = "Token label")]
+    pub name: String,
+
+    #[arg(
+        long = "scope",
+        require_equals = true,
+        required = true,
+        value_name = "SCOPE",
+        help = "list, read[:pattern] or write[:pattern] (repeatable; pattern is a name or prefix*)"
+    )]
+    pub scope: Vec<String>,
+
+    #[arg(
+        long = "master-password",
+        require_equals = true,
+        value_name = "PASSWORD",
+        help = "Master password (required in --mode=json if master password is set)"
+    )]
+    pub master_password: Option<String>,
+}
+
+#[derive(Debug, Clone, Args, Deserialize)]
+pub struct TokenRevokeArgs {
+    #[arg(value_name = "ID", help = "Token id or name")]
+    pub id: String,
+
+    #[arg(
+        long = "master-password",
+        require_equals = true,
+        value_name = "PASSWORD",
+        help = "Master password (required in --mode=json if master password is set)"
+    )]
+    pub master_password: Option<String>,
+}
+
+#[derive(Debug, Clone, Args, Deserialize)]
 pub struct SetMasterPasswordArgs {
     #[arg(
         long = "master-password",
@@ -326,11 +928,13 @@ pub struct SetMasterPasswordArgs {
     pub password: Option<String>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum EncryptionLevelArg {
     /// Faster, weaker
     Low,
     /// Balanced (default)
+    #[default]
     Medium,
     /// Slower, stronger
     Strong,
@@ -369,7 +973,7 @@ impl From<EncryptionLevelArg> for EncryptionLevel {
     }
 }
 
-#[derive(Debug, Clone, Args)]
+#[derive(Debug, Clone, Args, Deserialize)]
 pub struct AddEncryptionKeyArgs {
     #[arg(value_name = "NAME", help = "Encryption key name")]
     pub name: String,
@@ -380,6 +984,7 @@ pub struct AddEncryptionKeyArgs {
         value_name = "LEVEL",
         help = "Encryption level (default: medium)"
     )]
+    #[serde(default)]
     pub level: EncryptionLevelArg,
 
     #[arg(
@@ -407,7 +1012,7 @@ pub struct AddEncryptionKeyArgs {
     pub password: Option<String>,
 }
 
-#[derive(Debug, Clone, Args)]
+#[derive(Debug, Clone, Args, Deserialize)]
 pub struct LinkKeyArgs {
     #[arg(value_name = "NAME", help = "Encryption key name")]
     pub name: String,
@@ -421,7 +1026,7 @@ pub struct LinkKeyArgs {
     pub password: Option<String>,
 }
 
-#[derive(Debug, Clone, Args)]
+#[derive(Debug, Clone, Args, Deserialize)]
 pub struct SyncKeysArgs {
     #[arg(
         long = "master-password",
@@ -440,7 +1045,7 @@ pub struct SyncKeysArgs {
     pub password: Option<String>,
 }
 
-#[derive(Debug, Clone, Args)]
+#[derive(Debug, Clone, Args, Deserialize)]
 pub struct SetArgs {
     #[arg(value_name = "NAME", help = "Entry name")]
     pub name: String,
@@ -458,9 +1063,46 @@ pub struct SetArgs {
         help = "Password to encrypt data"
     )]
     pub password: Option<String>,
+
+    #[arg(
+        long = "field",
+        require_equals = true,
+        value_name = "KEY=VALUE",
+        conflicts_with = "data",
+        help = "Set a field of a record entry (repeatable; KEY= removes the field; creates the record if missing)"
+    )]
+    #[serde(default)]
+    pub fields: Vec<String>,
+
+    #[arg(
+        long = "type",
+        require_equals = true,
+        value_enum,
+        value_name = "DATA_TYPE",
+        conflicts_with = "fields",
+        help = "Store the data as this type instead of inferring it (binary takes hex)"
+    )]
+    #[serde(rename = "type")]
+    pub data_type: Option<DataTypeArg>,
+
+    #[arg(
+        long = "expires",
+        require_equals = true,
+        value_name = "DATE",
+        help = "Expiry date (YYYY-MM-DD, UTC); kept from the old entry if omitted"
+    )]
+    pub expires: Option<String>,
+
+    #[arg(
+        long = "master-password",
+        require_equals = true,
+        value_name = "PASSWORD",
+        help = "Master password (needed to overwrite a locked entry)"
+    )]
+    pub master_password: Option<String>,
 }
 
-#[derive(Debug, Clone, Args)]
+#[derive(Debug, Clone, Args, Deserialize)]
 pub struct GetArgs {
     #[arg(value_name = "NAME", help = "Entry name")]
     pub name: String,
@@ -474,10 +1116,217 @@ pub struct GetArgs {
     pub password: Option<String>,
 
     #[arg(long = "clipboard", short = 'c', help = "Copy data to clipboard")]
+    #[serde(default)]
     pub clipboard: bool,
+
+    #[arg(
+        long = "field",
+        require_equals = true,
+        value_name = "FIELD",
+        help = "Return only this field of a record entry"
+    )]
+    pub field: Option<String>,
+
+    #[arg(long = "allow-expired", help = "Read the entry even if it has expired")]
+    #[serde(default)]
+    pub allow_expired: bool,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+#[derive(Debug, Clone, Args, Deserialize)]
+pub struct GenerateArgs {
+    #[arg(value_name = "NAME", help = "Entry name")]
+    pub name: String,
+
+    #[arg(
+        long = "length",
+        require_equals = true,
+        default_value_t = 24,
+        help = "Password length"
+    )]
+    #[serde(default = "default_generate_length")]
+    pub length: usize,
+
+    #[arg(
+        long = "classes",
+        require_equals = true,
+        value_enum,
+        value_delimiter = ',',
+        help = "Character classes, each used at least once (default: lower,upper,digits,symbols)"
+    )]
+    #[serde(default)]
+    pub classes: Vec<CharClass>,
+
+    #[arg(
+        long = "no-ambiguous",
+        help = "Leave out look-alike characters (I l 1 O 0 o |)"
+    )]
+    #[serde(default)]
+    pub no_ambiguous: bool,
+
+    #[arg(
+        long = "words",
+        require_equals = true,
+        value_name = "N",
+        conflicts_with_all = ["length", "classes", "no_ambiguous"],
+        help = "Generate a passphrase of N words instead"
+    )]
+    pub words: Option<usize>,
+
+    #[arg(
+        long = "separator",
+        require_equals = true,
+        default_value = "-",
+        requires = "words",
+        help = "Passphrase word separator"
+    )]
+    #[serde(default = "default_generate_separator")]
+    pub separator: String,
+
+    #[arg(
+        long = "capitalize",
+        requires = "words",
+        help = "Capitalize passphrase words"
+    )]
+    #[serde(default)]
+    pub capitalize: bool,
+
+    #[arg(
+        long = "password",
+        require_equals = true,
+        value_name = "PASSWORD",
+        help = "Password to encrypt the generated value"
+    )]
+    pub password: Option<String>,
+
+    #[arg(
+        long = "master-password",
+        require_equals = true,
+        value_name = "PASSWORD",
+        help = "Master password (needed to overwrite a locked entry)"
+    )]
+    pub master_password: Option<String>,
+}
+
+fn default_generate_length() -> usize {
+    24
+}
+
+fn default_generate_separator() -> String {
+    "-".to_string()
+}
+
+#[derive(Debug, Clone, Args, Deserialize)]
+pub struct SetOtpArgs {
+    #[arg(value_name = "NAME", help = "Entry name")]
+    pub name: String,
+
+    #[arg(
+        value_name = "SECRET",
+        help = "Seed in --encoding (base32 by default) or an otpauth:// URI"
+    )]
+    pub secret: String,
+
+    #[arg(
+        long = "encoding",
+        require_equals = true,
+        value_enum,
+        default_value = "base32",
+        help = "Encoding of a raw seed"
+    )]
+    #[serde(default)]
+    pub encoding: OtpSecretEncoding,
+
+    #[arg(
+        long = "hotp",
+        help = "Counter based (HOTP) instead of time based (TOTP)"
+    )]
+    #[serde(default)]
+    pub hotp: bool,
+
+    #[arg(
+        long = "counter",
+        require_equals = true,
+        value_name = "N",
+        help = "Next HOTP counter (implies --hotp)"
+    )]
+    pub counter: Option<u64>,
+
+    #[arg(
+        long = "digits",
+        require_equals = true,
+        value_name = "6-10",
+        help = "Code length (default: 6)"
+    )]
+    pub digits: Option<u8>,
+
+    #[arg(
+        long = "period",
+        require_equals = true,
+        value_name = "SECONDS",
+        help = "TOTP step (default: 30)"
+    )]
+    pub period: Option<u64>,
+
+    #[arg(
+        long = "algorithm",
+        require_equals = true,
+        value_enum,
+        help = "HMAC algorithm (default: sha1)"
+    )]
+    pub algorithm: Option<OtpAlgorithmArg>,
+
+    #[arg(long = "issuer", require_equals = true, help = "Service name")]
+    pub issuer: Option<String>,
+
+    #[arg(long = "account", require_equals = true, help = "Account name")]
+    pub account: Option<String>,
+
+    #[arg(
+        long = "password",
+        require_equals = true,
+        value_name = "PASSWORD",
+        help = "Password to encrypt the seed"
+    )]
+    pub password: Option<String>,
+
+    #[arg(
+        long = "master-password",
+        require_equals = true,
+        value_name = "PASSWORD",
+        help = "Master password (needed to overwrite a locked entry)"
+    )]
+    pub master_password: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OtpSecretEncoding {
+    #[default]
+    Base32,
+    Base58,
+    Hex,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OtpAlgorithmArg {
+    Sha1,
+    Sha256,
+    Sha512,
+}
+
+impl From<OtpAlgorithmArg> for OtpAlgorithm {
+    fn from(value: OtpAlgorithmArg) -> Self {
+        match value {
+            OtpAlgorithmArg::Sha1 => OtpAlgorithm::Sha1,
+            OtpAlgorithmArg::Sha256 => OtpAlgorithm::Sha256,
+            OtpAlgorithmArg::Sha512 => OtpAlgorithm::Sha512,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum DataTypeArg {
     Binary,
     Plain,
@@ -498,16 +1347,48 @@ impl From<DataTypeArg> for DataType {
     }
 }
 
-#[derive(Debug, Clone, Args)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DefaultDataTypeArg {
+    /// Detect hex, base58 and base64 (default)
+    Infer,
+    /// Store text as typed
+    Plain,
+}
+
+#[derive(Debug, Clone, Args, Deserialize)]
 pub struct ChangeDataTypeArgs {
-    #[arg(value_name = "NAME", help = "Entry name")]
+    #[arg(value_name = "NAME", help = "Entry name, or a name glob with --match")]
     pub name: String,
 
     #[arg(value_enum, value_name = "DATA_TYPE", help = "New data type")]
     pub new_data_type: DataTypeArg,
+
+    #[arg(
+        long = "match",
+        help = "Change every entry whose name matches the glob"
+    )]
+    #[serde(default, rename = "match")]
+    pub matching: bool,
+
+    #[arg(
+        long = "dry-run",
+        requires = "matching",
+        help = "Only list the affected entries"
+    )]
+    #[serde(default)]
+    pub dry_run: bool,
+
+    #[arg(
+        long = "master-password",
+        require_equals = true,
+        value_name = "PASSWORD",
+        help = "Master password (needed to change the type of a locked entry)"
+    )]
+    pub master_password: Option<String>,
 }
 
-#[derive(Debug, Clone, Args)]
+#[derive(Debug, Clone, Args, Deserialize)]
 pub struct ChangePasswordArgs {
     #[arg(value_name = "NAME", help = "Entry name")]
     pub name: String,
@@ -527,4 +1408,12 @@ pub struct ChangePasswordArgs {
         help = "New password (to change; omit to remove encryption)"
     )]
     pub new_password: Option<String>,
+
+    #[arg(
+        long = "master-password",
+        require_equals = true,
+        value_name = "PASSWORD",
+        help = "Master password (needed for a locked entry)"
+    )]
+    pub master_password: Option<String>,
 }
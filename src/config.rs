@@ -10,15 +10,64 @@ use borsh_derive::{BorshDeserialize, BorshSerialize};
 pub const CONFIG_FILE_NAME: &str = ".svpi";
 
 const CONFIG_MAGIC: [u8; 4] = *b"SCFG";
-const CONFIG_VERSION: u32 = 1;
+const CONFIG_VERSION: u32 = 3;
 
 #[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
 pub struct SvpiConfig {
     magic: [u8; 4],
     version: u32,
-    /// 0=cli, 1=json, 2=server, 3=chrome
+    /// 0=cli, 1=json, 2=server, 3=chrome, 4=json-lines
     pub mode: u8,
     pub file: Option<String>,
+    /// Origins allowed by the API server CORS policy (server mode).
+    pub cors_origins: Vec<String>,
+    /// `set` (CLI and API) stores text as plain instead of inferring hex/base58/base64.
+    pub plain_by_default: bool,
+}
+
+/// Layout of version 2 configs (before `plain_by_default`).
+#[derive(BorshDeserialize)]
+struct SvpiConfigV2 {
+    magic: [u8; 4],
+    version: u32,
+    mode: u8,
+    file: Option<String>,
+    cors_origins: Vec<String>,
+}
+
+impl From<SvpiConfigV2> for SvpiConfig {
+    fn from(v2: SvpiConfigV2) -> Self {
+        Self {
+            magic: v2.magic,
+            version: v2.version,
+            mode: v2.mode,
+            file: v2.file,
+            cors_origins: v2.cors_origins,
+            plain_by_default: false,
+        }
+    }
+}
+
+/// Layout of version 1 configs (before `cors_origins`).
+#[derive(BorshDeserialize)]
+struct SvpiConfigV1 {
+    magic: [u8; 4],
+    version: u32,
+    mode: u8,
+    file: Option<String>,
+}
+
+impl From<SvpiConfigV1> for SvpiConfig {
+    fn from(v1: SvpiConfigV1) -> Self {
+        Self {
+            magic: v1.magic,
+            version: v1.version,
+            mode: v1.mode,
+            file: v1.file,
+            cors_origins: Vec::new(),
+            plain_by_default: false,
+        }
+    }
 }
 
 impl Default for SvpiConfig {
@@ -28,6 +77,8 @@ impl Default for SvpiConfig {
             version: CONFIG_VERSION,
             mode: 0,
             file: None,
+            cors_origins: Vec::new(),
+            plain_by_default: false,
         }
     }
 }
@@ -42,6 +93,15 @@ impl SvpiConfig {
         Self::load_from_path(&path)
     }
 
+    /// `set-default-type plain` in the `.svpi` config: `set` (CLI and API)
+    /// stores text without a type as plain instead of inferring one.
+    pub fn plain_by_default_in_cwd() -> bool {
+        Self::load_from_cwd()
+            .ok()
+            .flatten()
+            .is_some_and(|cfg| cfg.plain_by_default)
+    }
+
     pub fn load_from_path(path: &Path) -> io::Result<Option<Self>> {
         let bytes = match fs::read(path) {
             Ok(v) => v,
@@ -57,11 +117,17 @@ impl SvpiConfig {
         }
 
         let cfg = match Self::try_from_slice(&bytes) {
-            Ok(v) => v,
-            Err(_) => return Ok(None),
+            Ok(v) if v.version == CONFIG_VERSION => v,
+            _ => match SvpiConfigV2::try_from_slice(&bytes) {
+                Ok(v2) if v2.version == 2 => v2.into(),
+                _ => match SvpiConfigV1::try_from_slice(&bytes) {
+                    Ok(v1) if v1.version == 1 => v1.into(),
+                    _ => return Ok(None),
+                },
+            },
         };
 
-        if (cfg.magic != CONFIG_MAGIC) || cfg.version != CONFIG_VERSION {
+        if cfg.magic != CONFIG_MAGIC {
             return Ok(None);
         }
 
@@ -101,3 +167,87 @@ impl SvpiConfig {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(BorshSerialize)]
+    struct V1 {
+        magic: [u8; 4],
+        version: u32,
+        mode: u8,
+        file: Option<String>,
+    }
+
+    #[derive(BorshSerialize)]
+    struct V2 {
+        magic: [u8; 4],
+        version: u32,
+        mode: u8,
+        file: Option<String>,
+        cors_origins: Vec<String>,
+    }
+
+    #[test]
+    fn loads_v1_and_round_trips_cors_origins() {
+        let dir = std::env::temp_dir().join(format!("svpi-config-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(CONFIG_FILE_NAME);
+
+        let v1 = V1 {
+            magic: CONFIG_MAGIC,
+            version: 1,
+            mode: 2,
+            file: Some("vault.bin".to_string()),
+        };
+        fs::write(&path, borsh::to_vec(&v1).unwrap()).unwrap();
+        let cfg = SvpiConfig::load_from_path(&path)
+            .unwrap()
+            .expect("v1 config");
+        assert_eq!(cfg.mode, 2);
+        assert_eq!(cfg.file.as_deref(), Some("vault.bin"));
+        assert!(cfg.cors_origins.is_empty());
+
+        let mut cfg = cfg;
+        cfg.cors_origins = vec!["http://localhost:5173".to_string()];
+        cfg.save_to_path(&path).unwrap();
+        let cfg = SvpiConfig::load_from_path(&path)
+            .unwrap()
+            .expect("v3 config");
+        assert_eq!(cfg.cors_origins, vec!["http://localhost:5173".to_string()]);
+        assert!(!cfg.plain_by_default);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn loads_v2_and_round_trips_plain_by_default() {
+        let dir = std::env::temp_dir().join(format!("svpi-config-v2-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(CONFIG_FILE_NAME);
+
+        let v2 = V2 {
+            magic: CONFIG_MAGIC,
+            version: 2,
+            mode: 0,
+            file: None,
+            cors_origins: vec!["http://localhost:5173".to_string()],
+        };
+        fs::write(&path, borsh::to_vec(&v2).unwrap()).unwrap();
+        let mut cfg = SvpiConfig::load_from_path(&path)
+            .unwrap()
+            .expect("v2 config");
+        assert_eq!(cfg.cors_origins, vec!["http://localhost:5173".to_string()]);
+        assert!(!cfg.plain_by_default);
+
+        cfg.plain_by_default = true;
+        cfg.save_to_path(&path).unwrap();
+        let cfg = SvpiConfig::load_from_path(&path)
+            .unwrap()
+            .expect("v3 config");
+        assert!(cfg.plain_by_default);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,7 +1,10 @@
 use std::{
     fs::{File, OpenOptions},
     io::{Read, Seek, SeekFrom, Write},
-    sync::{Arc, Mutex},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
 };
 
 use crate::data_mgr::DeviceError;
@@ -9,6 +12,8 @@ use crate::data_mgr::DeviceError;
 #[derive(Debug, Clone)]
 pub struct FileSystemDataManager {
     file: Arc<Mutex<File>>,
+    /// Set by every write, so callers can tell their own changes from other processes'.
+    written: Arc<AtomicBool>,
 }
 
 impl FileSystemDataManager {
@@ -40,8 +45,14 @@ impl FileSystemDataManager {
         }
         Ok(Self {
             file: Arc::new(Mutex::new(file)),
+            written: Arc::new(AtomicBool::new(false)),
         })
     }
+
+    /// Whether the file was written since the last call.
+    pub fn take_written(&self) -> bool {
+        self.written.swap(false, Ordering::Relaxed)
+    }
 }
 
 impl FileSystemDataManager {
@@ -49,6 +60,7 @@ impl FileSystemDataManager {
         let file = self.file.lock().expect("Failed to lock file");
         file.set_len(size as u64)
             .map_err(|_| DeviceError::InitMemoryError)?;
+        self.written.store(true, Ordering::Relaxed);
         Ok(())
     }
 
@@ -72,6 +84,7 @@ impl FileSystemDataManager {
 
         file.seek(SeekFrom::Start(address.into()))
             .map_err(|_| DeviceError::WriteError)?;
+        self.written.store(true, Ordering::Relaxed);
         file.write_all(data).map_err(|_| DeviceError::WriteError)?;
         file.sync_data().map_err(|_| DeviceError::WriteError)?;
         Ok(())
@@ -69,6 +69,15 @@ impl DataManager {
             DataManager::Memory(mgr) => mgr.init_memory(size),
         }
     }
+
+    /// Whether this manager wrote to a vault file since the last call; only
+    /// files can also be changed by other processes.
+    pub fn take_written(&self) -> bool {
+        match self {
+            DataManager::FileSystem(mgr) => mgr.take_written(),
+            DataManager::SerialPort(_) | DataManager::Memory(_) => false,
+        }
+    }
 }
 
 impl DataManagerExt for DataManager {
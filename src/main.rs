@@ -25,8 +25,14 @@ async fn main() {
     let config = crate::config::SvpiConfig::load_from_cwd().ok().flatten();
 
     let prefer_json_errors = match mode_arg.as_deref() {
-        Some(v) => v.trim().eq_ignore_ascii_case("json"),
-        None => config.as_ref().map(|c| c.mode == 1).unwrap_or(false),
+        Some(v) => {
+            let v = v.trim();
+            v.eq_ignore_ascii_case("json") || v.eq_ignore_ascii_case("json-lines")
+        }
+        None => config
+            .as_ref()
+            .map(|c| c.mode == 1 || c.mode == 4)
+            .unwrap_or(false),
     };
 
     let mut cli = match crate::cli::CliArgs::try_parse() {
@@ -55,6 +61,7 @@ async fn main() {
             1 => Some(Mode::Json),
             2 => Some(Mode::Server),
             3 => Some(Mode::Chrome),
+            4 => Some(Mode::JsonLines),
             _ => None,
         }) {
             cli.mode = mode;
@@ -63,28 +70,41 @@ async fn main() {
 
     match cli.mode {
         Mode::Cli | Mode::Json => std::process::exit(svpi::cli_mode::run_with_cli(&cli)),
+        Mode::JsonLines => {
+            if cli.command.is_some() {
+                let resp = SvpiResponse::invalid_argument(
+                    None,
+                    "args",
+                    "subcommand is not supported in --mode=json-lines (send requests on stdin)",
+                );
+                resp.print_json();
+                std::process::exit(2);
+            }
+            std::process::exit(svpi::json_lines::run_json_lines(&cli))
+        }
         Mode::Server => {
             if cli.command.is_some() {
                 eprintln!("invalid_argument: subcommand is not supported in --mode=server");
                 std::process::exit(2);
             }
-            api::server::api_server(
-                cli.interface_type(),
-                cli.auto_exit,
-                cli.bind,
-                cli.port,
-                cli.cors,
-            )
-            .launch()
-            .await
-            .expect("Failed to start API server!");
+            let options = match api::server::ServerOptions::from_cli(&cli) {
+                Ok(v) => v,
+                Err(message) => {
+                    eprintln!("invalid_argument: {message}");
+                    std::process::exit(2);
+                }
+            };
+            if let Err(message) = api::server::run_server(cli.interface_type(), options).await {
+                eprintln!("error: {message}");
+                std::process::exit(1);
+            }
         }
         Mode::Chrome => {
             if cli.command.is_some() {
                 eprintln!("invalid_argument: subcommand is not supported in --mode=chrome");
                 std::process::exit(2);
             }
-            api::chrome::run_chrome_app(cli.interface_type())
+            api::chrome::run_chrome_app(cli.interface_type(), cli.rate_limit_policy())
                 .expect("Failed to run Chrome native app!");
         }
     }
@@ -0,0 +1,145 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    pass_mgr::{PasswordManager, PasswordManagerError},
+    seg_mgr::{Data, DataType, SegmentError},
+};
+
+impl PasswordManager {
+    /// Alias names and the entries they point at.
+    pub fn aliases(&mut self) -> Result<BTreeMap<String, String>, PasswordManagerError> {
+        let mut aliases = BTreeMap::new();
+        for segment in self.0.get_active_segments_mut() {
+            if segment.info.data_type != DataType::Alias {
+                continue;
+            }
+            if let Data::Alias(target) = segment
+                .read_data()
+                .map_err(PasswordManagerError::ReadPasswordError)?
+            {
+                aliases.insert(segment.get_name(), target);
+            }
+        }
+        Ok(aliases)
+    }
+
+    /// Aliases that point at `target`.
+    pub fn aliases_of(&mut self, target: &str) -> Result<Vec<String>, PasswordManagerError> {
+        Ok(self
+            .aliases()?
+            .into_iter()
+            .filter(|(_, to)| to == target)
+            .map(|(name, _)| name)
+            .collect())
+    }
+
+    /// The entry `name` stands for: its target if it is an alias, otherwise
+    /// `name` itself. Fails if the target no longer exists.
+    pub fn resolve_alias(&mut self, name: &str) -> Result<String, PasswordManagerError> {
+        let Some(segment) = self.0.find_segment_by_name(name) else {
+            return Err(PasswordManagerError::ReadPasswordError(
+                SegmentError::NotFound(name.to_string()),
+            ));
+        };
+        if segment.info.data_type != DataType::Alias {
+            return Ok(name.to_string());
+        }
+        let Data::Alias(target) = segment
+            .read_data()
+            .map_err(PasswordManagerError::ReadPasswordError)?
+        else {
+            return Err(PasswordManagerError::DanglingAlias(name.to_string()));
+        };
+
+        match self.0.find_segment_by_name(&target) {
+            Some(segment) if is_alias_target(segment.info.data_type) => Ok(target),
+            _ => Err(PasswordManagerError::DanglingAlias(name.to_string())),
+        }
+    }
+
+    /// Stores `name` as an alias of the entry `target`. Aliases of aliases,
+    /// keys and internal records are refused. `false` means out of memory.
+    pub fn save_alias(&mut self, name: &str, target: &str) -> Result<bool, PasswordManagerError> {
+        if name == target {
+            return Err(PasswordManagerError::InvalidAlias(format!(
+                "'{name}' cannot be an alias of itself"
+            )));
+        }
+        let Some(segment) = self.0.find_segment_by_name(target) else {
+            return Err(PasswordManagerError::ReadPasswordError(
+                SegmentError::NotFound(target.to_string()),
+            ));
+        };
+        if !is_alias_target(segment.info.data_type) {
+            return Err(PasswordManagerError::InvalidAlias(format!(
+                "'{target}' ({}) cannot be the target of an alias",
+                segment.info.data_type.to_string()
+            )));
+        }
+
+        self.save_data(name, Data::Alias(target.to_string()), None)
+    }
+
+    /// Points the aliases of `old_name` at `new_name` after a rename. An alias
+    /// that no longer fits keeps the old target and shows up as dangling.
+    pub(super) fn retarget_aliases(
+        &mut self,
+        old_name: &str,
+        new_name: &str,
+    ) -> Result<(), PasswordManagerError> {
+        for alias in self.aliases_of(old_name)? {
+            let data = Data::Alias(new_name.to_string())
+                .to_bytes()
+                .map_err(PasswordManagerError::DataError)?;
+            self.0
+                .set_segment(&alias, &data, DataType::Alias, None)
+                .map_err(PasswordManagerError::SavePasswordError)?;
+        }
+        Ok(())
+    }
+}
+
+fn is_alias_target(data_type: DataType) -> bool {
+    !matches!(
+        data_type,
+        DataType::Alias | DataType::EncryptionKey | DataType::Internal
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{data_mgr::DataInterfaceType, seg_mgr::EncryptionLevel};
+
+    #[test]
+    fn alias_follows_target() {
+        let mut mgr =
+            PasswordManager::from_device_type(DataInterfaceType::Memory(vec![])).expect("init");
+        mgr.get_data_manager()
+            .init_device(2048, EncryptionLevel::Low)
+            .expect("init device");
+        mgr.save_password("token", "secret", None).unwrap();
+
+        assert!(mgr.save_alias("legacy-token", "token").unwrap());
+        assert_eq!(mgr.resolve_alias("legacy-token").unwrap(), "token");
+        assert_eq!(mgr.resolve_alias("token").unwrap(), "token");
+        assert!(matches!(
+            mgr.save_alias("chained", "legacy-token"),
+            Err(PasswordManagerError::InvalidAlias(_))
+        ));
+        assert!(matches!(
+            mgr.save_alias("token", "token"),
+            Err(PasswordManagerError::InvalidAlias(_))
+        ));
+
+        mgr.rename_password("token", "api-token").unwrap();
+        assert_eq!(mgr.resolve_alias("legacy-token").unwrap(), "api-token");
+        assert_eq!(mgr.aliases_of("api-token").unwrap(), vec!["legacy-token"]);
+
+        mgr.remove_password("api-token").unwrap();
+        assert!(matches!(
+            mgr.resolve_alias("legacy-token"),
+            Err(PasswordManagerError::DanglingAlias(_))
+        ));
+    }
+}
@@ -0,0 +1,150 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::pass_mgr::{PasswordManager, PasswordManagerError};
+
+const ENTRY_ATTRIBUTES_SEGMENT: &str = "entry_attributes";
+
+/// Metadata kept next to an entry (not part of its data or segment header).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EntryAttributes {
+    /// Sites the entry belongs to, as given by the user (`https://github.com/login`, `github.com`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub urls: Vec<String>,
+    /// When the entry data was last written (Unix seconds); unknown for older entries.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified: Option<u64>,
+    /// Sorted and unique (see `normalize_tag`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Slash-separated folder path (see `normalize_folder`); `None` is the top level.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub folder: Option<String>,
+    /// Refuses overwrite, rename, removal and password changes (see `ensure_unlocked`).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub locked: bool,
+    /// When the entry expires (Unix seconds, see `parse_expiry`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires: Option<u64>,
+}
+
+impl EntryAttributes {
+    pub fn is_empty(&self) -> bool {
+        *self == EntryAttributes::default()
+    }
+}
+
+/// Attributes by entry name.
+pub type EntryAttributesMap = BTreeMap<String, EntryAttributes>;
+
+impl PasswordManager {
+    pub fn load_entry_attributes(&mut self) -> Result<EntryAttributesMap, PasswordManagerError> {
+        let Some(segment) = self.0.find_internal_segment(ENTRY_ATTRIBUTES_SEGMENT) else {
+            return Ok(EntryAttributesMap::new());
+        };
+
+        let data = segment
+            .read_data()
+            .map_err(PasswordManagerError::EntryAttributesError)?
+            .to_bytes()
+            .map_err(PasswordManagerError::DataError)?;
+
+        serde_json::from_slice(&data).map_err(|_| PasswordManagerError::InvalidEntryAttributes)
+    }
+
+    pub fn save_entry_attributes(
+        &mut self,
+        attributes: &EntryAttributesMap,
+    ) -> Result<bool, PasswordManagerError> {
+        let attributes = attributes
+            .iter()
+            .filter(|(_, attrs)| !attrs.is_empty())
+            .collect::<BTreeMap<_, _>>();
+
+        if attributes.is_empty() {
+            if let Some(segment) = self.0.find_internal_segment(ENTRY_ATTRIBUTES_SEGMENT) {
+                segment
+                    .remove()
+                    .map_err(PasswordManagerError::EntryAttributesError)?;
+            }
+            return Ok(true);
+        }
+
+        let data = serde_json::to_vec(&attributes)
+            .map_err(|_| PasswordManagerError::InvalidEntryAttributes)?;
+        self.0
+            .set_internal_segment(ENTRY_ATTRIBUTES_SEGMENT, &data)
+            .map(|seg| seg.is_some())
+            .map_err(PasswordManagerError::EntryAttributesError)
+    }
+
+    /// Applies `update` to the attributes of `name` and stores them.
+    /// `false` means out of memory.
+    pub fn update_entry_attributes<F>(
+        &mut self,
+        name: &str,
+        update: F,
+    ) -> Result<bool, PasswordManagerError>
+    where
+        F: FnOnce(&mut EntryAttributes),
+    {
+        let mut attributes = self.load_entry_attributes()?;
+        update(attributes.entry(name.to_string()).or_default());
+        self.save_entry_attributes(&attributes)
+    }
+
+    /// Keeps the attributes with the entry when it is renamed or removed.
+    pub(super) fn move_entry_attributes(
+        &mut self,
+        old_name: &str,
+        new_name: Option<&str>,
+    ) -> Result<(), PasswordManagerError> {
+        let mut attributes = self.load_entry_attributes()?;
+        let Some(attrs) = attributes.remove(old_name) else {
+            return Ok(());
+        };
+        if let Some(new_name) = new_name {
+            attributes.insert(new_name.to_string(), attrs);
+        }
+        self.save_entry_attributes(&attributes)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{data_mgr::DataInterfaceType, seg_mgr::EncryptionLevel};
+
+    #[test]
+    fn attributes_follow_rename_and_remove() {
+        let mut mgr =
+            PasswordManager::from_device_type(DataInterfaceType::Memory(vec![])).expect("init");
+        mgr.get_data_manager()
+            .init_device(2048, EncryptionLevel::Low)
+            .expect("init device");
+        mgr.save_password("github", "secret", None).unwrap();
+
+        assert!(mgr
+            .update_entry_attributes("github", |attrs| {
+                attrs.urls = vec!["github.com".to_string()];
+            })
+            .unwrap());
+
+        mgr.rename_password("github", "github-work").unwrap();
+        let attributes = mgr.load_entry_attributes().unwrap();
+        assert!(!attributes.contains_key("github"));
+        assert_eq!(
+            attributes["github-work"].urls,
+            vec!["github.com".to_string()]
+        );
+
+        mgr.remove_password("github-work").unwrap();
+        assert!(mgr.load_entry_attributes().unwrap().is_empty());
+        assert!(mgr
+            .get_data_manager()
+            .find_internal_segment(ENTRY_ATTRIBUTES_SEGMENT)
+            .is_none());
+    }
+}
@@ -0,0 +1,447 @@
+use std::collections::{BTreeMap, HashSet};
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    pass_mgr::{
+        expiry_state, format_date, BreachDb, ExpiryState, PasswordManager, PasswordManagerError,
+    },
+    seg_mgr::{Data, DataType, DATA_FINGERPRINT_SIZE},
+};
+
+pub const DEFAULT_STALE_DAYS: u64 = 365;
+
+/// Passwords below these estimates are reported as weak.
+const WEAK_HIGH_BITS: f64 = 40.0;
+const WEAK_MEDIUM_BITS: f64 = 60.0;
+
+/// Name fragments that mark an entry as a secret.
+const SECRET_NAME_HINTS: [&str; 10] = [
+    "pass", "pwd", "secret", "token", "key", "pin", "seed", "mnemonic", "otp", "2fa",
+];
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditSeverity {
+    Low,
+    Medium,
+    High,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditFindingKind {
+    Breached,
+    Weak,
+    Reused,
+    Stale,
+    Expired,
+    ExpiresSoon,
+    UnencryptedSecret,
+    DefaultKey,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditFinding {
+    pub name: String,
+    pub kind: AuditFindingKind,
+    pub severity: AuditSeverity,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entropy_bits: Option<f64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub reused_with: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub age_days: Option<u64>,
+    /// Unix seconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires: Option<u64>,
+    /// Occurrences in the breached-password list.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub breach_count: Option<u64>,
+}
+
+impl AuditFinding {
+    fn new(name: &str, kind: AuditFindingKind, severity: AuditSeverity, message: String) -> Self {
+        Self {
+            name: name.to_string(),
+            kind,
+            severity,
+            message,
+            entropy_bits: None,
+            reused_with: Vec::new(),
+            age_days: None,
+            expires: None,
+            breach_count: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditReport {
+    /// Entries looked at (encryption keys and aliases are not audited).
+    pub entries: usize,
+    /// Entries whose data was read, i.e. unencrypted or opened by one of the passwords.
+    pub checked: usize,
+    /// Encrypted entries none of the passwords opened; only key and age checks apply to them.
+    pub locked: Vec<String>,
+    /// Entries found in the breached-password list (only with a breach database).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub breached: Option<usize>,
+    /// Most severe first.
+    pub findings: Vec<AuditFinding>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AuditOptions {
+    pub passwords: Vec<String>,
+    pub stale_days: u64,
+    /// Entries expiring within this many days are reported.
+    pub expiry_days: u64,
+    /// Unix seconds.
+    pub now: u64,
+    /// Passwords are also looked up here when set.
+    pub breach_db: Option<BreachDb>,
+}
+
+/// Rough strength estimate in bits: the character pool per position, with runs
+/// of repeated or sequential characters (`aaaa`, `1234`, `cba`) counted once.
+pub fn estimate_entropy(password: &str) -> f64 {
+    let chars = password.chars().collect::<Vec<_>>();
+
+    let mut pool = 0u32;
+    if chars.iter().any(|c| c.is_ascii_lowercase()) {
+        pool += 26;
+    }
+    if chars.iter().any(|c| c.is_ascii_uppercase()) {
+        pool += 26;
+    }
+    if chars.iter().any(|c| c.is_ascii_digit()) {
+        pool += 10;
+    }
+    if chars.iter().any(|c| c.is_ascii_punctuation() || *c == ' ') {
+        pool += 33;
+    }
+    if chars.iter().any(|c| !c.is_ascii()) {
+        pool += 100;
+    }
+    if pool == 0 {
+        return 0.0;
+    }
+
+    let mut effective = 0usize;
+    let mut prev_step = None;
+    for (i, c) in chars.iter().enumerate() {
+        let step = (i > 0).then(|| *c as i64 - chars[i - 1] as i64);
+        let in_run = matches!(step, Some(-1..=1)) && step == prev_step;
+        if !in_run {
+            effective += 1;
+        }
+        prev_step = step;
+    }
+
+    effective as f64 * (pool as f64).log2()
+}
+
+fn looks_like_secret(name: &str, data: &Data) -> Option<AuditSeverity> {
+    let name = name.to_lowercase();
+    match data {
+        Data::Otp(_) => Some(AuditSeverity::High),
+        Data::Record(record) if record.get("password").is_some() => Some(AuditSeverity::High),
+        _ if SECRET_NAME_HINTS.iter().any(|hint| name.contains(hint)) => Some(AuditSeverity::High),
+        Data::Plain(text)
+            if (8..=128).contains(&text.chars().count())
+                && !text.chars().any(char::is_whitespace)
+                && estimate_entropy(text) >= WEAK_HIGH_BITS =>
+        {
+            Some(AuditSeverity::Medium)
+        }
+        _ => None,
+    }
+}
+
+/// The part of an entry that is compared for reuse and, if it was typed in as
+/// text, rated for strength. Text that happens to parse as hex or base58 is
+/// stored with that type, so it is rated as well.
+fn secret_of(data: &Data) -> Option<(Vec<u8>, Option<String>)> {
+    match data {
+        Data::Record(record) => record
+            .get("password")
+            .map(|password| (password.as_bytes().to_vec(), Some(password.to_string()))),
+        Data::Otp(config) => Some((config.secret.clone(), None)),
+        Data::Binary(bytes) => Some((bytes.clone(), None)),
+        _ => data
+            .to_string()
+            .ok()
+            .map(|text| (text.as_bytes().to_vec(), Some(text))),
+    }
+}
+
+impl PasswordManager {
+    pub fn audit(&mut self, options: &AuditOptions) -> Result<AuditReport, PasswordManagerError> {
+        let attributes = self.load_entry_attributes()?;
+        let key_fingerprints = self
+            .get_encryption_keys()
+            .into_iter()
+            .map(|seg| seg.info.fingerprint.fingerprint)
+            .collect::<HashSet<[u8; DATA_FINGERPRINT_SIZE]>>();
+        let entries = self
+            .0
+            .get_active_segments()
+            .into_iter()
+            .filter(|seg| {
+                !seg.info.data_type.is_internal()
+                    && !matches!(
+                        seg.info.data_type,
+                        DataType::EncryptionKey | DataType::Alias
+                    )
+            })
+            .map(|seg| (seg.get_name(), seg.info.password_fingerprint))
+            .collect::<Vec<_>>();
+
+        let mut report = AuditReport {
+            entries: entries.len(),
+            checked: 0,
+            locked: Vec::new(),
+            breached: options.breach_db.as_ref().map(|_| 0),
+            findings: Vec::new(),
+        };
+        let mut secrets = BTreeMap::<[u8; 32], Vec<String>>::new();
+
+        for (name, password_fingerprint) in &entries {
+            if let Some(fingerprint) = password_fingerprint {
+                if !key_fingerprints.contains(fingerprint) {
+                    report.findings.push(AuditFinding::new(
+                        name,
+                        AuditFindingKind::DefaultKey,
+                        AuditSeverity::Low,
+                        "Encrypted with the password itself instead of a stored encryption key"
+                            .to_string(),
+                    ));
+                }
+            }
+
+            let modified = attributes.get(name).and_then(|attrs| attrs.modified);
+            if let Some(modified) = modified {
+                let age_days = options.now.saturating_sub(modified) / SECONDS_PER_DAY;
+                if age_days >= options.stale_days {
+                    let mut finding = AuditFinding::new(
+                        name,
+                        AuditFindingKind::Stale,
+                        AuditSeverity::Low,
+                        format!("Not modified for {age_days} days"),
+                    );
+                    finding.age_days = Some(age_days);
+                    report.findings.push(finding);
+                }
+            }
+
+            let expires = attributes.get(name).and_then(|attrs| attrs.expires);
+            if let Some(expires) = expires {
+                let finding = match expiry_state(expires, options.now, options.expiry_days) {
+                    Some(ExpiryState::Expired) => Some(AuditFinding::new(
+                        name,
+                        AuditFindingKind::Expired,
+                        AuditSeverity::High,
+                        format!("Expired on {}", format_date(expires)),
+                    )),
+                    Some(ExpiryState::ExpiresSoon) => Some(AuditFinding::new(
+                        name,
+                        AuditFindingKind::ExpiresSoon,
+                        AuditSeverity::Medium,
+                        format!("Expires on {}", format_date(expires)),
+                    )),
+                    None => None,
+                };
+                if let Some(mut finding) = finding {
+                    finding.expires = Some(expires);
+                    report.findings.push(finding);
+                }
+            }
+
+            let data = if password_fingerprint.is_some() {
+                options
+                    .passwords
+                    .iter()
+                    .find_map(|password| self.read_data(name, || password.clone()).ok())
+            } else {
+                Some(self.read_data(name, String::new)?)
+            };
+            let Some(data) = data else {
+                report.locked.push(name.clone());
+                continue;
+            };
+            report.checked += 1;
+
+            if password_fingerprint.is_none() {
+                if let Some(severity) = looks_like_secret(name, &data) {
+                    report.findings.push(AuditFinding::new(
+                        name,
+                        AuditFindingKind::UnencryptedSecret,
+                        severity,
+                        "Looks like a secret but is stored unencrypted".to_string(),
+                    ));
+                }
+            }
+
+            let Some((secret, text)) = secret_of(&data) else {
+                continue;
+            };
+            if secret.is_empty() {
+                continue;
+            }
+            if let (Some(text), Some(breach_db)) = (&text, &options.breach_db) {
+                let count = breach_db
+                    .lookup(text)
+                    .map_err(PasswordManagerError::BreachDbError)?;
+                if count > 0 {
+                    let mut finding = AuditFinding::new(
+                        name,
+                        AuditFindingKind::Breached,
+                        AuditSeverity::High,
+                        format!("Found in the breached-password list ({count} times)"),
+                    );
+                    finding.breach_count = Some(count);
+                    report.findings.push(finding);
+                    report.breached = report.breached.map(|n| n + 1);
+                }
+            }
+            if let Some(text) = text {
+                let entropy = estimate_entropy(&text);
+                let severity = if entropy < WEAK_HIGH_BITS {
+                    Some(AuditSeverity::High)
+                } else if entropy < WEAK_MEDIUM_BITS {
+                    Some(AuditSeverity::Medium)
+                } else {
+                    None
+                };
+                if let Some(severity) = severity {
+                    let mut finding = AuditFinding::new(
+                        name,
+                        AuditFindingKind::Weak,
+                        severity,
+                        format!("Weak password (~{entropy:.0} bits)"),
+                    );
+                    finding.entropy_bits = Some((entropy * 10.0).round() / 10.0);
+                    report.findings.push(finding);
+                }
+            }
+            secrets
+                .entry(Sha256::digest(&secret).into())
+                .or_default()
+                .push(name.clone());
+        }
+
+        for names in secrets.values().filter(|names| names.len() > 1) {
+            for name in names {
+                let mut finding = AuditFinding::new(
+                    name,
+                    AuditFindingKind::Reused,
+                    AuditSeverity::High,
+                    format!("Same secret as {} other entries", names.len() - 1),
+                );
+                finding.reused_with = names.iter().filter(|n| *n != name).cloned().collect();
+                report.findings.push(finding);
+            }
+        }
+
+        report.findings.sort_by(|a, b| {
+            b.severity
+                .cmp(&a.severity)
+                .then_with(|| a.name.cmp(&b.name))
+                .then_with(|| a.kind.cmp(&b.kind))
+        });
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{data_mgr::DataInterfaceType, seg_mgr::EncryptionLevel};
+
+    fn kinds(report: &AuditReport, name: &str) -> Vec<AuditFindingKind> {
+        report
+            .findings
+            .iter()
+            .filter(|f| f.name == name)
+            .map(|f| f.kind)
+            .collect()
+    }
+
+    #[test]
+    fn entropy_estimate_collapses_runs() {
+        assert_eq!(estimate_entropy(""), 0.0);
+        assert_eq!(estimate_entropy("aaaaaaaa"), estimate_entropy("aa"));
+        assert!(estimate_entropy("12345678") < 10.0);
+        assert!(estimate_entropy("correct-Horse-7-battery") > WEAK_MEDIUM_BITS);
+    }
+
+    #[test]
+    fn audit_reports_findings() {
+        let mut mgr =
+            PasswordManager::from_device_type(DataInterfaceType::Memory(vec![])).expect("init");
+        mgr.get_data_manager()
+            .init_device(4096, EncryptionLevel::Low)
+            .expect("init device");
+        mgr.save_password("mail", "hunter22", Some("pw".to_string()))
+            .unwrap();
+        mgr.save_password("bank", "hunter22", Some("pw".to_string()))
+            .unwrap();
+        mgr.save_password("api-token", "letmein", None).unwrap();
+        mgr.save_password("vault", "x9$Kq!7zLm#2Vb@8Wn&4", Some("other".to_string()))
+            .unwrap();
+        mgr.save_alias("old-api-token", "api-token").unwrap();
+        mgr.update_entry_attributes("api-token", |attrs| attrs.modified = Some(0))
+            .unwrap();
+        mgr.set_entry_expiry("vault", Some(39 * SECONDS_PER_DAY))
+            .unwrap();
+        mgr.set_entry_expiry("bank", Some(50 * SECONDS_PER_DAY))
+            .unwrap();
+
+        let report = mgr
+            .audit(&AuditOptions {
+                passwords: vec!["wrong".to_string(), "pw".to_string()],
+                stale_days: 30,
+                expiry_days: 7,
+                now: 40 * SECONDS_PER_DAY,
+                breach_db: None,
+            })
+            .unwrap();
+
+        assert_eq!(report.entries, 4);
+        assert_eq!(report.checked, 3);
+        assert_eq!(report.locked, vec!["vault".to_string()]);
+        assert_eq!(
+            kinds(&report, "mail"),
+            vec![
+                AuditFindingKind::Reused,
+                AuditFindingKind::Weak,
+                AuditFindingKind::DefaultKey
+            ]
+        );
+        assert_eq!(
+            kinds(&report, "api-token"),
+            vec![
+                AuditFindingKind::Weak,
+                AuditFindingKind::UnencryptedSecret,
+                AuditFindingKind::Stale
+            ]
+        );
+        assert_eq!(
+            kinds(&report, "vault"),
+            vec![AuditFindingKind::Expired, AuditFindingKind::DefaultKey]
+        );
+        assert!(!kinds(&report, "bank").contains(&AuditFindingKind::ExpiresSoon));
+        let reused = report
+            .findings
+            .iter()
+            .find(|f| f.name == "bank" && f.kind == AuditFindingKind::Reused)
+            .unwrap();
+        assert_eq!(reused.reused_with, vec!["mail".to_string()]);
+        assert_eq!(report.findings[0].severity, AuditSeverity::High);
+    }
+}
@@ -0,0 +1,174 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
+
+use sha1::{Digest, Sha1};
+
+/// Length of the hash prefix that names a range file.
+const RANGE_PREFIX_LEN: usize = 5;
+
+/// Local copy of a breached-password list in the HIBP SHA-1 format, looked up
+/// without any network access.
+#[derive(Debug, Clone)]
+pub enum BreachDb {
+    /// One file of `HASH:COUNT` lines sorted by hash (`pwned-passwords-sha1-ordered-by-hash`).
+    SortedFile(PathBuf),
+    /// A directory of range files named by the first five hash characters
+    /// (`21BD1` or `21BD1.txt`), holding `SUFFIX:COUNT` lines.
+    RangeDir(PathBuf),
+}
+
+impl BreachDb {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        if path.metadata()?.is_dir() {
+            Ok(Self::RangeDir(path.to_path_buf()))
+        } else {
+            Ok(Self::SortedFile(path.to_path_buf()))
+        }
+    }
+
+    /// How many times the password appears in the list (`0` if it does not).
+    pub fn lookup(&self, password: &str) -> io::Result<u64> {
+        let hash = hex::encode_upper(Sha1::digest(password.as_bytes()));
+        match self {
+            Self::SortedFile(path) => lookup_sorted(path, &hash),
+            Self::RangeDir(dir) => lookup_range(dir, &hash),
+        }
+    }
+}
+
+/// Splits a `HASH:COUNT` line; a missing count reads as one occurrence.
+fn parse_line(line: &str) -> Option<(String, u64)> {
+    let line = line.trim();
+    if line.is_empty() {
+        return None;
+    }
+    let (hash, count) = line.split_once(':').unwrap_or((line, "1"));
+    Some((hash.to_ascii_uppercase(), count.trim().parse().unwrap_or(1)))
+}
+
+/// First line that starts at or after `offset`, with the offset just past it.
+fn line_at(reader: &mut BufReader<File>, offset: u64) -> io::Result<Option<(String, u64)>> {
+    let mut buf = Vec::new();
+    if offset == 0 {
+        reader.seek(SeekFrom::Start(0))?;
+    } else {
+        reader.seek(SeekFrom::Start(offset - 1))?;
+        reader.read_until(b'\n', &mut buf)?;
+        buf.clear();
+    }
+    let start = reader.stream_position()?;
+    let read = reader.read_until(b'\n', &mut buf)?;
+    if read == 0 {
+        return Ok(None);
+    }
+    Ok(Some((
+        String::from_utf8_lossy(&buf).into_owned(),
+        start + read as u64,
+    )))
+}
+
+/// Binary search over byte offsets, so multi-gigabyte lists are not read in full.
+fn lookup_sorted(path: &Path, hash: &str) -> io::Result<u64> {
+    let file = File::open(path)?;
+    let mut lo = 0;
+    let mut hi = file.metadata()?.len();
+    let mut reader = BufReader::new(file);
+
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        let Some((line, end)) = line_at(&mut reader, mid)? else {
+            hi = mid;
+            continue;
+        };
+        let Some((line_hash, count)) = parse_line(&line) else {
+            lo = end;
+            continue;
+        };
+        match line_hash.as_str().cmp(hash) {
+            std::cmp::Ordering::Equal => return Ok(count),
+            std::cmp::Ordering::Less => lo = end,
+            std::cmp::Ordering::Greater => hi = mid,
+        }
+    }
+    Ok(0)
+}
+
+fn lookup_range(dir: &Path, hash: &str) -> io::Result<u64> {
+    let (prefix, suffix) = hash.split_at(RANGE_PREFIX_LEN);
+    let lower = prefix.to_ascii_lowercase();
+    let candidates = [
+        prefix.to_string(),
+        format!("{prefix}.txt"),
+        lower.clone(),
+        format!("{lower}.txt"),
+    ];
+    let Some(path) = candidates
+        .iter()
+        .map(|name| dir.join(name))
+        .find(|path| path.is_file())
+    else {
+        return Ok(0);
+    };
+
+    for line in BufReader::new(File::open(path)?).lines() {
+        if let Some((line_suffix, count)) = parse_line(&line?) {
+            if line_suffix == suffix {
+                return Ok(count);
+            }
+        }
+    }
+    Ok(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    fn sha1_upper(password: &str) -> String {
+        hex::encode_upper(Sha1::digest(password.as_bytes()))
+    }
+
+    #[test]
+    fn lookup_sorted_file_and_range_dir() {
+        let base = std::env::temp_dir().join(format!("svpi-breach-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&base);
+        fs::create_dir_all(base.join("ranges")).unwrap();
+
+        let mut lines = ["password", "123456", "qwerty", "letmein"]
+            .iter()
+            .enumerate()
+            .map(|(i, password)| format!("{}:{}", sha1_upper(password), i + 10))
+            .collect::<Vec<_>>();
+        lines.sort();
+        let sorted = base.join("hashes.txt");
+        fs::write(&sorted, lines.join("\r\n")).unwrap();
+
+        let hash = sha1_upper("password");
+        fs::write(
+            base.join("ranges").join(format!("{}.txt", &hash[..5])),
+            format!("0000000000000000000000000000000000A:3\n{}:10\n", &hash[5..]),
+        )
+        .unwrap();
+
+        let db = BreachDb::open(&sorted).unwrap();
+        assert!(matches!(db, BreachDb::SortedFile(_)));
+        assert_eq!(db.lookup("password").unwrap(), 10);
+        assert_eq!(db.lookup("123456").unwrap(), 11);
+        assert_eq!(db.lookup("qwerty").unwrap(), 12);
+        assert_eq!(db.lookup("letmein").unwrap(), 13);
+        assert_eq!(db.lookup("x9$Kq!7zLm#2Vb@8Wn&4").unwrap(), 0);
+
+        let db = BreachDb::open(&base.join("ranges")).unwrap();
+        assert!(matches!(db, BreachDb::RangeDir(_)));
+        assert_eq!(db.lookup("password").unwrap(), 10);
+        assert_eq!(db.lookup("letmein").unwrap(), 0);
+
+        assert!(BreachDb::open(&base.join("missing")).is_err());
+        fs::remove_dir_all(&base).unwrap();
+    }
+}
@@ -0,0 +1,340 @@
+use std::collections::BTreeSet;
+
+use regex::{Regex, RegexBuilder};
+
+use crate::{
+    pass_mgr::{PasswordManager, PasswordManagerError},
+    seg_mgr::{DataType, SegmentError, DATA_NAME_SIZE},
+};
+
+/// Compiled size limit for rename expressions.
+const MAX_RENAME_REGEX_SIZE: usize = 1 << 20;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BulkAction {
+    Remove,
+    Rename(String),
+    /// `None` moves the entry to the top level.
+    Move(Option<String>),
+    ChangeType(DataType),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BulkChange {
+    pub name: String,
+    pub action: BulkAction,
+}
+
+/// A sed-style `s/pattern/replacement/[gi]` rename. Any punctuation character
+/// can stand in for `/`; `\1` and `&` in the replacement refer to the match.
+#[derive(Debug, Clone)]
+pub struct RenameRule {
+    regex: Regex,
+    replacement: String,
+    global: bool,
+}
+
+/// Splits on `delimiter`, keeping `\<delimiter>` as a literal delimiter.
+fn split_sed_parts(body: &str, delimiter: char) -> Vec<String> {
+    let mut parts = vec![String::new()];
+    let mut chars = body.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\\' && chars.peek() == Some(&delimiter) {
+            parts.last_mut().unwrap().push(delimiter);
+            chars.next();
+        } else if c == delimiter {
+            parts.push(String::new());
+        } else {
+            parts.last_mut().unwrap().push(c);
+            if c == '\\' {
+                if let Some(next) = chars.next() {
+                    parts.last_mut().unwrap().push(next);
+                }
+            }
+        }
+    }
+    parts
+}
+
+/// sed replacement syntax -> `Regex::replace` syntax.
+fn convert_replacement(replacement: &str) -> String {
+    let mut out = String::new();
+    let mut chars = replacement.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(d) if d.is_ascii_digit() => out.push_str(&format!("${{{d}}}")),
+                Some('$') => out.push_str("$$"),
+                Some(other) => out.push(other),
+                None => out.push('\\'),
+            },
+            '&' => out.push_str("${0}"),
+            '$' => out.push_str("$$"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+impl RenameRule {
+    pub fn parse(expr: &str) -> Result<Self, String> {
+        let invalid = || format!("Expected s/pattern/replacement/[flags], got '{expr}'");
+        let body = expr.strip_prefix('s').ok_or_else(invalid)?;
+        let delimiter = body.chars().next().ok_or_else(invalid)?;
+        if delimiter.is_alphanumeric() || delimiter.is_whitespace() || delimiter == '\\' {
+            return Err(invalid());
+        }
+
+        let parts = split_sed_parts(&body[delimiter.len_utf8()..], delimiter);
+        let [pattern, replacement, flags] = parts.as_slice() else {
+            return Err(invalid());
+        };
+        if pattern.is_empty() {
+            return Err("Rename pattern is empty".to_string());
+        }
+
+        let mut global = false;
+        let mut case_insensitive = false;
+        for flag in flags.chars() {
+            match flag {
+                'g' => global = true,
+                'i' => case_insensitive = true,
+                other => return Err(format!("Unknown rename flag '{other}'")),
+            }
+        }
+
+        let regex = RegexBuilder::new(pattern)
+            .case_insensitive(case_insensitive)
+            .size_limit(MAX_RENAME_REGEX_SIZE)
+            .build()
+            .map_err(|err| err.to_string())?;
+        Ok(Self {
+            regex,
+            replacement: convert_replacement(replacement),
+            global,
+        })
+    }
+
+    /// New name for `name`, or `None` if the pattern does not match it.
+    pub fn apply(&self, name: &str) -> Option<String> {
+        if !self.regex.is_match(name) {
+            return None;
+        }
+        let renamed = if self.global {
+            self.regex.replace_all(name, self.replacement.as_str())
+        } else {
+            self.regex.replace(name, self.replacement.as_str())
+        };
+        Some(renamed.into_owned())
+    }
+}
+
+/// Renames for the entries `rule` matches (unchanged names are left out).
+/// `is_taken` says whether a name is already used by any segment, keys
+/// included. Fails if a new name is empty, too long, taken, or produced twice.
+pub fn plan_renames<F>(
+    names: &[String],
+    rule: &RenameRule,
+    mut is_taken: F,
+) -> Result<Vec<BulkChange>, String>
+where
+    F: FnMut(&str) -> bool,
+{
+    let mut targets = BTreeSet::new();
+    let mut changes = Vec::new();
+
+    for name in names {
+        let Some(new_name) = rule.apply(name).filter(|new_name| new_name != name) else {
+            continue;
+        };
+        if new_name.is_empty() || new_name.len() > DATA_NAME_SIZE {
+            return Err(format!(
+                "New name for '{name}' must be 1-{DATA_NAME_SIZE} bytes, got '{new_name}'"
+            ));
+        }
+        if is_taken(&new_name) || !targets.insert(new_name.clone()) {
+            return Err(format!(
+                "Renaming '{name}' to '{new_name}' would overwrite an entry"
+            ));
+        }
+        changes.push(BulkChange {
+            name: name.clone(),
+            action: BulkAction::Rename(new_name),
+        });
+    }
+    Ok(changes)
+}
+
+impl PasswordManager {
+    /// Applies all `changes` or none of them: the storage is copied first and
+    /// written back (and the segments reloaded) if any change fails.
+    /// `false` means out of memory, also rolled back.
+    pub fn apply_bulk(&mut self, changes: &[BulkChange]) -> Result<bool, PasswordManagerError> {
+        let snapshot = self
+            .0
+            .get_dump()
+            .map_err(PasswordManagerError::TransactionError)?;
+
+        let result = self.apply_changes(changes);
+        if !matches!(result, Ok(true)) {
+            self.0
+                .set_dump(&snapshot)
+                .map_err(PasswordManagerError::TransactionError)?;
+            self.0
+                .load_segments()
+                .map_err(PasswordManagerError::TransactionError)?;
+        }
+        result
+    }
+
+    fn apply_changes(&mut self, changes: &[BulkChange]) -> Result<bool, PasswordManagerError> {
+        let original = self.load_entry_attributes()?;
+        let mut attributes = original.clone();
+
+        for change in changes {
+            let name = change.name.as_str();
+            let Some(segment) = self.0.find_segment_by_name(name) else {
+                return Err(PasswordManagerError::ReadPasswordError(
+                    SegmentError::NotFound(name.to_string()),
+                ));
+            };
+            match &change.action {
+                BulkAction::Remove => {
+                    segment
+                        .remove()
+                        .map_err(PasswordManagerError::RemovePasswordError)?;
+                    attributes.remove(name);
+                }
+                BulkAction::Rename(new_name) => {
+                    segment
+                        .rename(new_name)
+                        .map_err(PasswordManagerError::RenamePasswordError)?;
+                    if let Some(attrs) = attributes.remove(name) {
+                        attributes.insert(new_name.clone(), attrs);
+                    }
+                    self.retarget_aliases(name, new_name)?;
+                }
+                BulkAction::Move(folder) => {
+                    attributes.entry(name.to_string()).or_default().folder = folder.clone();
+                }
+                BulkAction::ChangeType(data_type) => segment
+                    .set_type(*data_type)
+                    .map_err(PasswordManagerError::ChangeDataTypeError)?,
+            }
+        }
+
+        if attributes == original {
+            return Ok(true);
+        }
+        self.save_entry_attributes(&attributes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{data_mgr::DataInterfaceType, seg_mgr::EncryptionLevel};
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn rename_rule_follows_sed_syntax() {
+        let rule = RenameRule::parse("s/^old-/new-/").unwrap();
+        assert_eq!(rule.apply("old-github").as_deref(), Some("new-github"));
+        assert_eq!(rule.apply("github"), None);
+
+        let rule = RenameRule::parse(r"s|(\w+)/(\w+)|\2.\1 &|").unwrap();
+        assert_eq!(rule.apply("work/gh").as_deref(), Some("gh.work work/gh"));
+
+        let rule = RenameRule::parse("s/A/b/gi").unwrap();
+        assert_eq!(rule.apply("aXa").as_deref(), Some("bXb"));
+        let rule = RenameRule::parse(r"s/\//-/").unwrap();
+        assert_eq!(rule.apply("a/b/c").as_deref(), Some("a-b/c"));
+        let rule = RenameRule::parse("s/x/$1/").unwrap();
+        assert_eq!(rule.apply("x").as_deref(), Some("$1"));
+
+        assert!(RenameRule::parse("old-").is_err());
+        assert!(RenameRule::parse("s/a/b").is_err());
+        assert!(RenameRule::parse("s/a/b/x").is_err());
+        assert!(RenameRule::parse("s/(/b/").is_err());
+    }
+
+    #[test]
+    fn plan_renames_rejects_collisions() {
+        let existing = names(&["old-a", "old-b", "new-b", "c"]);
+        let is_taken = |name: &str| existing.iter().any(|e| e == name) || name == "key";
+        let rule = RenameRule::parse("s/^old-a$/renamed/").unwrap();
+        let plan = plan_renames(&existing, &rule, is_taken).unwrap();
+        assert_eq!(
+            plan,
+            vec![BulkChange {
+                name: "old-a".to_string(),
+                action: BulkAction::Rename("renamed".to_string()),
+            }]
+        );
+
+        let rule = RenameRule::parse("s/^old-/new-/").unwrap();
+        assert!(plan_renames(&existing, &rule, is_taken).is_err());
+        let rule = RenameRule::parse("s/.*/same/").unwrap();
+        assert!(plan_renames(&existing, &rule, is_taken).is_err());
+        let rule = RenameRule::parse("s/^c$/key/").unwrap();
+        assert!(plan_renames(&existing, &rule, is_taken).is_err());
+        let rule = RenameRule::parse("s/^c$/&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&&/").unwrap();
+        assert!(plan_renames(&existing, &rule, is_taken).is_err());
+    }
+
+    #[test]
+    fn apply_bulk_rolls_back_on_failure() {
+        let mut mgr =
+            PasswordManager::from_device_type(DataInterfaceType::Memory(vec![])).expect("init");
+        mgr.get_data_manager()
+            .init_device(2048, EncryptionLevel::Low)
+            .expect("init device");
+        for name in ["tmp/a", "tmp/b", "keep"] {
+            mgr.save_password(name, "secret", None).unwrap();
+        }
+        mgr.add_entry_tags("tmp/a", &names(&["x"])).unwrap();
+
+        let changes = vec![
+            BulkChange {
+                name: "tmp/a".to_string(),
+                action: BulkAction::Rename("a".to_string()),
+            },
+            BulkChange {
+                name: "tmp/b".to_string(),
+                action: BulkAction::Move(Some("old".to_string())),
+            },
+            BulkChange {
+                name: "keep".to_string(),
+                action: BulkAction::ChangeType(DataType::Hex),
+            },
+        ];
+        assert!(mgr.apply_bulk(&changes).unwrap());
+        let attributes = mgr.load_entry_attributes().unwrap();
+        assert_eq!(attributes["a"].tags, names(&["x"]));
+        assert_eq!(attributes["tmp/b"].folder.as_deref(), Some("old"));
+        assert_eq!(
+            mgr.0.find_segment_by_name("keep").unwrap().info.data_type,
+            DataType::Hex
+        );
+
+        let changes = vec![
+            BulkChange {
+                name: "a".to_string(),
+                action: BulkAction::Remove,
+            },
+            BulkChange {
+                name: "missing".to_string(),
+                action: BulkAction::Remove,
+            },
+        ];
+        assert!(mgr.apply_bulk(&changes).is_err());
+        assert!(mgr.0.find_segment_by_name("a").is_some());
+        assert_eq!(
+            mgr.load_entry_attributes().unwrap()["a"].tags,
+            names(&["x"])
+        );
+    }
+}
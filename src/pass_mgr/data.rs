@@ -1,3 +1,5 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
 use crate::{
     pass_mgr::{PasswordManager, PasswordManagerError},
     seg_mgr::{Data, DataType, SegmentError},
@@ -9,9 +11,18 @@ impl PasswordManager {
         name: &str,
         password: &str,
         encryption_key: Option<String>,
+    ) -> Result<bool, PasswordManagerError> {
+        self.save_data(name, Data::from_str_infer(password), encryption_key)
+    }
+
+    /// Stores `data` with its own type (no inference from the text form).
+    pub fn save_data(
+        &mut self,
+        name: &str,
+        data: Data,
+        encryption_key: Option<String>,
     ) -> Result<bool, PasswordManagerError> {
         let dump_protection = self.0.metadata.dump_protection;
-        let data = Data::from_str_infer(password);
         let data_type = data.get_type();
 
         let (data, password_fingerprint) = if let Some(encryption_key) = encryption_key {
@@ -27,17 +38,44 @@ impl PasswordManager {
             )
         };
 
-        self.0
+        let saved = self
+            .0
             .set_segment(name, &data, data_type, password_fingerprint)
-            .map(|seg| seg.is_some())
-            .map_err(PasswordManagerError::SavePasswordError)
+            .map_err(PasswordManagerError::SavePasswordError)?
+            .is_some();
+        if saved {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0);
+            // The timestamp is best effort: the entry is already saved, so neither a
+            // full vault nor a failed attribute write turns the save into an error.
+            let _ = self.update_entry_attributes(name, |attrs| attrs.modified = Some(now));
+        }
+        Ok(saved)
     }
 
+    /// Reads and decrypts an entry in its text form.
+    #[allow(dead_code)]
     pub fn read_password<F>(
         &mut self,
         name: &str,
         get_encryption_key: F,
     ) -> Result<String, PasswordManagerError>
+    where
+        F: FnOnce() -> String,
+    {
+        self.read_data(name, get_encryption_key)?
+            .to_string()
+            .map_err(|err| PasswordManagerError::ReadPasswordError(SegmentError::DataError(err)))
+    }
+
+    /// Reads and decrypts an entry as typed data.
+    pub fn read_data<F>(
+        &mut self,
+        name: &str,
+        get_encryption_key: F,
+    ) -> Result<Data, PasswordManagerError>
     where
         F: FnOnce() -> String,
     {
@@ -64,18 +102,13 @@ impl PasswordManager {
             let encryption_key = get_encryption_key();
             let (_, encryption_key) =
                 self.get_encryption_key(&encryption_key, password_fingerprint)?;
-            let data = data_type
+            data_type
                 .decrypt(&data, &encryption_key, dump_protection)
                 .map_err(|err| {
                     PasswordManagerError::ReadPasswordError(SegmentError::DataError(err))
-                })?;
-            Ok(data.to_string().map_err(|err| {
-                PasswordManagerError::ReadPasswordError(SegmentError::DataError(err))
-            })?)
+                })
         } else {
-            Ok(data.to_string().map_err(|err| {
-                PasswordManagerError::ReadPasswordError(SegmentError::DataError(err))
-            })?)
+            Ok(data)
         }
     }
 
@@ -84,6 +117,7 @@ impl PasswordManager {
             segment
                 .remove()
                 .map_err(PasswordManagerError::RemovePasswordError)?;
+            self.move_entry_attributes(name, None)?;
         }
         Ok(())
     }
@@ -97,6 +131,10 @@ impl PasswordManager {
             segment
                 .rename(new_name)
                 .map_err(PasswordManagerError::RenamePasswordError)?;
+            if old_name != new_name {
+                self.move_entry_attributes(old_name, Some(new_name))?;
+                self.retarget_aliases(old_name, new_name)?;
+            }
         }
         Ok(())
     }
@@ -183,4 +221,15 @@ mod tests {
         let seg = mgr.0.find_segment_by_name("hex").unwrap();
         assert_eq!(seg.info.data_type, DataType::Hex);
     }
+
+    #[test]
+    fn change_data_type_refuses_internal() {
+        let mut mgr = setup_mgr();
+        mgr.save_password("entry", "hi", None).unwrap();
+        let before = mgr.0.find_segment_by_name("entry").unwrap().info.data_type;
+        assert!(mgr.change_data_type("entry", DataType::Internal).is_err());
+        assert!(mgr.0.find_internal_segment("entry").is_none());
+        let seg = mgr.0.find_segment_by_name("entry").unwrap();
+        assert_eq!(seg.info.data_type, before);
+    }
 }
@@ -0,0 +1,197 @@
+use serde::Serialize;
+
+use crate::pass_mgr::{PasswordManager, PasswordManagerError};
+
+/// `list`, `check` and `audit` warn this many days before an entry expires.
+pub const DEFAULT_EXPIRY_WARNING_DAYS: u64 = 30;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Days since 1970-01-01 for a proleptic Gregorian date.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_index = (month as i64 + 9) % 12;
+    let day_of_year = (153 * month_index + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// Inverse of `days_from_civil`.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// `2026-12-31` -> Unix seconds at 00:00 UTC of that day, when the entry
+/// counts as expired. Plain Unix seconds are accepted as well.
+pub fn parse_expiry(text: &str) -> Result<u64, String> {
+    let text = text.trim();
+    if let Ok(seconds) = text.parse::<u64>() {
+        return Ok(seconds);
+    }
+
+    let invalid = || format!("Expected a date as YYYY-MM-DD, got '{text}'");
+    let mut parts = text.splitn(3, '-');
+    let (Some(year), Some(month), Some(day)) = (parts.next(), parts.next(), parts.next()) else {
+        return Err(invalid());
+    };
+    let year = year.parse::<i64>().map_err(|_| invalid())?;
+    let month = month.parse::<u32>().map_err(|_| invalid())?;
+    let day = day.parse::<u32>().map_err(|_| invalid())?;
+    if !(1970..=9999).contains(&year) || !(1..=12).contains(&month) || day == 0 {
+        return Err(invalid());
+    }
+
+    let days = days_from_civil(year, month, day);
+    if civil_from_days(days) != (year, month, day) {
+        return Err(format!("{text} is not a valid date"));
+    }
+    Ok(days as u64 * SECONDS_PER_DAY)
+}
+
+/// Unix seconds -> `YYYY-MM-DD` (UTC).
+pub fn format_date(seconds: u64) -> String {
+    let (year, month, day) = civil_from_days((seconds / SECONDS_PER_DAY) as i64);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExpiryState {
+    Expired,
+    ExpiresSoon,
+}
+
+/// Whether an entry expiring at `expires` needs a warning at `now`.
+pub fn expiry_state(expires: u64, now: u64, warning_days: u64) -> Option<ExpiryState> {
+    if now >= expires {
+        Some(ExpiryState::Expired)
+    } else if expires - now <= warning_days * SECONDS_PER_DAY {
+        Some(ExpiryState::ExpiresSoon)
+    } else {
+        None
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ExpiryWarning {
+    pub name: String,
+    pub expires: u64,
+    pub state: ExpiryState,
+}
+
+impl PasswordManager {
+    /// Sets (or with `None` clears) when an entry expires, in Unix seconds.
+    /// `false` means out of memory.
+    pub fn set_entry_expiry(
+        &mut self,
+        name: &str,
+        expires: Option<u64>,
+    ) -> Result<bool, PasswordManagerError> {
+        self.update_entry_attributes(name, |attrs| attrs.expires = expires)
+    }
+
+    /// When an entry expires, if it has an expiry date.
+    pub fn entry_expiry(&mut self, name: &str) -> Result<Option<u64>, PasswordManagerError> {
+        Ok(self
+            .load_entry_attributes()?
+            .get(name)
+            .and_then(|attrs| attrs.expires))
+    }
+
+    /// Expired and soon-to-expire entries, the earliest first.
+    pub fn expiry_warnings(
+        &mut self,
+        now: u64,
+        warning_days: u64,
+    ) -> Result<Vec<ExpiryWarning>, PasswordManagerError> {
+        let mut warnings = self
+            .load_entry_attributes()?
+            .into_iter()
+            .filter_map(|(name, attrs)| {
+                let expires = attrs.expires?;
+                let state = expiry_state(expires, now, warning_days)?;
+                Some(ExpiryWarning {
+                    name,
+                    expires,
+                    state,
+                })
+            })
+            .collect::<Vec<_>>();
+        warnings.sort_by_key(|warning| warning.expires);
+        Ok(warnings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{data_mgr::DataInterfaceType, seg_mgr::EncryptionLevel};
+
+    #[test]
+    fn expiry_dates_roundtrip() {
+        assert_eq!(parse_expiry("1970-01-01").unwrap(), 0);
+        assert_eq!(parse_expiry("2026-12-31").unwrap(), 1_798_675_200);
+        assert_eq!(parse_expiry("1798675200").unwrap(), 1_798_675_200);
+        assert_eq!(format_date(1_798_675_200 + 3600), "2026-12-31");
+        assert_eq!(
+            format_date(parse_expiry("2024-02-29").unwrap()),
+            "2024-02-29"
+        );
+
+        assert!(parse_expiry("2025-02-29").is_err());
+        assert!(parse_expiry("2026-13-01").is_err());
+        assert!(parse_expiry("31.12.2026").is_err());
+        assert!(parse_expiry("never").is_err());
+    }
+
+    #[test]
+    fn expiry_warnings_cover_expired_and_soon() {
+        let mut mgr =
+            PasswordManager::from_device_type(DataInterfaceType::Memory(vec![])).expect("init");
+        mgr.get_data_manager()
+            .init_device(2048, EncryptionLevel::Low)
+            .expect("init device");
+        for name in ["old", "soon", "later", "never"] {
+            mgr.save_password(name, "token", None).unwrap();
+        }
+        let now = 100 * SECONDS_PER_DAY;
+        mgr.set_entry_expiry("old", Some(now - 1)).unwrap();
+        mgr.set_entry_expiry("soon", Some(now + 10 * SECONDS_PER_DAY))
+            .unwrap();
+        mgr.set_entry_expiry("later", Some(now + 90 * SECONDS_PER_DAY))
+            .unwrap();
+
+        let warnings = mgr.expiry_warnings(now, 30).unwrap();
+        assert_eq!(
+            warnings
+                .iter()
+                .map(|w| (w.name.as_str(), w.state))
+                .collect::<Vec<_>>(),
+            vec![
+                ("old", ExpiryState::Expired),
+                ("soon", ExpiryState::ExpiresSoon)
+            ]
+        );
+
+        assert_eq!(mgr.entry_expiry("old").unwrap(), Some(now - 1));
+        assert_eq!(mgr.entry_expiry("never").unwrap(), None);
+        mgr.set_entry_expiry("old", None).unwrap();
+        assert_eq!(mgr.expiry_warnings(now, 30).unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,153 @@
+use crate::pass_mgr::{PasswordManager, PasswordManagerError};
+
+pub const MAX_TAG_LENGTH: usize = 64;
+pub const MAX_FOLDER_LENGTH: usize = 256;
+
+/// Trimmed tag; tags are single words (no whitespace or commas).
+pub fn normalize_tag(tag: &str) -> Result<String, String> {
+    let tag = tag.trim();
+    if tag.is_empty() {
+        return Err("Tag is empty".to_string());
+    }
+    if tag.len() > MAX_TAG_LENGTH {
+        return Err(format!("Tag '{tag}' is longer than {MAX_TAG_LENGTH} bytes"));
+    }
+    if tag
+        .chars()
+        .any(|c| c.is_whitespace() || c.is_control() || c == ',')
+    {
+        return Err(format!("Tag '{tag}' must not contain whitespace or commas"));
+    }
+    Ok(tag.to_string())
+}
+
+/// `/work//github/` -> `work/github`. `None` is the top level (`""` or `/`).
+pub fn normalize_folder(folder: &str) -> Result<Option<String>, String> {
+    let parts = folder
+        .split('/')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>();
+    if let Some(part) = parts.iter().find(|part| **part == "." || **part == "..") {
+        return Err(format!("Folder must not contain '{part}'"));
+    }
+    if parts.iter().any(|part| part.chars().any(char::is_control)) {
+        return Err("Folder must not contain control characters".to_string());
+    }
+
+    let folder = parts.join("/");
+    if folder.len() > MAX_FOLDER_LENGTH {
+        return Err(format!("Folder is longer than {MAX_FOLDER_LENGTH} bytes"));
+    }
+    Ok((!folder.is_empty()).then_some(folder))
+}
+
+/// Whether an entry in `folder` is inside `parent` (itself or a subfolder).
+pub fn folder_contains(parent: &str, folder: Option<&str>) -> bool {
+    match folder {
+        Some(folder) => folder
+            .strip_prefix(parent)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/')),
+        None => false,
+    }
+}
+
+impl PasswordManager {
+    /// Adds normalized `tags` to an entry, keeping them sorted and unique.
+    /// `false` means out of memory.
+    pub fn add_entry_tags(
+        &mut self,
+        name: &str,
+        tags: &[String],
+    ) -> Result<bool, PasswordManagerError> {
+        self.update_entry_attributes(name, |attrs| {
+            attrs.tags.extend(tags.iter().cloned());
+            attrs.tags.sort();
+            attrs.tags.dedup();
+        })
+    }
+
+    /// Removes `tags` from an entry (all of them if `tags` is empty).
+    /// `false` means out of memory.
+    pub fn remove_entry_tags(
+        &mut self,
+        name: &str,
+        tags: &[String],
+    ) -> Result<bool, PasswordManagerError> {
+        self.update_entry_attributes(name, |attrs| {
+            if tags.is_empty() {
+                attrs.tags.clear();
+            } else {
+                attrs.tags.retain(|tag| !tags.contains(tag));
+            }
+        })
+    }
+
+    /// Moves an entry to a normalized `folder` (`None` is the top level).
+    /// `false` means out of memory.
+    pub fn set_entry_folder(
+        &mut self,
+        name: &str,
+        folder: Option<String>,
+    ) -> Result<bool, PasswordManagerError> {
+        self.update_entry_attributes(name, |attrs| attrs.folder = folder)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{data_mgr::DataInterfaceType, seg_mgr::EncryptionLevel};
+
+    #[test]
+    fn tags_and_folders_are_normalized() {
+        assert_eq!(normalize_tag(" work ").unwrap(), "work");
+        assert!(normalize_tag("").is_err());
+        assert!(normalize_tag("two words").is_err());
+        assert!(normalize_tag("a,b").is_err());
+
+        assert_eq!(
+            normalize_folder("/work//github/").unwrap(),
+            Some("work/github".to_string())
+        );
+        assert_eq!(normalize_folder("/").unwrap(), None);
+        assert!(normalize_folder("work/../home").is_err());
+
+        assert!(folder_contains("work", Some("work")));
+        assert!(folder_contains("work", Some("work/github")));
+        assert!(!folder_contains("work", Some("workshop")));
+        assert!(!folder_contains("work", None));
+    }
+
+    #[test]
+    fn tags_and_folder_are_stored_per_entry() {
+        let mut mgr =
+            PasswordManager::from_device_type(DataInterfaceType::Memory(vec![])).expect("init");
+        mgr.get_data_manager()
+            .init_device(2048, EncryptionLevel::Low)
+            .expect("init device");
+        mgr.save_password("github", "secret", None).unwrap();
+
+        let tags = |tags: &[&str]| tags.iter().map(|t| t.to_string()).collect::<Vec<_>>();
+        mgr.add_entry_tags("github", &tags(&["work", "dev"]))
+            .unwrap();
+        mgr.add_entry_tags("github", &tags(&["dev", "2fa"]))
+            .unwrap();
+        mgr.set_entry_folder("github", Some("work/code".to_string()))
+            .unwrap();
+        let attrs = mgr.load_entry_attributes().unwrap()["github"].clone();
+        assert_eq!(attrs.tags, tags(&["2fa", "dev", "work"]));
+        assert_eq!(attrs.folder.as_deref(), Some("work/code"));
+
+        mgr.remove_entry_tags("github", &tags(&["dev"])).unwrap();
+        assert_eq!(
+            mgr.load_entry_attributes().unwrap()["github"].tags,
+            tags(&["2fa", "work"])
+        );
+        mgr.remove_entry_tags("github", &[]).unwrap();
+        mgr.set_entry_folder("github", None).unwrap();
+        let attrs = mgr.load_entry_attributes().unwrap()["github"].clone();
+        assert!(attrs.tags.is_empty());
+        assert_eq!(attrs.folder, None);
+    }
+}
@@ -0,0 +1,71 @@
+use std::collections::BTreeSet;
+
+use crate::pass_mgr::{PasswordManager, PasswordManagerError};
+
+impl PasswordManager {
+    /// Locks or unlocks an entry. `false` means out of memory.
+    pub fn set_entry_locked(
+        &mut self,
+        name: &str,
+        locked: bool,
+    ) -> Result<bool, PasswordManagerError> {
+        self.update_entry_attributes(name, |attrs| attrs.locked = locked)
+    }
+
+    /// Names of the locked entries.
+    pub fn locked_entries(&mut self) -> Result<BTreeSet<String>, PasswordManagerError> {
+        Ok(self
+            .load_entry_attributes()?
+            .into_iter()
+            .filter(|(_, attrs)| attrs.locked)
+            .map(|(name, _)| name)
+            .collect())
+    }
+
+    /// Fails with `EntryLocked` for the first locked entry among `names`.
+    /// Commands call this before overwriting, renaming or removing entries.
+    pub fn ensure_unlocked<'a, I>(&mut self, names: I) -> Result<(), PasswordManagerError>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let locked = self.locked_entries()?;
+        match names.into_iter().find(|name| locked.contains(*name)) {
+            Some(name) => Err(PasswordManagerError::EntryLocked(name.to_string())),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{data_mgr::DataInterfaceType, seg_mgr::EncryptionLevel};
+
+    #[test]
+    fn lock_survives_overwrite_and_rename() {
+        let mut mgr =
+            PasswordManager::from_device_type(DataInterfaceType::Memory(vec![])).expect("init");
+        mgr.get_data_manager()
+            .init_device(2048, EncryptionLevel::Low)
+            .expect("init device");
+        mgr.save_password("root-ca", "key", None).unwrap();
+        mgr.save_password("notes", "text", None).unwrap();
+
+        assert!(mgr.set_entry_locked("root-ca", true).unwrap());
+        assert!(mgr.ensure_unlocked(["notes"]).is_ok());
+        assert!(matches!(
+            mgr.ensure_unlocked(["notes", "root-ca"]),
+            Err(PasswordManagerError::EntryLocked(name)) if name == "root-ca"
+        ));
+
+        mgr.save_password("root-ca", "new-key", None).unwrap();
+        mgr.rename_password("root-ca", "ca").unwrap();
+        assert_eq!(
+            mgr.locked_entries().unwrap(),
+            BTreeSet::from(["ca".to_string()])
+        );
+
+        mgr.set_entry_locked("ca", false).unwrap();
+        assert!(mgr.ensure_unlocked(["ca"]).is_ok());
+    }
+}
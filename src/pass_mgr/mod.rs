@@ -5,11 +5,35 @@ use crate::{
     seg_mgr::{DataError, DataManagerError, SegmentError, SegmentManager},
 };
 
+mod alias;
+mod attributes;
+mod audit;
+mod breach;
+mod bulk;
 mod data;
 mod encryption;
+mod expiry;
+mod groups;
+mod locks;
+mod otp;
 mod password;
-
+mod rate_limit;
+mod tokens;
+mod urls;
+
+pub use attributes::EntryAttributes;
+pub use audit::{AuditOptions, DEFAULT_STALE_DAYS};
+pub use breach::BreachDb;
+pub use bulk::{plan_renames, BulkAction, BulkChange, RenameRule};
 pub use encryption::EncryptionKeySource;
+pub use expiry::{
+    expiry_state, format_date, parse_expiry, ExpiryState, ExpiryWarning,
+    DEFAULT_EXPIRY_WARNING_DAYS,
+};
+pub use groups::{folder_contains, normalize_folder, normalize_tag};
+pub use rate_limit::{RateLimitPolicy, RateLimited};
+pub use tokens::{ApiToken, TokenScope};
+pub use urls::url_host;
 
 #[derive(Debug, Error)]
 pub enum PasswordManagerError {
@@ -40,6 +64,33 @@ pub enum PasswordManagerError {
     RenamePasswordError(SegmentError),
     #[error("Change data type error: {0}")]
     ChangeDataTypeError(SegmentError),
+
+    #[error("API tokens error: {0}")]
+    ApiTokensError(SegmentError),
+    #[error("Invalid API tokens record")]
+    InvalidApiTokens,
+
+    #[error("Rate limits error: {0}")]
+    RateLimitsError(SegmentError),
+
+    #[error("Entry attributes error: {0}")]
+    EntryAttributesError(SegmentError),
+    #[error("Invalid entry attributes record")]
+    InvalidEntryAttributes,
+
+    #[error("Breach database error: {0}")]
+    BreachDbError(std::io::Error),
+
+    #[error("Transaction error: {0}")]
+    TransactionError(DeviceError),
+
+    #[error("Alias '{0}' points at an entry that no longer exists")]
+    DanglingAlias(String),
+    #[error("Invalid alias: {0}")]
+    InvalidAlias(String),
+
+    #[error("Entry '{0}' is locked")]
+    EntryLocked(String),
 }
 
 pub struct PasswordManager(pub SegmentManager);
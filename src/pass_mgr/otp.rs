@@ -0,0 +1,91 @@
+use crate::{
+    pass_mgr::{PasswordManager, PasswordManagerError},
+    seg_mgr::{Data, DataType, OtpCode, OtpConfig, OtpKind, SegmentError},
+};
+
+impl PasswordManager {
+    /// Code of an OTP entry at `now` (Unix seconds). For HOTP the advanced
+    /// counter is written back, under the same key, before the code is
+    /// returned, so a code is never handed out twice. `None` means out of memory.
+    pub fn otp_code(
+        &mut self,
+        name: &str,
+        mut config: OtpConfig,
+        password: Option<String>,
+        now: u64,
+    ) -> Result<Option<OtpCode>, PasswordManagerError> {
+        let code = config.generate(now);
+        if config.kind == OtpKind::Totp {
+            return Ok(Some(code));
+        }
+
+        let dump_protection = self.0.metadata.dump_protection;
+        let Some(segment) = self.0.find_segment_by_name(name) else {
+            return Err(PasswordManagerError::ReadPasswordError(
+                SegmentError::NotFound(name.to_string()),
+            ));
+        };
+        let password_fingerprint = segment.info.password_fingerprint;
+
+        config.counter += 1;
+        let data = Data::Otp(config);
+        let (data, password_fingerprint) = match password_fingerprint {
+            Some(fingerprint) => {
+                let (fingerprint, encryption_key) =
+                    self.get_encryption_key(&password.unwrap_or_default(), Some(fingerprint))?;
+                let data = data
+                    .encrypt(&encryption_key, dump_protection)
+                    .map_err(PasswordManagerError::EncryptionError)?;
+                (data, Some(fingerprint))
+            }
+            None => (
+                data.to_bytes().map_err(PasswordManagerError::DataError)?,
+                None,
+            ),
+        };
+
+        let saved = self
+            .0
+            .set_segment(name, &data, DataType::Otp, password_fingerprint)
+            .map_err(PasswordManagerError::SavePasswordError)?
+            .is_some();
+        Ok(saved.then_some(code))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{data_mgr::DataInterfaceType, seg_mgr::EncryptionLevel};
+
+    #[test]
+    fn hotp_counter_is_stored_back() {
+        let mut mgr =
+            PasswordManager::from_device_type(DataInterfaceType::Memory(vec![])).expect("init");
+        mgr.get_data_manager()
+            .init_device(2048, EncryptionLevel::Low)
+            .expect("init device");
+        let uri = "otpauth://hotp/x?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ&counter=0";
+        let data = Data::from_str_typed(uri, DataType::Otp).unwrap();
+        mgr.save_data("hotp", data, Some("pw".to_string())).unwrap();
+
+        let mut codes = Vec::new();
+        for _ in 0..2 {
+            let Data::Otp(config) = mgr.read_data("hotp", || "pw".to_string()).unwrap() else {
+                panic!("expected an OTP entry");
+            };
+            let code = mgr
+                .otp_code("hotp", config, Some("pw".to_string()), 0)
+                .unwrap()
+                .unwrap();
+            codes.push(code.code);
+        }
+        // RFC 4226 appendix D, counters 0 and 1.
+        assert_eq!(codes, vec!["755224", "287082"]);
+
+        let Data::Otp(config) = mgr.read_data("hotp", || "pw".to_string()).unwrap() else {
+            panic!("expected an OTP entry");
+        };
+        assert_eq!(config.counter, 2);
+    }
+}
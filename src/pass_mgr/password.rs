@@ -112,7 +112,9 @@ impl PasswordManager {
             .0
             .get_active_segments_mut()
             .into_iter()
-            .filter(|seg| seg.info.data_type != DataType::EncryptionKey)
+            .filter(|seg| {
+                seg.info.data_type != DataType::EncryptionKey && !seg.info.data_type.is_internal()
+            })
             .collect::<Vec<_>>();
 
         for segment in segments {
@@ -0,0 +1,246 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::pass_mgr::{PasswordManager, PasswordManagerError};
+
+const RATE_LIMITS_SEGMENT: &str = "rate_limits";
+
+/// Failures allowed before backoff starts.
+const FREE_ATTEMPTS: u32 = 3;
+const BACKOFF_BASE_SECS: u64 = 1;
+const BACKOFF_MAX_SECS: u64 = 60;
+/// The global counter allows this many times the per-entry failures.
+const GLOBAL_LOCKOUT_FACTOR: u32 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitPolicy {
+    /// Failed attempts on one entry before it is locked out.
+    pub lockout_after: u32,
+    /// How long a lockout lasts; also how long without failures resets the counters.
+    pub lockout_secs: u64,
+}
+
+impl Default for RateLimitPolicy {
+    fn default() -> Self {
+        Self {
+            lockout_after: 10,
+            lockout_secs: 15 * 60,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimitScope {
+    Entry,
+    Global,
+}
+
+impl RateLimitScope {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            RateLimitScope::Entry => "entry",
+            RateLimitScope::Global => "global",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimited {
+    pub scope: RateLimitScope,
+    pub retry_after_secs: u64,
+    pub locked_out: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct FailedAttempts {
+    failures: u32,
+    /// Unix seconds.
+    last_failure: u64,
+    blocked_until: u64,
+    locked_out: bool,
+}
+
+impl FailedAttempts {
+    fn check(&self, now: u64, scope: RateLimitScope) -> Option<RateLimited> {
+        (now < self.blocked_until).then(|| RateLimited {
+            scope,
+            retry_after_secs: self.blocked_until - now,
+            locked_out: self.locked_out,
+        })
+    }
+
+    fn record_failure(
+        &mut self,
+        now: u64,
+        free_attempts: u32,
+        lockout_after: u32,
+        lockout_secs: u64,
+    ) {
+        if now.saturating_sub(self.last_failure) >= lockout_secs {
+            self.failures = 0;
+        }
+        self.failures = self.failures.saturating_add(1);
+        self.last_failure = now;
+
+        self.locked_out = self.failures >= lockout_after;
+        let delay = if self.locked_out {
+            lockout_secs
+        } else if self.failures > free_attempts {
+            let exp = (self.failures - free_attempts - 1).min(16);
+            (BACKOFF_BASE_SECS << exp).min(BACKOFF_MAX_SECS)
+        } else {
+            0
+        };
+        self.blocked_until = now + delay;
+    }
+}
+
+/// Failed password attempts made through the API transports, stored in the
+/// vault so they survive restarts of the server and of the Chrome host.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateLimits {
+    global: FailedAttempts,
+    entries: BTreeMap<String, FailedAttempts>,
+}
+
+impl RateLimits {
+    pub fn check(&self, name: &str, now: u64) -> Option<RateLimited> {
+        let global = self.global.check(now, RateLimitScope::Global);
+        let entry = self
+            .entries
+            .get(name)
+            .and_then(|attempts| attempts.check(now, RateLimitScope::Entry));
+        [global, entry]
+            .into_iter()
+            .flatten()
+            .max_by_key(|limited| limited.retry_after_secs)
+    }
+
+    pub fn record_failure(&mut self, name: &str, now: u64, policy: &RateLimitPolicy) {
+        // Forget entries that are no longer blocked and would be reset anyway.
+        self.entries.retain(|_, attempts| {
+            now < attempts.blocked_until
+                || now.saturating_sub(attempts.last_failure) < policy.lockout_secs
+        });
+
+        self.entries
+            .entry(name.to_string())
+            .or_default()
+            .record_failure(
+                now,
+                FREE_ATTEMPTS,
+                policy.lockout_after,
+                policy.lockout_secs,
+            );
+        self.global.record_failure(
+            now,
+            FREE_ATTEMPTS * GLOBAL_LOCKOUT_FACTOR,
+            policy.lockout_after.saturating_mul(GLOBAL_LOCKOUT_FACTOR),
+            policy.lockout_secs,
+        );
+    }
+
+    /// Clears the entry counter; the global counter only resets after a quiet
+    /// period, so one known password cannot be used to keep guessing others.
+    /// Returns `true` if anything changed.
+    pub fn record_success(&mut self, name: &str) -> bool {
+        self.entries.remove(name).is_some()
+    }
+}
+
+impl PasswordManager {
+    pub fn load_rate_limits(&mut self) -> Result<RateLimits, PasswordManagerError> {
+        let Some(segment) = self.0.find_internal_segment(RATE_LIMITS_SEGMENT) else {
+            return Ok(RateLimits::default());
+        };
+
+        let data = segment
+            .read_data()
+            .map_err(PasswordManagerError::RateLimitsError)?
+            .to_bytes()
+            .map_err(PasswordManagerError::DataError)?;
+
+        // A damaged record must not disable the limiter for good, but it must
+        // not lock everyone out either: start over.
+        Ok(serde_json::from_slice(&data).unwrap_or_default())
+    }
+
+    pub fn save_rate_limits(&mut self, limits: &RateLimits) -> Result<bool, PasswordManagerError> {
+        if *limits == RateLimits::default() {
+            if let Some(segment) = self.0.find_internal_segment(RATE_LIMITS_SEGMENT) {
+                segment
+                    .remove()
+                    .map_err(PasswordManagerError::RateLimitsError)?;
+            }
+            return Ok(true);
+        }
+
+        let data = serde_json::to_vec(limits).unwrap_or_default();
+        self.0
+            .set_internal_segment(RATE_LIMITS_SEGMENT, &data)
+            .map(|seg| seg.is_some())
+            .map_err(PasswordManagerError::RateLimitsError)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_grows_then_locks_out() {
+        let policy = RateLimitPolicy {
+            lockout_after: 6,
+            lockout_secs: 600,
+        };
+        let mut limits = RateLimits::default();
+        let now = 1_000;
+
+        for _ in 0..FREE_ATTEMPTS {
+            limits.record_failure("mail", now, &policy);
+            assert!(limits.check("mail", now).is_none());
+        }
+
+        limits.record_failure("mail", now, &policy);
+        let limited = limits.check("mail", now).expect("backoff");
+        assert_eq!(limited.scope, RateLimitScope::Entry);
+        assert_eq!(limited.retry_after_secs, BACKOFF_BASE_SECS);
+        assert!(!limited.locked_out);
+
+        limits.record_failure("mail", now + 1, &policy);
+        assert_eq!(
+            limits.check("mail", now + 1).unwrap().retry_after_secs,
+            BACKOFF_BASE_SECS * 2
+        );
+        assert!(limits.check("other", now + 1).is_none());
+
+        limits.record_failure("mail", now + 3, &policy);
+        let limited = limits.check("mail", now + 3).expect("lockout");
+        assert!(limited.locked_out);
+        assert_eq!(limited.retry_after_secs, 600);
+
+        assert!(limits.record_success("mail"));
+        assert!(limits.check("mail", now + 3).is_none());
+    }
+
+    #[test]
+    fn global_counter_spans_entries() {
+        let policy = RateLimitPolicy {
+            lockout_after: 2,
+            lockout_secs: 60,
+        };
+        let mut limits = RateLimits::default();
+        for i in 0..(2 * GLOBAL_LOCKOUT_FACTOR) {
+            limits.record_failure(&format!("entry-{i}"), 10, &policy);
+        }
+
+        let limited = limits.check("untouched", 10).expect("global lockout");
+        assert_eq!(limited.scope, RateLimitScope::Global);
+        assert!(limited.locked_out);
+
+        // Counters reset after a quiet period.
+        limits.record_failure("entry-0", 100, &policy);
+        assert!(limits.check("untouched", 100).is_none());
+    }
+}
@@ -0,0 +1,291 @@
+use std::{
+    fmt,
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::pass_mgr::{PasswordManager, PasswordManagerError};
+
+const API_TOKENS_SEGMENT: &str = "api_tokens";
+const API_TOKEN_PREFIX: &str = "svpi_";
+const API_TOKEN_SECRET_SIZE: usize = 32;
+
+/// What an API token may do. Patterns are an exact entry name, a prefix
+/// ending in `*`, or `*` for every entry.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum TokenScope {
+    /// `list`: status, segment and key metadata
+    List,
+    /// `read:<pattern>`: read entry data
+    Read(String),
+    /// `write:<pattern>`: create, change and remove entries
+    Write(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApiToken {
+    pub id: String,
+    pub name: String,
+    /// SHA-256 of the full token string (hex).
+    pub hash: String,
+    pub scopes: Vec<TokenScope>,
+    pub created_at: u64,
+}
+
+fn name_matches(pattern: &str, name: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => name.starts_with(prefix),
+        None => pattern == name,
+    }
+}
+
+impl TokenScope {
+    pub fn allows_list(&self) -> bool {
+        matches!(self, TokenScope::List)
+    }
+
+    pub fn allows_read(&self, name: &str) -> bool {
+        matches!(self, TokenScope::Read(pattern) if name_matches(pattern, name))
+    }
+
+    pub fn allows_write(&self, name: &str) -> bool {
+        matches!(self, TokenScope::Write(pattern) if name_matches(pattern, name))
+    }
+}
+
+impl fmt::Display for TokenScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokenScope::List => write!(f, "list"),
+            TokenScope::Read(pattern) => write!(f, "read:{pattern}"),
+            TokenScope::Write(pattern) => write!(f, "write:{pattern}"),
+        }
+    }
+}
+
+impl FromStr for TokenScope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, pattern) = match s.split_once(':') {
+            Some((kind, pattern)) => (kind, pattern),
+            None => (s, "*"),
+        };
+        if pattern.is_empty() {
+            return Err(format!("Empty pattern in scope '{s}'"));
+        }
+        if pattern[..pattern.len() - 1].contains('*') {
+            return Err(format!("'*' is only allowed at the end of scope '{s}'"));
+        }
+
+        match kind {
+            "list" if pattern == "*" => Ok(TokenScope::List),
+            "read" => Ok(TokenScope::Read(pattern.to_string())),
+            "write" => Ok(TokenScope::Write(pattern.to_string())),
+            _ => Err(format!(
+                "Invalid scope '{s}' (expected list, read[:pattern] or write[:pattern])"
+            )),
+        }
+    }
+}
+
+impl TryFrom<String> for TokenScope {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<TokenScope> for String {
+    fn from(value: TokenScope) -> Self {
+        value.to_string()
+    }
+}
+
+impl ApiToken {
+    pub fn hash_secret(token: &str) -> String {
+        hex::encode(Sha256::digest(token.as_bytes()))
+    }
+
+    pub fn allows_list(&self) -> bool {
+        self.scopes.iter().any(TokenScope::allows_list)
+    }
+
+    pub fn allows_read(&self, name: &str) -> bool {
+        self.scopes.iter().any(|scope| scope.allows_read(name))
+    }
+
+    pub fn allows_write(&self, name: &str) -> bool {
+        self.scopes.iter().any(|scope| scope.allows_write(name))
+    }
+}
+
+impl PasswordManager {
+    pub fn load_api_tokens(&mut self) -> Result<Vec<ApiToken>, PasswordManagerError> {
+        let Some(segment) = self.0.find_internal_segment(API_TOKENS_SEGMENT) else {
+            return Ok(Vec::new());
+        };
+
+        let data = segment
+            .read_data()
+            .map_err(PasswordManagerError::ApiTokensError)?
+            .to_bytes()
+            .map_err(PasswordManagerError::DataError)?;
+
+        serde_json::from_slice(&data).map_err(|_| PasswordManagerError::InvalidApiTokens)
+    }
+
+    fn save_api_tokens(&mut self, tokens: &[ApiToken]) -> Result<bool, PasswordManagerError> {
+        if tokens.is_empty() {
+            if let Some(segment) = self.0.find_internal_segment(API_TOKENS_SEGMENT) {
+                segment
+                    .remove()
+                    .map_err(PasswordManagerError::ApiTokensError)?;
+            }
+            return Ok(true);
+        }
+
+        let data =
+            serde_json::to_vec(tokens).map_err(|_| PasswordManagerError::InvalidApiTokens)?;
+        self.0
+            .set_internal_segment(API_TOKENS_SEGMENT, &data)
+            .map(|seg| seg.is_some())
+            .map_err(PasswordManagerError::ApiTokensError)
+    }
+
+    /// Creates a token and returns its record together with the secret, which
+    /// is not stored and cannot be recovered later. `None` means out of memory.
+    pub fn create_api_token(
+        &mut self,
+        name: &str,
+        scopes: Vec<TokenScope>,
+    ) -> Result<Option<(ApiToken, String)>, PasswordManagerError> {
+        let mut tokens = self.load_api_tokens()?;
+
+        let mut secret = [0u8; API_TOKEN_SECRET_SIZE];
+        OsRng.fill_bytes(&mut secret);
+        let token = format!("{API_TOKEN_PREFIX}{}", hex::encode(secret));
+        let hash = ApiToken::hash_secret(&token);
+
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        let record = ApiToken {
+            id: hash[..8].to_string(),
+            name: name.to_string(),
+            hash,
+            scopes,
+            created_at,
+        };
+        tokens.push(record.clone());
+
+        if !self.save_api_tokens(&tokens)? {
+            return Ok(None);
+        }
+        Ok(Some((record, token)))
+    }
+
+    /// Removes every token whose id or name equals `id_or_name`.
+    pub fn revoke_api_token(
+        &mut self,
+        id_or_name: &str,
+    ) -> Result<Vec<ApiToken>, PasswordManagerError> {
+        let (revoked, kept): (Vec<_>, Vec<_>) = self
+            .load_api_tokens()?
+            .into_iter()
+            .partition(|token| token.id == id_or_name || token.name == id_or_name);
+
+        if !revoked.is_empty() {
+            self.save_api_tokens(&kept)?;
+        }
+        Ok(revoked)
+    }
+
+    pub fn find_api_token(
+        &mut self,
+        token: &str,
+    ) -> Result<Option<ApiToken>, PasswordManagerError> {
+        let hash = ApiToken::hash_secret(token);
+        Ok(self
+            .load_api_tokens()?
+            .into_iter()
+            .find(|record| record.hash == hash))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{data_mgr::DataInterfaceType, seg_mgr::EncryptionLevel};
+
+    fn setup_mgr() -> PasswordManager {
+        let mut mgr =
+            PasswordManager::from_device_type(DataInterfaceType::Memory(vec![])).expect("init");
+        mgr.get_data_manager()
+            .init_device(2048, EncryptionLevel::Low)
+            .expect("init device");
+        mgr
+    }
+
+    #[test]
+    fn scope_parsing_and_matching() {
+        assert_eq!("list".parse::<TokenScope>().unwrap(), TokenScope::List);
+        assert_eq!(
+            "read".parse::<TokenScope>().unwrap(),
+            TokenScope::Read("*".to_string())
+        );
+
+        let scope: TokenScope = "read:github*".parse().unwrap();
+        assert!(scope.allows_read("github-work"));
+        assert!(!scope.allows_read("gitlab"));
+        assert!(!scope.allows_write("github-work"));
+
+        let scope: TokenScope = "write:mail".parse().unwrap();
+        assert!(scope.allows_write("mail"));
+        assert!(!scope.allows_write("mail2"));
+
+        assert!("list:x".parse::<TokenScope>().is_err());
+        assert!("read:a*b".parse::<TokenScope>().is_err());
+        assert!("admin".parse::<TokenScope>().is_err());
+    }
+
+    #[test]
+    fn create_find_and_revoke_tokens() {
+        let mut mgr = setup_mgr();
+        mgr.save_password("api_tokens", "user entry", None).unwrap();
+
+        let (record, token) = mgr
+            .create_api_token("ci", vec![TokenScope::List])
+            .unwrap()
+            .expect("saved");
+        assert!(token.starts_with(API_TOKEN_PREFIX));
+        assert_ne!(record.hash, token);
+
+        let found = mgr.find_api_token(&token).unwrap().expect("found");
+        assert_eq!(found.id, record.id);
+        assert!(found.allows_list());
+        assert!(!found.allows_read("anything"));
+        assert!(mgr.find_api_token("svpi_wrong").unwrap().is_none());
+
+        let revoked = mgr.revoke_api_token(&record.id).unwrap();
+        assert_eq!(revoked.len(), 1);
+        assert!(mgr.find_api_token(&token).unwrap().is_none());
+        assert!(mgr.load_api_tokens().unwrap().is_empty());
+
+        assert_eq!(
+            mgr.read_data("api_tokens", String::new)
+                .unwrap()
+                .to_string()
+                .unwrap(),
+            "user entry"
+        );
+    }
+}
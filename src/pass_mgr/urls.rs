@@ -0,0 +1,289 @@
+use serde::Serialize;
+use url::{Host, Url};
+
+use crate::{
+    pass_mgr::{PasswordManager, PasswordManagerError},
+    seg_mgr::DataType,
+};
+
+/// Public suffixes with more than one label that are common enough to matter
+/// for autofill (country second levels and shared hosting). Anything else is
+/// treated as a single-label suffix (`com`, `de`, ...).
+const MULTI_LABEL_SUFFIXES: &[&str] = &[
+    "ac.jp",
+    "ac.uk",
+    "co.il",
+    "co.in",
+    "co.jp",
+    "co.kr",
+    "co.nz",
+    "co.uk",
+    "co.za",
+    "com.ar",
+    "com.au",
+    "com.br",
+    "com.cn",
+    "com.hk",
+    "com.mx",
+    "com.sg",
+    "com.tr",
+    "com.tw",
+    "com.ua",
+    "edu.au",
+    "gov.au",
+    "gov.uk",
+    "ltd.uk",
+    "me.uk",
+    "ne.jp",
+    "net.au",
+    "net.cn",
+    "or.jp",
+    "org.au",
+    "org.cn",
+    "org.nz",
+    "org.uk",
+    "plc.uk",
+    "appspot.com",
+    "azurewebsites.net",
+    "blogspot.com",
+    "cloudfront.net",
+    "firebaseapp.com",
+    "github.io",
+    "gitlab.io",
+    "herokuapp.com",
+    "netlify.app",
+    "pages.dev",
+    "vercel.app",
+    "web.app",
+    "workers.dev",
+];
+
+/// How an entry URL relates to the page being filled, best first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UrlMatch {
+    /// Same host.
+    Exact,
+    /// The page is on a subdomain of the entry's host (`github.com` for `gist.github.com`).
+    Subdomain,
+    /// Same registrable domain, otherwise unrelated hosts (`login.example.com` for `www.example.com`).
+    Domain,
+}
+
+/// An entry that may hold credentials for a page. Never carries entry data.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UrlCandidate {
+    pub name: String,
+    #[serde(rename = "match")]
+    pub matched: UrlMatch,
+    /// The entry URL that matched.
+    pub url: String,
+}
+
+/// Host of a URL or bare domain (`github.com`, `https://github.com:443/login`),
+/// lowercased and in punycode.
+pub fn url_host(url: &str) -> Option<Host<String>> {
+    let url = url.trim();
+    let parsed = if url.contains("://") {
+        Url::parse(url)
+    } else {
+        Url::parse(&format!("https://{url}"))
+    }
+    .ok()?;
+
+    match parsed.host()?.to_owned() {
+        Host::Domain(domain) => {
+            let domain = domain.trim_end_matches('.');
+            (!domain.is_empty()).then(|| Host::Domain(domain.to_string()))
+        }
+        host => Some(host),
+    }
+}
+
+/// The part of `host` a site owner can register: its public suffix plus one label.
+pub fn registrable_domain(host: &str) -> &str {
+    let suffix_labels = MULTI_LABEL_SUFFIXES
+        .iter()
+        .filter(|suffix| {
+            host.strip_suffix(*suffix)
+                .is_some_and(|rest| rest.ends_with('.'))
+        })
+        .map(|suffix| suffix.split('.').count())
+        .max()
+        .unwrap_or(1);
+
+    match host.rmatch_indices('.').nth(suffix_labels) {
+        Some((idx, _)) => &host[idx + 1..],
+        None => host,
+    }
+}
+
+/// IP addresses only match exactly; domains never match across registrable domains.
+pub fn match_host(entry: &Host<String>, page: &Host<String>) -> Option<UrlMatch> {
+    if entry == page {
+        return Some(UrlMatch::Exact);
+    }
+
+    let (Host::Domain(entry), Host::Domain(page)) = (entry, page) else {
+        return None;
+    };
+    if registrable_domain(entry) != registrable_domain(page) {
+        return None;
+    }
+
+    let subdomain = page
+        .strip_suffix(entry.as_str())
+        .is_some_and(|rest| rest.ends_with('.'));
+    Some(if subdomain {
+        UrlMatch::Subdomain
+    } else {
+        UrlMatch::Domain
+    })
+}
+
+impl PasswordManager {
+    /// Replaces the URLs of an entry (empty clears them). Callers check them
+    /// with `url_host`; URLs without a host never match. `false` means out of memory.
+    pub fn set_entry_urls(
+        &mut self,
+        name: &str,
+        urls: Vec<String>,
+    ) -> Result<bool, PasswordManagerError> {
+        self.update_entry_attributes(name, |attrs| attrs.urls = urls)
+    }
+
+    /// Entries whose URLs match the page `url`, best match first, then by name.
+    /// `None` if `url` has no host.
+    pub fn find_by_url(
+        &mut self,
+        url: &str,
+    ) -> Result<Option<Vec<UrlCandidate>>, PasswordManagerError> {
+        let Some(page) = url_host(url) else {
+            return Ok(None);
+        };
+
+        let entries = self
+            .0
+            .get_active_segments()
+            .into_iter()
+            .filter(|seg| {
+                seg.info.data_type != DataType::EncryptionKey && !seg.info.data_type.is_internal()
+            })
+            .map(|seg| seg.get_name())
+            .collect::<Vec<_>>();
+        let attributes = self.load_entry_attributes()?;
+
+        let mut candidates = entries
+            .into_iter()
+            .filter_map(|name| {
+                let best = attributes
+                    .get(&name)?
+                    .urls
+                    .iter()
+                    .filter_map(|url| {
+                        let matched = match_host(&url_host(url)?, &page)?;
+                        Some((matched, url.clone()))
+                    })
+                    .min_by_key(|(matched, _)| *matched)?;
+                Some(UrlCandidate {
+                    name,
+                    matched: best.0,
+                    url: best.1,
+                })
+            })
+            .collect::<Vec<_>>();
+        candidates.sort_by(|a, b| a.matched.cmp(&b.matched).then_with(|| a.name.cmp(&b.name)));
+
+        Ok(Some(candidates))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{data_mgr::DataInterfaceType, seg_mgr::EncryptionLevel};
+
+    fn host(url: &str) -> Host<String> {
+        url_host(url).expect("host")
+    }
+
+    #[test]
+    fn hosts_and_registrable_domains() {
+        assert_eq!(host("GitHub.com."), Host::Domain("github.com".to_string()));
+        assert_eq!(
+            host("https://user@gist.github.com:8443/x?y"),
+            Host::Domain("gist.github.com".to_string())
+        );
+        assert!(url_host("").is_none());
+
+        assert_eq!(registrable_domain("gist.github.com"), "github.com");
+        assert_eq!(registrable_domain("www.bbc.co.uk"), "bbc.co.uk");
+        assert_eq!(registrable_domain("alice.github.io"), "alice.github.io");
+        assert_eq!(registrable_domain("localhost"), "localhost");
+    }
+
+    #[test]
+    fn match_rules() {
+        let m = |entry: &str, page: &str| match_host(&host(entry), &host(page));
+
+        assert_eq!(
+            m("github.com", "https://github.com/login"),
+            Some(UrlMatch::Exact)
+        );
+        assert_eq!(
+            m("github.com", "gist.github.com"),
+            Some(UrlMatch::Subdomain)
+        );
+        assert_eq!(
+            m("login.example.com", "www.example.com"),
+            Some(UrlMatch::Domain)
+        );
+        assert_eq!(
+            m("login.example.com", "example.com"),
+            Some(UrlMatch::Domain)
+        );
+        assert_eq!(m("github.com", "github.com.evil.net"), None);
+        assert_eq!(m("bbc.co.uk", "itv.co.uk"), None);
+        assert_eq!(m("alice.github.io", "bob.github.io"), None);
+        assert_eq!(
+            m("http://10.0.0.1", "http://10.0.0.1:8080"),
+            Some(UrlMatch::Exact)
+        );
+        assert_eq!(m("10.0.0.1", "10.0.0.2"), None);
+    }
+
+    #[test]
+    fn find_by_url_returns_names_ranked() {
+        let mut mgr =
+            PasswordManager::from_device_type(DataInterfaceType::Memory(vec![])).expect("init");
+        mgr.get_data_manager()
+            .init_device(2048, EncryptionLevel::Low)
+            .expect("init device");
+        for name in ["github-work", "github-gist", "gitlab"] {
+            mgr.save_password(name, "secret", None).unwrap();
+        }
+        let urls = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        mgr.set_entry_urls("github-work", urls(&["https://github.com/login"]))
+            .unwrap();
+        mgr.set_entry_urls("github-gist", urls(&["gist.github.com"]))
+            .unwrap();
+        mgr.set_entry_urls("gitlab", urls(&["gitlab.com"])).unwrap();
+
+        let found = mgr
+            .find_by_url("https://gist.github.com/new")
+            .unwrap()
+            .unwrap();
+        let found = found
+            .iter()
+            .map(|c| (c.name.as_str(), c.matched))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            found,
+            vec![
+                ("github-gist", UrlMatch::Exact),
+                ("github-work", UrlMatch::Subdomain),
+            ]
+        );
+        assert!(mgr.find_by_url("not a url").unwrap().is_none());
+    }
+}
@@ -1,12 +1,17 @@
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 
-use super::segments::SegmentSummary;
+use super::segments::{ListFilter, SegmentSummary};
+
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::{
+    config::SvpiConfig,
     data_mgr::DataInterfaceType,
-    pass_mgr::PasswordManager,
-    seg_mgr::{DataType, ARCHITECTURE_VERSION},
+    pass_mgr::{
+        format_date, url_host, PasswordManager, PasswordManagerError, RateLimitPolicy, RateLimited,
+    },
+    seg_mgr::{Data, DataType, Record, SegmentError, ARCHITECTURE_VERSION, DATA_NAME_SIZE},
     utils::response::SvpiResponse,
 };
 
@@ -25,7 +30,7 @@ impl ApiTransport {
     }
 }
 
-fn cmd(transport: ApiTransport, name: &'static str) -> Option<String> {
+pub(crate) fn cmd(transport: ApiTransport, name: &'static str) -> Option<String> {
     Some(format!("{}.{}", transport.namespace(), name))
 }
 
@@ -34,15 +39,159 @@ pub enum CommandRequest {
     #[serde(rename = "status")]
     Status {},
     #[serde(rename = "list")]
-    List {},
+    List(ListFilter),
     #[serde(rename = "get_data")]
     GetData(GetDataRequest),
+    #[serde(rename = "list_keys")]
+    ListKeys {},
+    #[serde(rename = "set_data")]
+    SetData(SetDataRequest),
+    #[serde(rename = "remove_data")]
+    RemoveData(RemoveDataRequest),
+    #[serde(rename = "rename_data")]
+    RenameData(RenameDataRequest),
+    #[serde(rename = "change_data_type")]
+    ChangeDataType(ChangeDataTypeRequest),
+    #[serde(rename = "change_password")]
+    ChangePassword(ChangePasswordRequest),
+    #[serde(rename = "set_urls")]
+    SetUrls(SetUrlsRequest),
+    #[serde(rename = "find_by_url")]
+    FindByUrl(FindByUrlRequest),
+}
+
+impl CommandRequest {
+    /// Name used in `SvpiResponse::command` (without the transport prefix).
+    pub fn command_name(&self) -> &'static str {
+        match self {
+            CommandRequest::Status {} => "status",
+            CommandRequest::List(_) => "list",
+            CommandRequest::GetData(_) => "get",
+            CommandRequest::ListKeys {} => "list-keys",
+            CommandRequest::SetData(_) => "set",
+            CommandRequest::RemoveData(_) => "remove",
+            CommandRequest::RenameData(_) => "rename",
+            CommandRequest::ChangeDataType(_) => "change-data-type",
+            CommandRequest::ChangePassword(_) => "change-password",
+            CommandRequest::SetUrls(_) => "set-urls",
+            CommandRequest::FindByUrl(_) => "find-by-url",
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct GetDataRequest {
     pub name: String,
     pub password: Option<String>,
+    /// Return a single field of a record.
+    #[serde(default)]
+    pub field: Option<String>,
+    /// Read the entry even if its expiry date has passed.
+    #[serde(default)]
+    pub allow_expired: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SetDataRequest {
+    pub name: String,
+    #[serde(default)]
+    pub data: String,
+    pub password: Option<String>,
+    /// Stores a record with these fields instead of `data` (replaces the whole record).
+    #[serde(default)]
+    pub fields: Option<Record>,
+    /// Stores `data` as this type instead of inferring it.
+    #[serde(default)]
+    pub data_type: Option<DataType>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RemoveDataRequest {
+    pub name: String,
+    #[serde(default)]
+    pub confirm: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RenameDataRequest {
+    pub old_name: String,
+    pub new_name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChangeDataTypeRequest {
+    pub name: String,
+    pub data_type: DataType,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChangePasswordRequest {
+    pub name: String,
+    pub old_password: Option<String>,
+    pub new_password: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SetUrlsRequest {
+    pub name: String,
+    #[serde(default)]
+    pub urls: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FindByUrlRequest {
+    pub url: String,
+}
+
+/// The `confirmation_required` response to return unless `confirm` is set.
+fn require_confirm(
+    confirm: bool,
+    command: Option<String>,
+    action: &str,
+    details: serde_json::Value,
+) -> Option<SvpiResponse> {
+    (!confirm).then(|| SvpiResponse::confirmation_required(command, action, details))
+}
+
+fn data_not_found(command: Option<String>, name: &str) -> SvpiResponse {
+    SvpiResponse::err(
+        command,
+        "data_not_found",
+        format!("Data '{name}' not found"),
+        None,
+    )
+}
+
+fn keys_forbidden(command: Option<String>) -> SvpiResponse {
+    SvpiResponse::err(
+        command,
+        "forbidden",
+        "Encryption keys are not writable via API".to_string(),
+        None,
+    )
+}
+
+/// The error response for an empty or too long entry name, if any.
+fn validate_name(command: &Option<String>, field: &str, name: &str) -> Option<SvpiResponse> {
+    if name.trim().is_empty() {
+        return Some(SvpiResponse::missing_argument(command.clone(), field));
+    }
+    if name.len() > DATA_NAME_SIZE {
+        return Some(SvpiResponse::invalid_argument(
+            command.clone(),
+            field,
+            format!("Name must be at most {DATA_NAME_SIZE} bytes"),
+        ));
+    }
+    None
+}
+
+/// Returns the segment data type, or `None` if the entry does not exist.
+fn entry_data_type(pass_mgr: &mut PasswordManager, name: &str) -> Option<DataType> {
+    pass_mgr
+        .get_data_manager()
+        .find_segment_by_name(name)
+        .map(|seg| seg.info.data_type)
 }
 
 pub fn status(transport: ApiTransport, interface_type: DataInterfaceType) -> SvpiResponse {
@@ -53,6 +202,10 @@ pub fn status(transport: ApiTransport, interface_type: DataInterfaceType) -> Svp
         Err(err) => return SvpiResponse::data_manager_error_public(command, err),
     };
 
+    status_with(&mut pass_mgr, command)
+}
+
+fn status_with(pass_mgr: &mut PasswordManager, command: Option<String>) -> SvpiResponse {
     let seg_mgr = pass_mgr.get_data_manager();
     let found_version = seg_mgr.read_architecture_version().unwrap_or(0);
 
@@ -88,7 +241,49 @@ pub fn status(transport: ApiTransport, interface_type: DataInterfaceType) -> Svp
     )
 }
 
-pub fn list(transport: ApiTransport, interface_type: DataInterfaceType) -> SvpiResponse {
+/// Entries visible through the API (no encryption keys, no internal records).
+/// Unreadable entry attributes leave the tags and folders empty.
+pub fn list_entries(pass_mgr: &mut PasswordManager) -> Vec<SegmentSummary> {
+    let attributes = pass_mgr.load_entry_attributes().unwrap_or_default();
+    let aliases = pass_mgr.aliases().unwrap_or_default();
+    pass_mgr
+        .get_data_manager()
+        .get_active_segments()
+        .into_iter()
+        .filter(|seg| {
+            seg.info.data_type != DataType::EncryptionKey && !seg.info.data_type.is_internal()
+        })
+        .map(|seg| {
+            SegmentSummary::from_segment(&seg)
+                .with_attributes(attributes.get(&seg.get_name()))
+                .with_alias_target(aliases.get(&seg.get_name()))
+        })
+        .collect()
+}
+
+/// Target of `name` if it is an alias, so servers can check the token scope
+/// for the entry that `get` actually reads.
+pub fn alias_target(interface_type: DataInterfaceType, name: &str) -> Option<String> {
+    let mut pass_mgr = PasswordManager::try_load(interface_type).ok()?;
+    pass_mgr.aliases().ok()?.remove(name)
+}
+
+/// Encryption key segments (listed by `list_keys`, never readable).
+pub fn list_key_entries(pass_mgr: &mut PasswordManager) -> Vec<SegmentSummary> {
+    pass_mgr
+        .get_data_manager()
+        .get_active_segments()
+        .into_iter()
+        .filter(|seg| seg.info.data_type == DataType::EncryptionKey)
+        .map(SegmentSummary::from_segment)
+        .collect()
+}
+
+pub fn list(
+    transport: ApiTransport,
+    interface_type: DataInterfaceType,
+    filter: ListFilter,
+) -> SvpiResponse {
     let command = cmd(transport, "list");
 
     let mut pass_mgr = match PasswordManager::try_load(interface_type) {
@@ -96,13 +291,19 @@ pub fn list(transport: ApiTransport, interface_type: DataInterfaceType) -> SvpiR
         Err(err) => return SvpiResponse::data_manager_error_public(command, err),
     };
 
-    let segments = pass_mgr
-        .get_data_manager()
-        .get_active_segments()
-        .into_iter()
-        .filter(|seg| seg.info.data_type != DataType::EncryptionKey)
-        .map(|seg| SegmentSummary::from_segment(&seg))
-        .collect::<Vec<_>>();
+    list_with(&mut pass_mgr, command, filter)
+}
+
+fn list_with(
+    pass_mgr: &mut PasswordManager,
+    command: Option<String>,
+    filter: ListFilter,
+) -> SvpiResponse {
+    let keys = list_key_entries(pass_mgr);
+    let segments = match filter.apply(list_entries(pass_mgr), &keys) {
+        Ok(v) => v,
+        Err((name, message)) => return SvpiResponse::invalid_argument(command, name, message),
+    };
 
     SvpiResponse::ok(command, json!({ "segments": segments }))
 }
@@ -123,7 +324,24 @@ pub fn get_data(
         Err(err) => return SvpiResponse::data_manager_error_public(command, err),
     };
 
-    let name = req.name;
+    get_data_with(&mut pass_mgr, command, req)
+}
+
+fn get_data_with(
+    pass_mgr: &mut PasswordManager,
+    command: Option<String>,
+    req: GetDataRequest,
+) -> SvpiResponse {
+    if req.name.trim().is_empty() {
+        return SvpiResponse::missing_argument(command, "name");
+    }
+
+    let alias = req.name;
+    let name = match pass_mgr.resolve_alias(&alias) {
+        Ok(name) => name,
+        Err(PasswordManagerError::ReadPasswordError(SegmentError::NotFound(_))) => alias.clone(),
+        Err(err) => return SvpiResponse::password_manager_error(command, err),
+    };
     let (encrypted, data_type) = {
         let seg = pass_mgr.get_data_manager().find_segment_by_name(&name);
         let Some(seg) = seg else {
@@ -137,6 +355,23 @@ pub fn get_data(
         (seg.info.password_fingerprint.is_some(), seg.info.data_type)
     };
 
+    let expires = match pass_mgr.entry_expiry(&name) {
+        Ok(v) => v,
+        Err(err) => return SvpiResponse::password_manager_error(command, err),
+    };
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    if let Some(expires) = expires.filter(|&e| now >= e && !req.allow_expired) {
+        return SvpiResponse::err(
+            command,
+            "entry_expired",
+            format!("Entry '{name}' expired on {}", format_date(expires)),
+            Some(json!({ "name": name, "expires": expires })),
+        );
+    }
+
     if data_type == DataType::EncryptionKey {
         return SvpiResponse::err(
             command,
@@ -156,129 +391,747 @@ pub fn get_data(
     }
 
     let password = req.password.filter(|p| !p.is_empty());
-    match pass_mgr.read_password(&name, || password.clone().unwrap_or_default()) {
-        Ok(data) => SvpiResponse::ok(
-            command,
-            json!({
-                "name": name,
-                "data": data,
-                "data_type": data_type.to_string(),
-                "encrypted": encrypted,
-            }),
-        ),
-        Err(err) => SvpiResponse::password_manager_error(command, err),
+    let data = match pass_mgr.read_data(&name, || password.clone().unwrap_or_default()) {
+        Ok(v) => v,
+        Err(err) => return SvpiResponse::password_manager_error(command, err),
+    };
+
+    let mut otp = None;
+    let data = match (data, req.field) {
+        (Data::Record(record), Some(field)) => match record.get(&field) {
+            Some(value) => json!(value),
+            None => {
+                return SvpiResponse::err(
+                    command,
+                    "field_not_found",
+                    format!("Field '{field}' not found in '{name}'"),
+                    Some(json!({ "name": name, "field": field })),
+                )
+            }
+        },
+        (Data::Record(record), None) => json!(record),
+        (_, Some(_)) => {
+            return SvpiResponse::invalid_argument(
+                command,
+                "field",
+                format!("Data '{name}' is not a record"),
+            )
+        }
+        (Data::Otp(config), None) => match pass_mgr.otp_code(&name, config, password, now) {
+            Ok(Some(code)) => {
+                let data = json!(code.code);
+                otp = Some(code);
+                data
+            }
+            Ok(None) => {
+                return SvpiResponse::err(
+                    command,
+                    "not_enough_memory",
+                    "Not enough memory to store the HOTP counter",
+                    None,
+                )
+            }
+            Err(err) => return SvpiResponse::password_manager_error(command, err),
+        },
+        (data, None) => match data.to_string() {
+            Ok(v) => json!(v),
+            Err(err) => {
+                return SvpiResponse::password_manager_error(
+                    command,
+                    PasswordManagerError::DataError(err),
+                )
+            }
+        },
+    };
+
+    let mut result = json!({
+        "name": alias,
+        "data": data,
+        "data_type": data_type.to_string(),
+        "encrypted": encrypted,
+    });
+    if alias != name {
+        result["alias_of"] = json!(name);
     }
+    if let Some(otp) = otp {
+        result["otp"] = json!(otp);
+    }
+    SvpiResponse::ok(command, result)
 }
 
-pub fn handle(
+pub fn list_keys(transport: ApiTransport, interface_type: DataInterfaceType) -> SvpiResponse {
+    let command = cmd(transport, "list-keys");
+
+    let mut pass_mgr = match PasswordManager::try_load(interface_type) {
+        Ok(mgr) => mgr,
+        Err(err) => return SvpiResponse::data_manager_error_public(command, err),
+    };
+
+    list_keys_with(&mut pass_mgr, command)
+}
+
+fn list_keys_with(pass_mgr: &mut PasswordManager, command: Option<String>) -> SvpiResponse {
+    let keys = list_key_entries(pass_mgr);
+
+    SvpiResponse::ok(command, json!({ "keys": keys }))
+}
+
+pub fn set_data(
     transport: ApiTransport,
     interface_type: DataInterfaceType,
-    request: CommandRequest,
+    req: SetDataRequest,
 ) -> SvpiResponse {
-    match request {
-        CommandRequest::Status {} => status(transport, interface_type),
-        CommandRequest::List {} => list(transport, interface_type),
-        CommandRequest::GetData(req) => get_data(transport, interface_type, req),
+    let command = cmd(transport, "set");
+
+    let mut pass_mgr = match PasswordManager::try_load(interface_type) {
+        Ok(mgr) => mgr,
+        Err(err) => return SvpiResponse::data_manager_error_public(command, err),
+    };
+
+    set_data_with(&mut pass_mgr, command, req)
+}
+
+fn set_data_with(
+    pass_mgr: &mut PasswordManager,
+    command: Option<String>,
+    req: SetDataRequest,
+) -> SvpiResponse {
+    if let Some(resp) = validate_name(&command, "name", &req.name) {
+        return resp;
     }
+    // OTP seeds are never inferred, so `set` takes them explicitly.
+    let settable = |data_type: &DataType| data_type.is_user_data() || *data_type == DataType::Otp;
+    if let Some(data_type) = req.data_type.filter(|data_type| !settable(data_type)) {
+        return SvpiResponse::invalid_argument(
+            command,
+            "data_type",
+            format!(
+                "Data type '{}' cannot be set via API",
+                data_type.to_string()
+            ),
+        );
+    }
+    let data = match req.fields {
+        Some(_) if req.data_type.is_some() => {
+            return SvpiResponse::invalid_argument(
+                command,
+                "data_type",
+                "data_type applies to data, not fields",
+            )
+        }
+        Some(_) if !req.data.is_empty() => {
+            return SvpiResponse::invalid_argument(
+                command,
+                "fields",
+                "Pass either data or fields, not both",
+            )
+        }
+        Some(fields) => {
+            let mut record = Record::default();
+            for (field, value) in &fields.0 {
+                if field.trim().is_empty() {
+                    return SvpiResponse::invalid_argument(command, "fields", "Empty field name");
+                }
+                record.set(field.trim(), value);
+            }
+            Data::Record(record)
+        }
+        None if req.data.is_empty() => return SvpiResponse::missing_argument(command, "data"),
+        None => match req.data_type {
+            Some(data_type) => match Data::from_str_typed(&req.data, data_type) {
+                Ok(data) => data,
+                Err(err) => {
+                    return SvpiResponse::invalid_argument(
+                        command,
+                        "data",
+                        format!("Data is not valid {}: {err}", data_type.to_string()),
+                    )
+                }
+            },
+            None if SvpiConfig::plain_by_default_in_cwd() => Data::Plain(req.data.clone()),
+            None => Data::from_str_infer(&req.data),
+        },
+    };
+
+    if entry_data_type(pass_mgr, &req.name) == Some(DataType::EncryptionKey) {
+        return keys_forbidden(command);
+    }
+    // Locked entries are only changed from the CLI (with the master password).
+    if let Err(err) = pass_mgr.ensure_unlocked([req.name.as_str()]) {
+        return SvpiResponse::password_manager_error(command, err);
+    }
+
+    let data_type = data.get_type();
+    let password = req.password.filter(|p| !p.is_empty());
+    let saved = match pass_mgr.save_data(&req.name, data, password.clone()) {
+        Ok(v) => v,
+        Err(err) => return SvpiResponse::password_manager_error(command, err),
+    };
+    if !saved {
+        return SvpiResponse::err(command, "not_enough_memory", "Not enough memory", None);
+    }
+
+    SvpiResponse::ok(
+        command,
+        json!({
+            "saved": true,
+            "name": req.name,
+            "data_type": data_type.to_string(),
+            "encrypted": password.is_some(),
+        }),
+    )
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::{data_mgr::DataInterfaceType, pass_mgr::PasswordManager, seg_mgr::EncryptionLevel};
+pub fn remove_data(
+    transport: ApiTransport,
+    interface_type: DataInterfaceType,
+    req: RemoveDataRequest,
+) -> SvpiResponse {
+    let command = cmd(transport, "remove");
 
-    fn seeded_dump() -> Vec<u8> {
-        let mut pass_mgr =
-            PasswordManager::from_device_type(DataInterfaceType::Memory(vec![])).expect("init mgr");
-        pass_mgr
-            .get_data_manager()
-            .init_device(1024, EncryptionLevel::Low)
-            .expect("init device");
+    let mut pass_mgr = match PasswordManager::try_load(interface_type) {
+        Ok(mgr) => mgr,
+        Err(err) => return SvpiResponse::data_manager_error_public(command, err),
+    };
 
-        // Plain value
-        pass_mgr
-            .save_password("plain", "alpha", None)
-            .expect("save plain");
+    remove_data_with(&mut pass_mgr, command, req)
+}
 
-        // Encrypted value
-        pass_mgr
-            .save_password("secret", "bravo", Some("pw".to_string()))
-            .expect("save encrypted");
+fn remove_data_with(
+    pass_mgr: &mut PasswordManager,
+    command: Option<String>,
+    req: RemoveDataRequest,
+) -> SvpiResponse {
+    if req.name.trim().is_empty() {
+        return SvpiResponse::missing_argument(command, "name");
+    }
 
-        // Encryption key segment (must be hidden/forbidden for API)
-        pass_mgr.set_master_password("master").expect("set master");
-        pass_mgr
-            .add_encryption_key("master", "key", "keypw", EncryptionLevel::default())
-            .expect("add key");
+    match entry_data_type(pass_mgr, &req.name) {
+        None => return data_not_found(command, &req.name),
+        Some(DataType::EncryptionKey) => return keys_forbidden(command),
+        Some(_) => {}
+    }
+    if let Err(err) = pass_mgr.ensure_unlocked([req.name.as_str()]) {
+        return SvpiResponse::password_manager_error(command, err);
+    }
 
-        pass_mgr.get_data_manager().get_dump().expect("dump")
+    if let Some(resp) = require_confirm(
+        req.confirm,
+        command.clone(),
+        "remove",
+        json!({ "name": req.name }),
+    ) {
+        return resp;
     }
 
-    #[test]
-    fn status_ok_on_initialized_device() {
-        let dump = seeded_dump();
-        let resp = status(ApiTransport::Server, DataInterfaceType::Memory(dump));
-        assert!(resp.ok);
-        let result = resp.result.expect("result");
-        assert_eq!(result["status"], "ok");
+    if let Err(err) = pass_mgr.remove_password(&req.name) {
+        return SvpiResponse::password_manager_error(command, err);
     }
 
-    #[test]
-    fn status_reports_not_initialized() {
-        let resp = status(
-            ApiTransport::Server,
-            DataInterfaceType::Memory(vec![0u8; 256]),
-        );
-        assert!(!resp.ok);
-        assert_eq!(resp.error.as_ref().unwrap().code, "device_not_initialized");
+    let mut result = json!({ "removed": true, "name": req.name });
+    let dangling = pass_mgr.aliases_of(&req.name).unwrap_or_default();
+    if !dangling.is_empty() {
+        result["dangling_aliases"] = json!(dangling);
     }
+    SvpiResponse::ok(command, result)
+}
 
-    #[test]
-    fn list_filters_encryption_key_segments() {
-        let dump = seeded_dump();
-        let resp = list(ApiTransport::Server, DataInterfaceType::Memory(dump));
-        assert!(resp.ok);
-        let result = resp.result.expect("result");
-        let segments = result["segments"].as_array().expect("segments");
+pub fn rename_data(
+    transport: ApiTransport,
+    interface_type: DataInterfaceType,
+    req: RenameDataRequest,
+) -> SvpiResponse {
+    let command = cmd(transport, "rename");
 
-        let names = segments
-            .iter()
-            .filter_map(|item| item["name"].as_str())
-            .collect::<Vec<_>>();
+    let mut pass_mgr = match PasswordManager::try_load(interface_type) {
+        Ok(mgr) => mgr,
+        Err(err) => return SvpiResponse::data_manager_error_public(command, err),
+    };
 
-        assert!(names.contains(&"plain"));
-        assert!(names.contains(&"secret"));
-        assert!(!names.contains(&"key"));
+    rename_data_with(&mut pass_mgr, command, req)
+}
+
+fn rename_data_with(
+    pass_mgr: &mut PasswordManager,
+    command: Option<String>,
+    req: RenameDataRequest,
+) -> SvpiResponse {
+    if req.old_name.trim().is_empty() {
+        return SvpiResponse::missing_argument(command, "old_name");
+    }
+    if let Some(resp) = validate_name(&command, "new_name", &req.new_name) {
+        return resp;
     }
 
-    #[test]
-    fn get_data_forbidden_for_encryption_key() {
-        let dump = seeded_dump();
-        let resp = get_data(
-            ApiTransport::Server,
-            DataInterfaceType::Memory(dump),
-            GetDataRequest {
-                name: "key".to_string(),
-                password: None,
-            },
+    match entry_data_type(pass_mgr, &req.old_name) {
+        None => return data_not_found(command, &req.old_name),
+        Some(DataType::EncryptionKey) => return keys_forbidden(command),
+        Some(_) => {}
+    }
+    if req.old_name != req.new_name && entry_data_type(pass_mgr, &req.new_name).is_some() {
+        return SvpiResponse::invalid_argument(
+            command,
+            "new_name",
+            format!("Data '{}' already exists", req.new_name),
         );
-        assert!(!resp.ok);
-        assert_eq!(resp.error.as_ref().unwrap().code, "forbidden");
+    }
+    if let Err(err) = pass_mgr.ensure_unlocked([req.old_name.as_str()]) {
+        return SvpiResponse::password_manager_error(command, err);
     }
 
-    #[test]
-    fn get_data_requires_password_for_encrypted_segments() {
-        let dump = seeded_dump();
-        let resp = get_data(
-            ApiTransport::Server,
-            DataInterfaceType::Memory(dump),
-            GetDataRequest {
-                name: "secret".to_string(),
-                password: None,
-            },
-        );
-        assert!(!resp.ok);
-        assert_eq!(resp.error.as_ref().unwrap().code, "password_required");
+    if let Err(err) = pass_mgr.rename_password(&req.old_name, &req.new_name) {
+        return SvpiResponse::password_manager_error(command, err);
+    }
+
+    SvpiResponse::ok(
+        command,
+        json!({ "renamed": true, "from": req.old_name, "to": req.new_name }),
+    )
+}
+
+pub fn change_data_type(
+    transport: ApiTransport,
+    interface_type: DataInterfaceType,
+    req: ChangeDataTypeRequest,
+) -> SvpiResponse {
+    let command = cmd(transport, "change-data-type");
+
+    let mut pass_mgr = match PasswordManager::try_load(interface_type) {
+        Ok(mgr) => mgr,
+        Err(err) => return SvpiResponse::data_manager_error_public(command, err),
+    };
+
+    change_data_type_with(&mut pass_mgr, command, req)
+}
+
+fn change_data_type_with(
+    pass_mgr: &mut PasswordManager,
+    command: Option<String>,
+    req: ChangeDataTypeRequest,
+) -> SvpiResponse {
+    if req.name.trim().is_empty() {
+        return SvpiResponse::missing_argument(command, "name");
+    }
+    if !req.data_type.is_user_data() {
+        return SvpiResponse::invalid_argument(
+            command,
+            "data_type",
+            format!(
+                "Data type '{}' cannot be assigned via API",
+                req.data_type.to_string()
+            ),
+        );
+    }
+
+    match entry_data_type(pass_mgr, &req.name) {
+        None => return data_not_found(command, &req.name),
+        Some(DataType::EncryptionKey) => return keys_forbidden(command),
+        Some(_) => {}
+    }
+    if let Err(err) = pass_mgr.ensure_unlocked([req.name.as_str()]) {
+        return SvpiResponse::password_manager_error(command, err);
+    }
+
+    if let Err(err) = pass_mgr.change_data_type(&req.name, req.data_type) {
+        return SvpiResponse::password_manager_error(command, err);
+    }
+
+    SvpiResponse::ok(
+        command,
+        json!({ "changed": true, "name": req.name, "data_type": req.data_type.to_string() }),
+    )
+}
+
+pub fn change_password(
+    transport: ApiTransport,
+    interface_type: DataInterfaceType,
+    req: ChangePasswordRequest,
+) -> SvpiResponse {
+    let command = cmd(transport, "change-password");
+
+    let mut pass_mgr = match PasswordManager::try_load(interface_type) {
+        Ok(mgr) => mgr,
+        Err(err) => return SvpiResponse::data_manager_error_public(command, err),
+    };
+
+    change_password_with(&mut pass_mgr, command, req)
+}
+
+fn change_password_with(
+    pass_mgr: &mut PasswordManager,
+    command: Option<String>,
+    req: ChangePasswordRequest,
+) -> SvpiResponse {
+    if req.name.trim().is_empty() {
+        return SvpiResponse::missing_argument(command, "name");
+    }
+
+    let encrypted = {
+        let seg = pass_mgr.get_data_manager().find_segment_by_name(&req.name);
+        let Some(seg) = seg else {
+            return data_not_found(command, &req.name);
+        };
+        if seg.info.data_type == DataType::EncryptionKey {
+            return keys_forbidden(command);
+        }
+        seg.info.password_fingerprint.is_some()
+    };
+    if let Err(err) = pass_mgr.ensure_unlocked([req.name.as_str()]) {
+        return SvpiResponse::password_manager_error(command, err);
+    }
+
+    let old_password = req.old_password.filter(|p| !p.is_empty());
+    let new_password = req.new_password.filter(|p| !p.is_empty());
+    if encrypted && old_password.is_none() {
+        return SvpiResponse::err(
+            command,
+            "password_required",
+            "Old password required",
+            Some(json!({ "name": req.name })),
+        );
+    }
+
+    let data = match pass_mgr.read_data(&req.name, || old_password.unwrap_or_default()) {
+        Ok(v) => v,
+        Err(err) => return SvpiResponse::password_manager_error(command, err),
+    };
+
+    match pass_mgr.save_data(&req.name, data, new_password.clone()) {
+        Ok(true) => SvpiResponse::ok(
+            command,
+            json!({ "changed": true, "name": req.name, "encrypted": new_password.is_some() }),
+        ),
+        Ok(false) => SvpiResponse::err(command, "not_enough_memory", "Not enough memory", None),
+        Err(err) => SvpiResponse::password_manager_error(command, err),
+    }
+}
+
+pub fn set_urls(
+    transport: ApiTransport,
+    interface_type: DataInterfaceType,
+    req: SetUrlsRequest,
+) -> SvpiResponse {
+    let command = cmd(transport, "set-urls");
+
+    let mut pass_mgr = match PasswordManager::try_load(interface_type) {
+        Ok(mgr) => mgr,
+        Err(err) => return SvpiResponse::data_manager_error_public(command, err),
+    };
+
+    set_urls_with(&mut pass_mgr, command, req)
+}
+
+fn set_urls_with(
+    pass_mgr: &mut PasswordManager,
+    command: Option<String>,
+    req: SetUrlsRequest,
+) -> SvpiResponse {
+    if req.name.trim().is_empty() {
+        return SvpiResponse::missing_argument(command, "name");
+    }
+    if let Some(url) = req.urls.iter().find(|url| url_host(url).is_none()) {
+        return SvpiResponse::invalid_argument(command, "urls", format!("Invalid URL '{url}'"));
+    }
+
+    match entry_data_type(pass_mgr, &req.name) {
+        None => return data_not_found(command, &req.name),
+        Some(DataType::EncryptionKey) => return keys_forbidden(command),
+        Some(_) => {}
+    }
+
+    match pass_mgr.set_entry_urls(&req.name, req.urls.clone()) {
+        Ok(true) => SvpiResponse::ok(command, json!({ "name": req.name, "urls": req.urls })),
+        Ok(false) => SvpiResponse::err(command, "not_enough_memory", "Not enough memory", None),
+        Err(err) => SvpiResponse::password_manager_error(command, err),
+    }
+}
+
+pub fn find_by_url(
+    transport: ApiTransport,
+    interface_type: DataInterfaceType,
+    req: FindByUrlRequest,
+) -> SvpiResponse {
+    let command = cmd(transport, "find-by-url");
+
+    let mut pass_mgr = match PasswordManager::try_load(interface_type) {
+        Ok(mgr) => mgr,
+        Err(err) => return SvpiResponse::data_manager_error_public(command, err),
+    };
+
+    find_by_url_with(&mut pass_mgr, command, req)
+}
+
+fn find_by_url_with(
+    pass_mgr: &mut PasswordManager,
+    command: Option<String>,
+    req: FindByUrlRequest,
+) -> SvpiResponse {
+    if req.url.trim().is_empty() {
+        return SvpiResponse::missing_argument(command, "url");
+    }
+
+    match pass_mgr.find_by_url(&req.url) {
+        Ok(Some(candidates)) => {
+            SvpiResponse::ok(command, json!({ "url": req.url, "candidates": candidates }))
+        }
+        Ok(None) => {
+            SvpiResponse::invalid_argument(command, "url", format!("Invalid URL '{}'", req.url))
+        }
+        Err(err) => SvpiResponse::password_manager_error(command, err),
+    }
+}
+
+pub fn handle(
+    transport: ApiTransport,
+    interface_type: DataInterfaceType,
+    request: CommandRequest,
+) -> SvpiResponse {
+    match request {
+        CommandRequest::Status {} => status(transport, interface_type),
+        CommandRequest::List(filter) => list(transport, interface_type, filter),
+        CommandRequest::GetData(req) => get_data(transport, interface_type, req),
+        CommandRequest::ListKeys {} => list_keys(transport, interface_type),
+        CommandRequest::SetData(req) => set_data(transport, interface_type, req),
+        CommandRequest::RemoveData(req) => remove_data(transport, interface_type, req),
+        CommandRequest::RenameData(req) => rename_data(transport, interface_type, req),
+        CommandRequest::ChangeDataType(req) => change_data_type(transport, interface_type, req),
+        CommandRequest::ChangePassword(req) => change_password(transport, interface_type, req),
+        CommandRequest::SetUrls(req) => set_urls(transport, interface_type, req),
+        CommandRequest::FindByUrl(req) => find_by_url(transport, interface_type, req),
+    }
+}
+
+fn rate_limited(command: Option<String>, name: &str, limited: RateLimited) -> SvpiResponse {
+    let message = if limited.locked_out {
+        "Too many failed password attempts; locked out"
+    } else {
+        "Too many failed password attempts; try again later"
+    };
+    SvpiResponse::err(
+        command,
+        "rate_limited",
+        message,
+        Some(json!({
+            "name": name,
+            "scope": limited.scope.as_str(),
+            "retry_after_secs": limited.retry_after_secs,
+            "locked_out": limited.locked_out,
+        })),
+    )
+}
+
+/// Like `handle`, but requests that check an entry password (`get_data`,
+/// `change_password`) are subject to the failed-attempt counters kept in the vault.
+pub fn handle_limited(
+    transport: ApiTransport,
+    interface_type: DataInterfaceType,
+    request: CommandRequest,
+    policy: &RateLimitPolicy,
+) -> SvpiResponse {
+    if !matches!(
+        request,
+        CommandRequest::GetData(_) | CommandRequest::ChangePassword(_)
+    ) {
+        return handle(transport, interface_type, request);
+    }
+
+    let command = cmd(transport, request.command_name());
+    let mut pass_mgr = match PasswordManager::try_load(interface_type) {
+        Ok(mgr) => mgr,
+        Err(err) => return SvpiResponse::data_manager_error_public(command, err),
+    };
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    handle_limited_with(&mut pass_mgr, transport, request, policy, now)
+}
+
+/// Runs any request on an already loaded vault.
+pub fn handle_with(
+    pass_mgr: &mut PasswordManager,
+    transport: ApiTransport,
+    request: CommandRequest,
+) -> SvpiResponse {
+    let command = cmd(transport, request.command_name());
+    match request {
+        CommandRequest::Status {} => status_with(pass_mgr, command),
+        CommandRequest::List(filter) => list_with(pass_mgr, command, filter),
+        CommandRequest::GetData(req) => get_data_with(pass_mgr, command, req),
+        CommandRequest::ListKeys {} => list_keys_with(pass_mgr, command),
+        CommandRequest::SetData(req) => set_data_with(pass_mgr, command, req),
+        CommandRequest::RemoveData(req) => remove_data_with(pass_mgr, command, req),
+        CommandRequest::RenameData(req) => rename_data_with(pass_mgr, command, req),
+        CommandRequest::ChangeDataType(req) => change_data_type_with(pass_mgr, command, req),
+        CommandRequest::ChangePassword(req) => change_password_with(pass_mgr, command, req),
+        CommandRequest::SetUrls(req) => set_urls_with(pass_mgr, command, req),
+        CommandRequest::FindByUrl(req) => find_by_url_with(pass_mgr, command, req),
+    }
+}
+
+/// `handle_limited` on an already loaded vault; `now` is in Unix seconds.
+pub fn handle_limited_with(
+    pass_mgr: &mut PasswordManager,
+    transport: ApiTransport,
+    request: CommandRequest,
+    policy: &RateLimitPolicy,
+    now: u64,
+) -> SvpiResponse {
+    let name = match &request {
+        CommandRequest::GetData(req) => req.name.clone(),
+        CommandRequest::ChangePassword(req) => req.name.clone(),
+        _ => return handle_with(pass_mgr, transport, request),
+    };
+    // Count attempts against the entry itself, so aliases share its counter.
+    // Unresolvable names keep their own; the request fails on them anyway.
+    let name = pass_mgr.resolve_alias(&name).unwrap_or(name);
+    let command = cmd(transport, request.command_name());
+
+    let mut limits = match pass_mgr.load_rate_limits() {
+        Ok(v) => v,
+        Err(err) => return SvpiResponse::password_manager_error(command, err),
+    };
+    if let Some(limited) = limits.check(&name, now) {
+        return rate_limited(command, &name, limited);
+    }
+
+    let resp = handle_with(pass_mgr, transport, request);
+
+    let failed = resp
+        .error
+        .as_ref()
+        .is_some_and(|err| err.code == "password_error");
+    let changed = if failed {
+        limits.record_failure(&name, now, policy);
+        true
+    } else {
+        resp.ok && limits.record_success(&name)
+    };
+    if changed {
+        // An attempt that cannot be counted fails the request, or the lockout would fail open.
+        match pass_mgr.save_rate_limits(&limits) {
+            Ok(true) => {}
+            Ok(false) => {
+                return SvpiResponse::err(
+                    command,
+                    "not_enough_memory",
+                    "Not enough memory to store failed password attempts",
+                    None,
+                )
+            }
+            Err(err) => return SvpiResponse::password_manager_error(command, err),
+        }
+    }
+
+    resp
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{data_mgr::DataInterfaceType, pass_mgr::PasswordManager, seg_mgr::EncryptionLevel};
+
+    fn seeded_dump() -> Vec<u8> {
+        let mut pass_mgr =
+            PasswordManager::from_device_type(DataInterfaceType::Memory(vec![])).expect("init mgr");
+        pass_mgr
+            .get_data_manager()
+            .init_device(2048, EncryptionLevel::Low)
+            .expect("init device");
+
+        // Plain value
+        pass_mgr
+            .save_password("plain", "alpha", None)
+            .expect("save plain");
+
+        // Encrypted value
+        pass_mgr
+            .save_password("secret", "bravo", Some("pw".to_string()))
+            .expect("save encrypted");
+
+        // Encryption key segment (must be hidden/forbidden for API)
+        pass_mgr.set_master_password("master").expect("set master");
+        pass_mgr
+            .add_encryption_key("master", "key", "keypw", EncryptionLevel::default())
+            .expect("add key");
+
+        pass_mgr.get_data_manager().get_dump().expect("dump")
+    }
+
+    #[test]
+    fn status_ok_on_initialized_device() {
+        let dump = seeded_dump();
+        let resp = status(ApiTransport::Server, DataInterfaceType::Memory(dump));
+        assert!(resp.ok);
+        let result = resp.result.expect("result");
+        assert_eq!(result["status"], "ok");
+    }
+
+    #[test]
+    fn status_reports_not_initialized() {
+        let resp = status(
+            ApiTransport::Server,
+            DataInterfaceType::Memory(vec![0u8; 256]),
+        );
+        assert!(!resp.ok);
+        assert_eq!(resp.error.as_ref().unwrap().code, "device_not_initialized");
+    }
+
+    #[test]
+    fn list_filters_encryption_key_segments() {
+        let dump = seeded_dump();
+        let resp = list(
+            ApiTransport::Server,
+            DataInterfaceType::Memory(dump),
+            ListFilter::default(),
+        );
+        assert!(resp.ok);
+        let result = resp.result.expect("result");
+        let segments = result["segments"].as_array().expect("segments");
+
+        let names = segments
+            .iter()
+            .filter_map(|item| item["name"].as_str())
+            .collect::<Vec<_>>();
+
+        assert!(names.contains(&"plain"));
+        assert!(names.contains(&"secret"));
+        assert!(!names.contains(&"key"));
+    }
+
+    #[test]
+    fn get_data_forbidden_for_encryption_key() {
+        let dump = seeded_dump();
+        let resp = get_data(
+            ApiTransport::Server,
+            DataInterfaceType::Memory(dump),
+            GetDataRequest {
+                name: "key".to_string(),
+                password: None,
+                field: None,
+                allow_expired: false,
+            },
+        );
+        assert!(!resp.ok);
+        assert_eq!(resp.error.as_ref().unwrap().code, "forbidden");
+    }
+
+    #[test]
+    fn get_data_requires_password_for_encrypted_segments() {
+        let dump = seeded_dump();
+        let resp = get_data(
+            ApiTransport::Server,
+            DataInterfaceType::Memory(dump),
+            GetDataRequest {
+                name: "secret".to_string(),
+                password: None,
+                field: None,
+                allow_expired: false,
+            },
+        );
+        assert!(!resp.ok);
+        assert_eq!(resp.error.as_ref().unwrap().code, "password_required");
     }
 
     #[test]
@@ -291,6 +1144,8 @@ mod tests {
             GetDataRequest {
                 name: "plain".to_string(),
                 password: None,
+                field: None,
+                allow_expired: false,
             },
         );
         assert!(plain.ok);
@@ -302,9 +1157,619 @@ mod tests {
             GetDataRequest {
                 name: "secret".to_string(),
                 password: Some("pw".to_string()),
+                field: None,
+                allow_expired: false,
             },
         );
         assert!(encrypted_ok.ok);
         assert_eq!(encrypted_ok.result.as_ref().unwrap()["data"], "bravo");
     }
+
+    fn load_seeded() -> PasswordManager {
+        PasswordManager::try_load(DataInterfaceType::Memory(seeded_dump())).expect("load")
+    }
+
+    #[test]
+    fn handle_parses_write_requests() {
+        let req: CommandRequest =
+            serde_json::from_str(r#"{"remove_data": {"name": "plain"}}"#).expect("parse");
+        assert!(matches!(
+            req,
+            CommandRequest::RemoveData(RemoveDataRequest { confirm: false, .. })
+        ));
+
+        let req: CommandRequest =
+            serde_json::from_str(r#"{"change_data_type": {"name": "plain", "data_type": "hex"}}"#)
+                .expect("parse");
+        assert!(matches!(
+            req,
+            CommandRequest::ChangeDataType(ChangeDataTypeRequest {
+                data_type: DataType::Hex,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn list_keys_returns_only_encryption_keys() {
+        let resp = list_keys(
+            ApiTransport::Chrome,
+            DataInterfaceType::Memory(seeded_dump()),
+        );
+        assert!(resp.ok);
+        assert_eq!(resp.command.as_deref(), Some("chrome.list-keys"));
+        let keys = resp.result.unwrap()["keys"].as_array().unwrap().clone();
+        assert_eq!(keys.len(), 1);
+        assert_eq!(keys[0]["name"], "key");
+    }
+
+    #[test]
+    fn set_data_saves_and_overwrites() {
+        let mut mgr = load_seeded();
+        let command = cmd(ApiTransport::Server, "set");
+
+        let resp = set_data_with(
+            &mut mgr,
+            command.clone(),
+            SetDataRequest {
+                name: "new".to_string(),
+                data: "charlie".to_string(),
+                password: Some("pw".to_string()),
+                fields: None,
+                data_type: None,
+            },
+        );
+        assert!(resp.ok);
+        assert_eq!(resp.result.as_ref().unwrap()["encrypted"], true);
+        assert_eq!(
+            mgr.read_data("new", || "pw".to_string())
+                .unwrap()
+                .to_string()
+                .unwrap(),
+            "charlie"
+        );
+
+        let resp = set_data_with(
+            &mut mgr,
+            command,
+            SetDataRequest {
+                name: "plain".to_string(),
+                data: "delta".to_string(),
+                password: None,
+                fields: None,
+                data_type: None,
+            },
+        );
+        assert!(resp.ok);
+        assert_eq!(
+            mgr.read_data("plain", String::new)
+                .unwrap()
+                .to_string()
+                .unwrap(),
+            "delta"
+        );
+    }
+
+    #[test]
+    fn set_data_rejects_bad_input() {
+        let mut mgr = load_seeded();
+        let command = cmd(ApiTransport::Server, "set");
+
+        let resp = set_data_with(
+            &mut mgr,
+            command.clone(),
+            SetDataRequest {
+                name: "x".repeat(DATA_NAME_SIZE + 1),
+                data: "v".to_string(),
+                password: None,
+                fields: None,
+                data_type: None,
+            },
+        );
+        assert_eq!(resp.error.as_ref().unwrap().code, "invalid_argument");
+
+        let resp = set_data_with(
+            &mut mgr,
+            command.clone(),
+            SetDataRequest {
+                name: "new".to_string(),
+                data: String::new(),
+                password: None,
+                fields: None,
+                data_type: None,
+            },
+        );
+        assert_eq!(resp.error.as_ref().unwrap().code, "missing_argument");
+
+        let resp = set_data_with(
+            &mut mgr,
+            command,
+            SetDataRequest {
+                name: "key".to_string(),
+                data: "v".to_string(),
+                password: None,
+                fields: None,
+                data_type: None,
+            },
+        );
+        assert_eq!(resp.error.as_ref().unwrap().code, "forbidden");
+    }
+
+    #[test]
+    fn record_fields_roundtrip() {
+        let mut mgr = load_seeded();
+        let fields = serde_json::from_value::<Record>(json!({
+            "username": "alice",
+            "password": "hunter2",
+            "notes": "",
+        }))
+        .unwrap();
+
+        let resp = set_data_with(
+            &mut mgr,
+            cmd(ApiTransport::Server, "set"),
+            SetDataRequest {
+                name: "login".to_string(),
+                data: String::new(),
+                password: Some("pw".to_string()),
+                fields: Some(fields),
+                data_type: None,
+            },
+        );
+        assert!(resp.ok);
+        assert_eq!(resp.result.as_ref().unwrap()["data_type"], "record");
+
+        let get = |mgr: &mut PasswordManager, field: Option<&str>| {
+            get_data_with(
+                mgr,
+                cmd(ApiTransport::Server, "get"),
+                GetDataRequest {
+                    name: "login".to_string(),
+                    password: Some("pw".to_string()),
+                    field: field.map(str::to_string),
+                    allow_expired: false,
+                },
+            )
+        };
+        let resp = get(&mut mgr, None);
+        assert_eq!(
+            resp.result.as_ref().unwrap()["data"],
+            json!({ "username": "alice", "password": "hunter2" })
+        );
+        let resp = get(&mut mgr, Some("password"));
+        assert_eq!(resp.result.as_ref().unwrap()["data"], "hunter2");
+        let resp = get(&mut mgr, Some("url"));
+        assert_eq!(resp.error.as_ref().unwrap().code, "field_not_found");
+
+        let resp = change_data_type_with(
+            &mut mgr,
+            cmd(ApiTransport::Server, "change-data-type"),
+            ChangeDataTypeRequest {
+                name: "plain".to_string(),
+                data_type: DataType::Record,
+            },
+        );
+        assert_eq!(resp.error.as_ref().unwrap().code, "invalid_argument");
+    }
+
+    #[test]
+    fn remove_data_requires_confirm() {
+        let mut mgr = load_seeded();
+        let command = cmd(ApiTransport::Server, "remove");
+
+        let resp = remove_data_with(
+            &mut mgr,
+            command.clone(),
+            RemoveDataRequest {
+                name: "plain".to_string(),
+                confirm: false,
+            },
+        );
+        assert_eq!(resp.error.as_ref().unwrap().code, "confirmation_required");
+        assert!(mgr
+            .get_data_manager()
+            .find_segment_by_name("plain")
+            .is_some());
+
+        let resp = remove_data_with(
+            &mut mgr,
+            command.clone(),
+            RemoveDataRequest {
+                name: "plain".to_string(),
+                confirm: true,
+            },
+        );
+        assert!(resp.ok);
+        assert!(mgr
+            .get_data_manager()
+            .find_segment_by_name("plain")
+            .is_none());
+
+        let resp = remove_data_with(
+            &mut mgr,
+            command,
+            RemoveDataRequest {
+                name: "key".to_string(),
+                confirm: true,
+            },
+        );
+        assert_eq!(resp.error.as_ref().unwrap().code, "forbidden");
+    }
+
+    #[test]
+    fn rename_data_refuses_existing_target() {
+        let mut mgr = load_seeded();
+        let command = cmd(ApiTransport::Server, "rename");
+
+        let resp = rename_data_with(
+            &mut mgr,
+            command.clone(),
+            RenameDataRequest {
+                old_name: "plain".to_string(),
+                new_name: "secret".to_string(),
+            },
+        );
+        assert_eq!(resp.error.as_ref().unwrap().code, "invalid_argument");
+
+        let resp = rename_data_with(
+            &mut mgr,
+            command,
+            RenameDataRequest {
+                old_name: "plain".to_string(),
+                new_name: "renamed".to_string(),
+            },
+        );
+        assert!(resp.ok);
+        assert_eq!(
+            mgr.read_data("renamed", String::new)
+                .unwrap()
+                .to_string()
+                .unwrap(),
+            "alpha"
+        );
+    }
+
+    #[test]
+    fn locked_entries_refuse_writes() {
+        let mut mgr = load_seeded();
+        mgr.set_entry_locked("plain", true).unwrap();
+        let code = |resp: SvpiResponse| resp.error.unwrap().code;
+
+        let resp = set_data_with(
+            &mut mgr,
+            cmd(ApiTransport::Server, "set"),
+            SetDataRequest {
+                name: "plain".to_string(),
+                data: "overwritten".to_string(),
+                password: None,
+                fields: None,
+                data_type: None,
+            },
+        );
+        assert_eq!(code(resp), "entry_locked");
+
+        let resp = remove_data_with(
+            &mut mgr,
+            cmd(ApiTransport::Server, "remove"),
+            RemoveDataRequest {
+                name: "plain".to_string(),
+                confirm: true,
+            },
+        );
+        assert_eq!(code(resp), "entry_locked");
+
+        let resp = rename_data_with(
+            &mut mgr,
+            cmd(ApiTransport::Server, "rename"),
+            RenameDataRequest {
+                old_name: "plain".to_string(),
+                new_name: "renamed".to_string(),
+            },
+        );
+        assert_eq!(code(resp), "entry_locked");
+
+        let resp = change_data_type_with(
+            &mut mgr,
+            cmd(ApiTransport::Server, "change-data-type"),
+            ChangeDataTypeRequest {
+                name: "plain".to_string(),
+                data_type: DataType::Binary,
+            },
+        );
+        assert_eq!(code(resp), "entry_locked");
+        assert_eq!(
+            mgr.read_data("plain", String::new).unwrap(),
+            Data::Plain("alpha".to_string())
+        );
+    }
+
+    #[test]
+    fn set_with_data_type_skips_inference() {
+        let mut mgr = load_seeded();
+        let set = |data: &str, data_type| SetDataRequest {
+            name: "pin".to_string(),
+            data: data.to_string(),
+            password: None,
+            fields: None,
+            data_type: Some(data_type),
+        };
+
+        let resp = set_data_with(
+            &mut mgr,
+            cmd(ApiTransport::Server, "set"),
+            set("deadbeef", DataType::Plain),
+        );
+        assert_eq!(resp.result.unwrap()["data_type"], "plain");
+        assert_eq!(
+            mgr.read_data("pin", String::new).unwrap(),
+            Data::Plain("deadbeef".to_string())
+        );
+
+        let resp = set_data_with(
+            &mut mgr,
+            cmd(ApiTransport::Server, "set"),
+            set("not hex", DataType::Hex),
+        );
+        assert_eq!(resp.error.unwrap().code, "invalid_argument");
+        let resp = set_data_with(
+            &mut mgr,
+            cmd(ApiTransport::Server, "set"),
+            set("x", DataType::Alias),
+        );
+        assert_eq!(resp.error.unwrap().code, "invalid_argument");
+    }
+
+    #[test]
+    fn otpauth_text_is_plain_unless_asked_for() {
+        let mut mgr = load_seeded();
+        let uri = "otpauth://totp/x?secret=JBSWY3DP";
+        let set = |data_type| SetDataRequest {
+            name: "2fa".to_string(),
+            data: uri.to_string(),
+            password: None,
+            fields: None,
+            data_type,
+        };
+
+        let resp = set_data_with(&mut mgr, cmd(ApiTransport::Server, "set"), set(None));
+        assert_eq!(resp.result.unwrap()["data_type"], "plain");
+        assert_eq!(
+            mgr.read_data("2fa", String::new).unwrap(),
+            Data::Plain(uri.to_string())
+        );
+
+        let resp = set_data_with(
+            &mut mgr,
+            cmd(ApiTransport::Server, "set"),
+            set(Some(DataType::Otp)),
+        );
+        assert_eq!(resp.result.unwrap()["data_type"], "otp");
+    }
+
+    #[test]
+    fn expired_entries_need_allow_expired() {
+        let mut mgr = load_seeded();
+        mgr.set_entry_expiry("plain", Some(1)).unwrap();
+        let get = |allow_expired| GetDataRequest {
+            name: "plain".to_string(),
+            password: None,
+            field: None,
+            allow_expired,
+        };
+
+        let resp = get_data_with(&mut mgr, cmd(ApiTransport::Server, "get"), get(false));
+        assert_eq!(resp.error.unwrap().code, "entry_expired");
+
+        let resp = get_data_with(&mut mgr, cmd(ApiTransport::Server, "get"), get(true));
+        assert_eq!(resp.result.unwrap()["data"], "alpha");
+    }
+
+    #[test]
+    fn change_data_type_refuses_internal() {
+        assert!(serde_json::from_str::<CommandRequest>(
+            r#"{"change_data_type": {"name": "plain", "data_type": "internal"}}"#
+        )
+        .is_err());
+
+        let mut mgr = load_seeded();
+        let resp = change_data_type_with(
+            &mut mgr,
+            cmd(ApiTransport::Server, "change-data-type"),
+            ChangeDataTypeRequest {
+                name: "plain".to_string(),
+                data_type: DataType::Internal,
+            },
+        );
+        assert_eq!(resp.error.as_ref().unwrap().code, "invalid_argument");
+        assert!(mgr
+            .get_data_manager()
+            .find_internal_segment("plain")
+            .is_none());
+    }
+
+    #[test]
+    fn change_data_type_updates_entry() {
+        let mut mgr = load_seeded();
+        let command = cmd(ApiTransport::Server, "change-data-type");
+
+        let resp = change_data_type_with(
+            &mut mgr,
+            command.clone(),
+            ChangeDataTypeRequest {
+                name: "plain".to_string(),
+                data_type: DataType::EncryptionKey,
+            },
+        );
+        assert_eq!(resp.error.as_ref().unwrap().code, "invalid_argument");
+
+        let resp = change_data_type_with(
+            &mut mgr,
+            command,
+            ChangeDataTypeRequest {
+                name: "plain".to_string(),
+                data_type: DataType::Binary,
+            },
+        );
+        assert!(resp.ok);
+        let seg = mgr
+            .get_data_manager()
+            .find_segment_by_name("plain")
+            .unwrap();
+        assert_eq!(seg.info.data_type, DataType::Binary);
+    }
+
+    #[test]
+    fn change_password_reencrypts_entry() {
+        let mut mgr = load_seeded();
+        let command = cmd(ApiTransport::Server, "change-password");
+
+        let resp = change_password_with(
+            &mut mgr,
+            command.clone(),
+            ChangePasswordRequest {
+                name: "secret".to_string(),
+                old_password: None,
+                new_password: Some("pw2".to_string()),
+            },
+        );
+        assert_eq!(resp.error.as_ref().unwrap().code, "password_required");
+
+        let resp = change_password_with(
+            &mut mgr,
+            command,
+            ChangePasswordRequest {
+                name: "secret".to_string(),
+                old_password: Some("pw".to_string()),
+                new_password: None,
+            },
+        );
+        assert!(resp.ok);
+        assert_eq!(resp.result.as_ref().unwrap()["encrypted"], false);
+        assert_eq!(
+            mgr.read_data("secret", String::new)
+                .unwrap()
+                .to_string()
+                .unwrap(),
+            "bravo"
+        );
+    }
+
+    #[test]
+    fn failed_passwords_are_rate_limited() {
+        let mut pass_mgr = load_seeded();
+        let policy = RateLimitPolicy {
+            lockout_after: 5,
+            lockout_secs: 600,
+        };
+        let get = |password: &str| {
+            CommandRequest::GetData(GetDataRequest {
+                name: "secret".to_string(),
+                password: Some(password.to_string()),
+                field: None,
+                allow_expired: false,
+            })
+        };
+
+        let mut codes = Vec::new();
+        for _ in 0..5 {
+            let resp = handle_limited_with(
+                &mut pass_mgr,
+                ApiTransport::Server,
+                get("bad"),
+                &policy,
+                100,
+            );
+            codes.push(resp.error.unwrap().code);
+        }
+        assert_eq!(
+            codes,
+            [
+                "password_error",
+                "password_error",
+                "password_error",
+                "password_error",
+                "rate_limited"
+            ]
+        );
+
+        // Even the right password is refused until the backoff expires.
+        let resp =
+            handle_limited_with(&mut pass_mgr, ApiTransport::Server, get("pw"), &policy, 100);
+        assert_eq!(resp.error.as_ref().unwrap().code, "rate_limited");
+        assert_eq!(resp.command.as_deref(), Some("api.get"));
+
+        let resp =
+            handle_limited_with(&mut pass_mgr, ApiTransport::Server, get("pw"), &policy, 200);
+        assert!(resp.ok);
+        assert!(pass_mgr
+            .load_rate_limits()
+            .unwrap()
+            .check("secret", 200)
+            .is_none());
+    }
+
+    #[test]
+    fn aliases_share_the_rate_limit_of_their_target() {
+        let mut pass_mgr = load_seeded();
+        assert!(pass_mgr.save_alias("legacy", "secret").unwrap());
+        let policy = RateLimitPolicy {
+            lockout_after: 5,
+            lockout_secs: 600,
+        };
+        let get = |name: &str| {
+            CommandRequest::GetData(GetDataRequest {
+                name: name.to_string(),
+                password: Some("bad".to_string()),
+                field: None,
+                allow_expired: false,
+            })
+        };
+
+        for name in ["legacy", "secret", "legacy", "secret"] {
+            let resp =
+                handle_limited_with(&mut pass_mgr, ApiTransport::Server, get(name), &policy, 100);
+            assert_eq!(resp.error.unwrap().code, "password_error");
+        }
+        let resp = handle_limited_with(
+            &mut pass_mgr,
+            ApiTransport::Server,
+            get("legacy"),
+            &policy,
+            100,
+        );
+        assert_eq!(resp.error.unwrap().code, "rate_limited");
+        let limits = pass_mgr.load_rate_limits().unwrap();
+        assert!(limits.check("secret", 100).is_some());
+        assert!(limits.check("legacy", 100).is_none());
+    }
+
+    #[test]
+    fn uncounted_failures_fail_the_request() {
+        let mut pass_mgr = load_seeded();
+        let policy = RateLimitPolicy {
+            lockout_after: 5,
+            lockout_secs: 600,
+        };
+        let mut i = 0;
+        while pass_mgr
+            .save_password(&format!("fill{i}"), &"x".repeat(16), None)
+            .unwrap()
+        {
+            i += 1;
+        }
+
+        let resp = handle_limited_with(
+            &mut pass_mgr,
+            ApiTransport::Server,
+            CommandRequest::GetData(GetDataRequest {
+                name: "secret".to_string(),
+                password: Some("bad".to_string()),
+                field: None,
+                allow_expired: false,
+            }),
+            &policy,
+            100,
+        );
+        assert_eq!(resp.error.unwrap().code, "not_enough_memory");
+    }
 }
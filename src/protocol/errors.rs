@@ -13,12 +13,13 @@ impl SvpiError {
             | "missing_argument"
             | "invalid_argument"
             | "confirmation_required" => 2,
-            "device_not_found" => 3,
+            "device_not_found" | "device_disconnected" => 3,
             "device_not_initialized" => 4,
             "architecture_mismatch" => 5,
             "data_not_found" => 6,
             "password_required" | "password_error" | "master_password_invalid" => 7,
             "not_enough_memory" => 8,
+            "rate_limited" => 9,
             _ => 1,
         }
     }
@@ -85,6 +86,10 @@ impl SvpiResponse {
             PasswordManagerError::ReadPasswordError(SegmentError::DataError(
                 DataError::DecryptionError,
             )) => ("password_error", err.to_string()),
+            PasswordManagerError::BreachDbError(_) => ("io_error", err.to_string()),
+            PasswordManagerError::DanglingAlias(_) => ("dangling_alias", err.to_string()),
+            PasswordManagerError::InvalidAlias(_) => ("invalid_argument", err.to_string()),
+            PasswordManagerError::EntryLocked(_) => ("entry_locked", err.to_string()),
             _ => ("device_error", err.to_string()),
         };
         SvpiResponse::err(cmd, code, message, None)
@@ -0,0 +1,183 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use super::segments::SegmentSummary;
+
+/// Notifications published by the API transports. Events never carry secret
+/// values, only entry names and `SegmentSummary` metadata.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ApiEvent {
+    /// The serial device answered again after being disconnected.
+    DeviceConnected {
+        at: u64,
+    },
+    /// The serial device stopped answering.
+    DeviceDisconnected {
+        at: u64,
+    },
+    EntryAdded {
+        at: u64,
+        entry: SegmentSummary,
+    },
+    EntryRemoved {
+        at: u64,
+        name: String,
+    },
+    EntryRenamed {
+        at: u64,
+        from: String,
+        entry: SegmentSummary,
+    },
+    /// The entry is now encrypted with a different key (or no longer encrypted).
+    EntryRekeyed {
+        at: u64,
+        entry: SegmentSummary,
+    },
+    /// The entry data, data type, tags, folder, lock or expiry changed.
+    EntryUpdated {
+        at: u64,
+        entry: SegmentSummary,
+    },
+}
+
+impl ApiEvent {
+    pub const fn name(&self) -> &'static str {
+        match self {
+            ApiEvent::DeviceConnected { .. } => "device_connected",
+            ApiEvent::DeviceDisconnected { .. } => "device_disconnected",
+            ApiEvent::EntryAdded { .. } => "entry_added",
+            ApiEvent::EntryRemoved { .. } => "entry_removed",
+            ApiEvent::EntryRenamed { .. } => "entry_renamed",
+            ApiEvent::EntryRekeyed { .. } => "entry_rekeyed",
+            ApiEvent::EntryUpdated { .. } => "entry_updated",
+        }
+    }
+}
+
+/// Same stored data under another name: what a rename leaves behind.
+fn same_content(a: &SegmentSummary, b: &SegmentSummary) -> bool {
+    a.fingerprint == b.fingerprint
+        && a.size == b.size
+        && a.data_type == b.data_type
+        && a.password_fingerprint == b.password_fingerprint
+}
+
+/// Compares two listings (see `api::list_entries`) and describes the changes.
+pub fn diff_entries(old: &[SegmentSummary], new: &[SegmentSummary], at: u64) -> Vec<ApiEvent> {
+    let old_by_name: BTreeMap<&str, &SegmentSummary> =
+        old.iter().map(|e| (e.name.as_str(), e)).collect();
+    let new_by_name: BTreeMap<&str, &SegmentSummary> =
+        new.iter().map(|e| (e.name.as_str(), e)).collect();
+
+    let mut removed: Vec<&SegmentSummary> = old
+        .iter()
+        .filter(|e| !new_by_name.contains_key(e.name.as_str()))
+        .collect();
+
+    let mut events = Vec::new();
+    for entry in new {
+        match old_by_name.get(entry.name.as_str()) {
+            Some(prev) if *prev == entry => {}
+            Some(prev) if prev.password_fingerprint != entry.password_fingerprint => {
+                events.push(ApiEvent::EntryRekeyed {
+                    at,
+                    entry: entry.clone(),
+                })
+            }
+            Some(_) => events.push(ApiEvent::EntryUpdated {
+                at,
+                entry: entry.clone(),
+            }),
+            None => match removed.iter().position(|prev| same_content(prev, entry)) {
+                Some(index) => {
+                    let prev = removed.remove(index);
+                    events.push(ApiEvent::EntryRenamed {
+                        at,
+                        from: prev.name.clone(),
+                        entry: entry.clone(),
+                    });
+                }
+                None => events.push(ApiEvent::EntryAdded {
+                    at,
+                    entry: entry.clone(),
+                }),
+            },
+        }
+    }
+
+    events.extend(removed.into_iter().map(|prev| ApiEvent::EntryRemoved {
+        at,
+        name: prev.name.clone(),
+    }));
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, fingerprint: &str, password_fingerprint: Option<&str>) -> SegmentSummary {
+        SegmentSummary {
+            name: name.to_string(),
+            data_type: "plain".to_string(),
+            size: 8,
+            fingerprint: fingerprint.to_string(),
+            password_fingerprint: password_fingerprint.map(str::to_string),
+            tags: Vec::new(),
+            folder: None,
+            alias_of: None,
+            locked: false,
+            expires: None,
+        }
+    }
+
+    #[test]
+    fn diff_reports_each_kind_of_change() {
+        let old = vec![
+            entry("kept", "f1", None),
+            entry("old-name", "f2", None),
+            entry("gone", "f3", None),
+            entry("rekeyed", "f4", Some("aa")),
+            entry("updated", "f5", None),
+        ];
+        let new = vec![
+            entry("kept", "f1", None),
+            entry("new-name", "f2", None),
+            entry("rekeyed", "f4", Some("bb")),
+            entry("updated", "f6", None),
+            entry("added", "f7", None),
+        ];
+
+        let names: Vec<_> = diff_entries(&old, &new, 1)
+            .iter()
+            .map(ApiEvent::name)
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "entry_renamed",
+                "entry_rekeyed",
+                "entry_updated",
+                "entry_added",
+                "entry_removed"
+            ]
+        );
+        assert!(diff_entries(&new, &new, 2).is_empty());
+    }
+
+    #[test]
+    fn events_serialize_without_secrets() {
+        let event = ApiEvent::EntryRenamed {
+            at: 5,
+            from: "a".to_string(),
+            entry: entry("b", "f", None),
+        };
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["event"], "entry_renamed");
+        assert_eq!(json["from"], "a");
+        assert_eq!(json["entry"]["name"], "b");
+        assert!(json["entry"].get("data").is_none());
+    }
+}
@@ -1,3 +1,4 @@
 pub mod api;
 pub mod errors;
+pub mod events;
 pub mod segments;
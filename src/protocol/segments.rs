@@ -1,14 +1,30 @@
-use serde::Serialize;
+use clap::ValueEnum;
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
 
-use crate::seg_mgr::{Data, DataType, Segment};
+use crate::{
+    pass_mgr::{folder_contains, normalize_folder, EntryAttributes},
+    seg_mgr::{Data, DataType, Segment},
+};
 
-#[derive(Debug, Clone, Serialize)]
+/// Compiled size limit for `ListFilter::pattern` (it can come from API clients).
+const MAX_PATTERN_REGEX_SIZE: usize = 1 << 20;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct SegmentSummary {
     pub name: String,
     pub data_type: String,
     pub size: u32,
     pub fingerprint: String,
     pub password_fingerprint: Option<String>,
+    pub tags: Vec<String>,
+    pub folder: Option<String>,
+    /// Target entry name, for aliases.
+    pub alias_of: Option<String>,
+    /// Refuses overwrite, rename and removal (see `svpi lock`).
+    pub locked: bool,
+    /// When the entry expires (Unix seconds).
+    pub expires: Option<u64>,
 }
 
 impl SegmentSummary {
@@ -24,6 +40,298 @@ impl SegmentSummary {
             size: seg.info.size,
             fingerprint: seg.info.fingerprint.to_string(),
             password_fingerprint,
+            tags: Vec::new(),
+            folder: None,
+            alias_of: None,
+            locked: false,
+            expires: None,
+        }
+    }
+
+    /// Sets the target name of an alias entry.
+    pub fn with_alias_target(mut self, target: Option<&String>) -> Self {
+        self.alias_of = target.cloned();
+        self
+    }
+
+    /// Copies the tags, folder, lock and expiry kept in the entry attributes.
+    pub fn with_attributes(mut self, attrs: Option<&EntryAttributes>) -> Self {
+        if let Some(attrs) = attrs {
+            self.tags = attrs.tags.clone();
+            self.folder = attrs.folder.clone();
+            self.locked = attrs.locked;
+            self.expires = attrs.expires;
+        }
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ListSort {
+    Name,
+    Size,
+    Type,
+}
+
+/// Filters for `list` (CLI, server and Chrome API); unset fields match everything.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ListFilter {
+    /// Glob on the name (`*`, `?`), or a regular expression if `regex` is set.
+    pub pattern: Option<String>,
+    pub regex: bool,
+    pub data_type: Option<DataType>,
+    pub encrypted: Option<bool>,
+    /// Encryption key name, or a key fingerprint (hex; a prefix is enough).
+    pub key: Option<String>,
+    pub min_size: Option<u32>,
+    pub max_size: Option<u32>,
+    /// Entries must have every one of these tags.
+    pub tags: Vec<String>,
+    /// Folder path; entries in its subfolders match as well.
+    pub folder: Option<String>,
+    /// Device order when unset.
+    pub sort: Option<ListSort>,
+    pub desc: bool,
+}
+
+fn glob_to_regex(glob: &str) -> String {
+    let mut regex = String::from("^");
+    for c in glob.chars() {
+        match c {
+            '*' => regex.push_str(".*"),
+            '?' => regex.push('.'),
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push('$');
+    regex
+}
+
+impl ListFilter {
+    /// Filters and sorts `segments`. `keys` are the encryption key segments, so
+    /// that `key` can name one. Errors carry the name of the offending field.
+    pub fn apply(
+        &self,
+        segments: Vec<SegmentSummary>,
+        keys: &[SegmentSummary],
+    ) -> Result<Vec<SegmentSummary>, (&'static str, String)> {
+        let pattern = match &self.pattern {
+            Some(pattern) if !pattern.is_empty() => {
+                let source = if self.regex {
+                    pattern.clone()
+                } else {
+                    glob_to_regex(pattern)
+                };
+                let regex = RegexBuilder::new(&source)
+                    .size_limit(MAX_PATTERN_REGEX_SIZE)
+                    .build()
+                    .map_err(|err| ("pattern", err.to_string()))?;
+                Some(regex)
+            }
+            _ => None,
+        };
+
+        let key_fingerprint = match self.key.as_deref().filter(|key| !key.is_empty()) {
+            Some(key) => match keys.iter().find(|seg| seg.name == key) {
+                Some(seg) => Some((seg.fingerprint.clone(), true)),
+                None if key.chars().all(|c| c.is_ascii_hexdigit()) => {
+                    Some((key.to_ascii_lowercase(), false))
+                }
+                None => return Err(("key", format!("No encryption key named '{key}'"))),
+            },
+            None => None,
+        };
+        let data_type = self.data_type.map(|data_type| data_type.to_string());
+        let folder = match self.folder.as_deref().map(normalize_folder) {
+            Some(Ok(folder)) => folder,
+            Some(Err(message)) => return Err(("folder", message)),
+            None => None,
+        };
+
+        let mut segments = segments
+            .into_iter()
+            .filter(|seg| {
+                let matches_key = |(fingerprint, exact): &(String, bool)| {
+                    seg.password_fingerprint.as_ref().is_some_and(|fp| {
+                        if *exact {
+                            fp == fingerprint
+                        } else {
+                            fp.starts_with(fingerprint.as_str())
+                        }
+                    })
+                };
+                pattern
+                    .as_ref()
+                    .is_none_or(|re: &Regex| re.is_match(&seg.name))
+                    && data_type.as_ref().is_none_or(|t| seg.data_type == *t)
+                    && self
+                        .encrypted
+                        .is_none_or(|encrypted| seg.password_fingerprint.is_some() == encrypted)
+                    && key_fingerprint.as_ref().is_none_or(matches_key)
+                    && self.min_size.is_none_or(|min| seg.size >= min)
+                    && self.max_size.is_none_or(|max| seg.size <= max)
+                    && self.tags.iter().all(|tag| seg.tags.contains(tag))
+                    && folder
+                        .as_deref()
+                        .is_none_or(|folder| folder_contains(folder, seg.folder.as_deref()))
+            })
+            .collect::<Vec<_>>();
+
+        match self.sort {
+            Some(ListSort::Name) => segments.sort_by(|a, b| a.name.cmp(&b.name)),
+            Some(ListSort::Size) => {
+                segments.sort_by(|a, b| a.size.cmp(&b.size).then_with(|| a.name.cmp(&b.name)))
+            }
+            Some(ListSort::Type) => segments.sort_by(|a, b| {
+                a.data_type
+                    .cmp(&b.data_type)
+                    .then_with(|| a.name.cmp(&b.name))
+            }),
+            None => {}
         }
+        if self.desc {
+            segments.reverse();
+        }
+        Ok(segments)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(name: &str, data_type: &str, size: u32, key: Option<&str>) -> SegmentSummary {
+        SegmentSummary {
+            name: name.to_string(),
+            data_type: data_type.to_string(),
+            size,
+            fingerprint: format!("{name}-fp"),
+            password_fingerprint: key.map(str::to_string),
+            tags: Vec::new(),
+            folder: None,
+            alias_of: None,
+            locked: false,
+            expires: None,
+        }
+    }
+
+    fn names(segments: &[SegmentSummary]) -> Vec<&str> {
+        segments.iter().map(|seg| seg.name.as_str()).collect()
+    }
+
+    #[test]
+    fn list_filter_matches_and_sorts() {
+        let segments = vec![
+            summary("github", "plain", 30, Some("aa11")),
+            summary("gitlab", "record", 80, Some("bb22")),
+            summary("notes", "plain", 10, None),
+            summary("git.key", "hex", 50, None),
+        ];
+        let keys = vec![SegmentSummary {
+            fingerprint: "aa11".to_string(),
+            ..summary("work", "encryptionkey", 64, None)
+        }];
+        let apply = |filter: ListFilter| filter.apply(segments.clone(), &keys).unwrap();
+
+        let filter = ListFilter {
+            pattern: Some("git*".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(names(&apply(filter)), vec!["github", "gitlab", "git.key"]);
+
+        let filter = ListFilter {
+            pattern: Some("^git(hub|lab)$".to_string()),
+            regex: true,
+            sort: Some(ListSort::Size),
+            desc: true,
+            ..Default::default()
+        };
+        assert_eq!(names(&apply(filter)), vec!["gitlab", "github"]);
+
+        let filter = ListFilter {
+            data_type: Some(DataType::Plain),
+            encrypted: Some(false),
+            ..Default::default()
+        };
+        assert_eq!(names(&apply(filter)), vec!["notes"]);
+
+        let filter = ListFilter {
+            key: Some("work".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(names(&apply(filter)), vec!["github"]);
+        let filter = ListFilter {
+            key: Some("BB".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(names(&apply(filter)), vec!["gitlab"]);
+
+        let filter = ListFilter {
+            min_size: Some(20),
+            max_size: Some(60),
+            sort: Some(ListSort::Name),
+            ..Default::default()
+        };
+        assert_eq!(names(&apply(filter)), vec!["git.key", "github"]);
+
+        let filter = ListFilter {
+            pattern: Some("(".to_string()),
+            regex: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            filter.apply(segments.clone(), &keys).unwrap_err().0,
+            "pattern"
+        );
+        let filter = ListFilter {
+            key: Some("home".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(filter.apply(segments.clone(), &keys).unwrap_err().0, "key");
+    }
+
+    #[test]
+    fn list_filter_matches_tags_and_folders() {
+        let grouped = |name: &str, tags: &[&str], folder: Option<&str>| SegmentSummary {
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            folder: folder.map(str::to_string),
+            ..summary(name, "plain", 10, None)
+        };
+        let segments = vec![
+            grouped("github", &["dev", "work"], Some("work/code")),
+            grouped("jira", &["work"], Some("work")),
+            grouped("bank", &["finance"], Some("home")),
+            grouped("notes", &[], None),
+        ];
+        let apply = |filter: ListFilter| filter.apply(segments.clone(), &[]).unwrap();
+
+        let filter = ListFilter {
+            tags: vec!["work".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(names(&apply(filter)), vec!["github", "jira"]);
+        let filter = ListFilter {
+            tags: vec!["work".to_string(), "dev".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(names(&apply(filter)), vec!["github"]);
+
+        let filter = ListFilter {
+            folder: Some("/work/".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(names(&apply(filter)), vec!["github", "jira"]);
+        let filter = ListFilter {
+            folder: Some("work/code".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(names(&apply(filter)), vec!["github"]);
+        let filter = ListFilter {
+            folder: Some("/".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(apply(filter).len(), 4);
     }
 }
@@ -2,15 +2,28 @@ use base58::{FromBase58, ToBase58};
 use base64::Engine;
 use borsh::{BorshDeserialize, BorshSerialize};
 use borsh_derive::{BorshDeserialize, BorshSerialize};
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::io::{Read, Write};
 use std::str::FromStr;
 use thiserror::Error;
 
+use crate::seg_mgr::{OtpConfig, Record};
+
 pub const DATA_NAME_SIZE: usize = 32;
 pub const DATA_FINGERPRINT_SIZE: usize = 4;
 
+/// Prefix of typed values in export files
+/// (`<name> = data:...;[fp=<hex>;][tags=<tag>+<tag>;][folder=<path>;]<type>,<data>`).
+const EXPORT_DATA_URI: &str = "data:application/vnd.binqbit.svpi;";
+/// Escaped in export parameter values (`;`, `,`, `=` and the `+` between tags).
+const EXPORT_PARAM_ESCAPE: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'/');
+
 #[derive(
     Debug, Default, Clone, Copy, PartialEq, BorshSerialize, BorshDeserialize, Serialize, Deserialize,
 )]
@@ -23,6 +36,16 @@ pub enum DataType {
     Base58,
     Base64,
     EncryptionKey,
+    /// Vault bookkeeping (e.g. API tokens); never listed, exported or readable by name.
+    /// Only `set_internal_segment` creates it, so no request can name it.
+    #[serde(skip_deserializing)]
+    Internal,
+    /// Named text fields (`Record`), stored as a JSON object.
+    Record,
+    /// TOTP/HOTP seed (`OtpConfig`), stored as an `otpauth://` URI.
+    Otp,
+    /// Name of another entry that `get` reads instead; never encrypted.
+    Alias,
 }
 
 #[derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
@@ -33,6 +56,9 @@ pub enum Data {
     Hex(String),
     Base58(String),
     Base64(String),
+    Record(Record),
+    Otp(OtpConfig),
+    Alias(String),
 }
 
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
@@ -60,6 +86,9 @@ pub struct FormattedData {
     pub data: Data,
     pub data_type: DataType,
     pub password_fingerprint: Option<[u8; DATA_FINGERPRINT_SIZE]>,
+    /// Entry attributes carried through export and import.
+    pub tags: Vec<String>,
+    pub folder: Option<String>,
 }
 
 #[derive(Debug, Error)]
@@ -92,6 +121,9 @@ impl Data {
             Data::Base64(data) => base64::engine::general_purpose::STANDARD
                 .decode(&data)
                 .map_err(DataError::Base64DecodeError),
+            Data::Record(record) => Ok(record.to_json().into_bytes()),
+            Data::Otp(config) => Ok(config.to_uri().into_bytes()),
+            Data::Alias(target) => Ok(target.as_bytes().to_vec()),
         }
     }
 
@@ -102,6 +134,9 @@ impl Data {
             Data::Hex(_) => DataType::Hex,
             Data::Base58(_) => DataType::Base58,
             Data::Base64(_) => DataType::Base64,
+            Data::Record(_) => DataType::Record,
+            Data::Otp(_) => DataType::Otp,
+            Data::Alias(_) => DataType::Alias,
         }
     }
 
@@ -118,6 +153,9 @@ impl Data {
             Data::Hex(hex_str) => Ok(hex_str),
             Data::Base58(base58_str) => Ok(base58_str),
             Data::Base64(base64_str) => Ok(base64_str),
+            Data::Record(record) => Ok(record.to_json()),
+            Data::Otp(config) => Ok(config.to_uri()),
+            Data::Alias(target) => Ok(target),
         }
     }
 
@@ -125,6 +163,8 @@ impl Data {
         self.to_string_typed(self.get_type())
     }
 
+    /// Hex, then base58, then base64, else plain. Never a record or OTP seed:
+    /// those are only stored when asked for, so existing text keeps its type.
     pub fn from_str_infer(str: &str) -> Data {
         if Data::Hex(str.to_string()).to_bytes().is_ok() {
             Data::Hex(str.to_string())
@@ -136,6 +176,14 @@ impl Data {
             Data::Plain(str.to_string())
         }
     }
+
+    /// `str` as `data_type`, without inference. Fails if it does not decode as
+    /// that type (`binary` takes hex).
+    pub fn from_str_typed(str: &str, data_type: DataType) -> Result<Data, DataError> {
+        let data = data_type.from_string(str)?;
+        data.to_bytes()?;
+        Ok(data)
+    }
 }
 
 impl DataType {
@@ -150,7 +198,12 @@ impl DataType {
             DataType::Base64 => Ok(Data::Base64(
                 base64::engine::general_purpose::STANDARD.encode(&data),
             )),
-            DataType::EncryptionKey => Ok(Data::Binary(data.to_vec())),
+            DataType::EncryptionKey | DataType::Internal => Ok(Data::Binary(data.to_vec())),
+            DataType::Record => Record::from_bytes(data).map(Data::Record),
+            DataType::Otp => OtpConfig::from_bytes(data).map(Data::Otp),
+            DataType::Alias => String::from_utf8(data.to_vec())
+                .map(Data::Alias)
+                .map_err(DataError::Utf8DecodeError),
         }
     }
 
@@ -163,11 +216,31 @@ impl DataType {
             DataType::Hex => Ok(Data::Hex(data.to_string())),
             DataType::Base58 => Ok(Data::Base58(data.to_string())),
             DataType::Base64 => Ok(Data::Base64(data.to_string())),
-            DataType::EncryptionKey => Ok(Data::Binary(
+            DataType::EncryptionKey | DataType::Internal => Ok(Data::Binary(
                 hex::decode(data).map_err(DataError::HexDecodeError)?,
             )),
+            DataType::Record => Record::from_json(data).map(Data::Record),
+            DataType::Otp => OtpConfig::from_bytes(data.as_bytes()).map(Data::Otp),
+            DataType::Alias => Ok(Data::Alias(data.to_string())),
         }
     }
+
+    pub fn is_internal(&self) -> bool {
+        matches!(self, DataType::Internal)
+    }
+
+    /// Types a caller may assign to an entry's data directly (`set --type`,
+    /// `change-data-type`). Everything else has its own command or is internal.
+    pub fn is_user_data(&self) -> bool {
+        matches!(
+            self,
+            DataType::Binary
+                | DataType::Plain
+                | DataType::Hex
+                | DataType::Base58
+                | DataType::Base64
+        )
+    }
 }
 
 impl ToString for DataType {
@@ -186,6 +259,9 @@ impl FromStr for DataType {
             "hex" => Ok(DataType::Hex),
             "base58" => Ok(DataType::Base58),
             "base64" => Ok(DataType::Base64),
+            "record" => Ok(DataType::Record),
+            "otp" => Ok(DataType::Otp),
+            "alias" => Ok(DataType::Alias),
             _ => Err(DataError::InvalidData),
         }
     }
@@ -314,9 +390,17 @@ impl FormattedData {
             data,
             data_type,
             password_fingerprint,
+            tags: Vec::new(),
+            folder: None,
         }
     }
 
+    pub fn with_grouping(mut self, tags: Vec<String>, folder: Option<String>) -> Self {
+        self.tags = tags;
+        self.folder = folder;
+        self
+    }
+
     pub fn from(
         name: String,
         data: String,
@@ -351,50 +435,90 @@ impl FormattedData {
             None
         };
 
-        Ok(Self {
-            name,
-            data,
-            data_type,
-            password_fingerprint,
-        })
+        Ok(Self::new(name, data, data_type, password_fingerprint))
     }
 
     pub fn encode(&self) -> Result<String, DataError> {
-        let name = self.name.clone();
         let data = self.data.to_string()?;
-        let data_type = self.data_type.to_string();
 
+        let mut params = Vec::new();
         if let Some(pf) = self.password_fingerprint {
-            Ok(format!(
-                "{} = data:application/vnd.binqbit.svpi;fp={};{},{}",
-                name,
-                DataFingerprint::from(pf).to_string(),
-                data_type,
-                data
-            ))
-        } else {
-            Ok(format!("{} = {}", name, data))
+            params.push(format!("fp={}", DataFingerprint::from(pf).to_string()));
+        }
+        if !self.tags.is_empty() {
+            let tags = self
+                .tags
+                .iter()
+                .map(|tag| utf8_percent_encode(tag, EXPORT_PARAM_ESCAPE).to_string())
+                .collect::<Vec<_>>();
+            params.push(format!("tags={}", tags.join("+")));
+        }
+        if let Some(folder) = &self.folder {
+            params.push(format!(
+                "folder={}",
+                utf8_percent_encode(folder, EXPORT_PARAM_ESCAPE)
+            ));
         }
+
+        // Inferring the type from the value would turn a record or alias into plain text.
+        if params.is_empty() && !matches!(self.data_type, DataType::Record | DataType::Alias) {
+            return Ok(format!("{} = {}", self.name, data));
+        }
+        params.push(self.data_type.to_string());
+        Ok(format!(
+            "{} = {}{},{}",
+            self.name,
+            EXPORT_DATA_URI,
+            params.join(";"),
+            data
+        ))
     }
 
     pub fn decode(data: &str) -> Result<Self, DataError> {
         let (name, data) = data.split_once("=").ok_or(DataError::InvalidData)?;
         let name = name.trim().to_string();
-        let parts = data.split(";").collect::<Vec<&str>>();
+        let data = data.trim();
 
-        let (password_fingerprint, data_type, data) = if parts.len() == 1 {
-            (None, None, parts[0].trim().to_string())
-        } else {
-            let password_fingerprint = parts[1].trim_start_matches("fp=");
-            let (data_type, data) = parts[2].split_once(",").ok_or(DataError::InvalidData)?;
-            (
-                Some(password_fingerprint.to_string()),
-                Some(data_type.to_string()),
-                data.to_string(),
-            )
+        let Some(typed) = data.strip_prefix(EXPORT_DATA_URI) else {
+            return FormattedData::from(name, data.to_string(), None, None);
+        };
+        let (head, data) = typed.split_once(',').ok_or(DataError::InvalidData)?;
+        let mut params = head.split(';').collect::<Vec<_>>();
+        let data_type = params.pop().unwrap_or_default().to_string();
+
+        let unescape = |value: &str| {
+            percent_decode_str(value)
+                .decode_utf8()
+                .map(|value| value.into_owned())
+                .map_err(|_| DataError::InvalidData)
         };
+        let mut password_fingerprint = None;
+        let mut tags = Vec::new();
+        let mut folder = None;
+        for param in params {
+            let (key, value) = param.split_once('=').ok_or(DataError::InvalidData)?;
+            match key {
+                "fp" => password_fingerprint = Some(value.to_string()),
+                "tags" => {
+                    tags = value
+                        .split('+')
+                        .filter(|tag| !tag.is_empty())
+                        .map(unescape)
+                        .collect::<Result<_, _>>()?
+                }
+                "folder" => folder = Some(unescape(value)?),
+                // Written by a newer version; the entry itself is still usable.
+                _ => {}
+            }
+        }
 
-        FormattedData::from(name, data, data_type, password_fingerprint)
+        Ok(FormattedData::from(
+            name,
+            data.to_string(),
+            Some(data_type),
+            password_fingerprint,
+        )?
+        .with_grouping(tags, folder))
     }
 }
 
@@ -497,6 +621,34 @@ mod tests {
             Data::from_str_infer("hello"),
             Data::Plain("hello".to_string())
         );
+        assert_eq!(
+            Data::from_str_infer("otpauth://totp/x?secret=JBSWY3DP"),
+            Data::Plain("otpauth://totp/x?secret=JBSWY3DP".to_string())
+        );
+        assert_eq!(
+            Data::from_str_typed("otpauth://totp/x?secret=JBSWY3DP", DataType::Otp)
+                .unwrap()
+                .get_type(),
+            DataType::Otp
+        );
+    }
+
+    #[test]
+    fn from_str_typed_skips_inference() {
+        assert_eq!(
+            Data::from_str_typed("deadbeef", DataType::Plain).unwrap(),
+            Data::Plain("deadbeef".to_string())
+        );
+        assert_eq!(
+            Data::from_str_typed("cafe", DataType::Hex).unwrap(),
+            Data::Hex("cafe".to_string())
+        );
+        assert_eq!(
+            Data::from_str_typed("6869", DataType::Binary).unwrap(),
+            Data::Binary(b"hi".to_vec())
+        );
+        assert!(Data::from_str_typed("hello", DataType::Hex).is_err());
+        assert!(Data::from_str_typed("0OIl", DataType::Base58).is_err());
     }
 
     #[test]
@@ -581,6 +733,52 @@ mod tests {
         );
     }
 
+    #[test]
+    fn formatted_record_keeps_its_type() {
+        let mut record = Record::default();
+        record.set("username", "alice");
+        record.set("notes", "a;b=c");
+        let fd = FormattedData::new(
+            "login".to_string(),
+            Data::Record(record.clone()),
+            DataType::Record,
+            None,
+        );
+
+        let decoded = FormattedData::decode(&fd.encode().unwrap()).unwrap();
+        assert_eq!(decoded.name, "login");
+        assert_eq!(decoded.data_type, DataType::Record);
+        assert_eq!(decoded.data, Data::Record(record));
+    }
+
+    #[test]
+    fn formatted_data_keeps_tags_and_folder() {
+        let fd = FormattedData::new(
+            "github".to_string(),
+            Data::Plain("value".to_string()),
+            DataType::Plain,
+            None,
+        )
+        .with_grouping(
+            vec!["dev".to_string(), "a+b;c".to_string()],
+            Some("work/code, 2024".to_string()),
+        );
+
+        let encoded = fd.encode().unwrap();
+        assert!(encoded.starts_with("github = data:application/vnd.binqbit.svpi;tags="));
+        let decoded = FormattedData::decode(&encoded).unwrap();
+        assert_eq!(decoded.data, Data::Plain("value".to_string()));
+        assert_eq!(decoded.tags, fd.tags);
+        assert_eq!(decoded.folder, fd.folder);
+
+        let decoded = FormattedData::decode(
+            "n = data:application/vnd.binqbit.svpi;color=red;folder=home;plain,v",
+        )
+        .unwrap();
+        assert_eq!(decoded.folder.as_deref(), Some("home"));
+        assert!(decoded.tags.is_empty());
+    }
+
     #[test]
     fn datainfo_new_sets_fields() {
         let data1 = b"foo";
@@ -9,11 +9,15 @@ mod data;
 mod encryption;
 mod mem_mgmt;
 mod metadata;
+mod otp;
+mod record;
 mod segment;
 
 pub use data::*;
 pub use encryption::*;
 pub use metadata::*;
+pub use otp::*;
+pub use record::*;
 pub use segment::*;
 
 pub const ARCHITECTURE_VERSION: u32 = 8;
@@ -0,0 +1,347 @@
+use base32::Alphabet;
+use borsh_derive::{BorshDeserialize, BorshSerialize};
+use hmac::{digest::KeyInit, Hmac, Mac};
+use percent_encoding::percent_decode_str;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::seg_mgr::DataError;
+
+const OTPAUTH_SCHEME: &str = "otpauth";
+const BASE32: Alphabet = Alphabet::Rfc4648 { padding: false };
+
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum OtpKind {
+    /// RFC 6238, time based.
+    #[default]
+    Totp,
+    /// RFC 4226, counter based.
+    Hotp,
+}
+
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum OtpAlgorithm {
+    #[default]
+    Sha1,
+    Sha256,
+    Sha512,
+}
+
+impl OtpAlgorithm {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OtpAlgorithm::Sha1 => "SHA1",
+            OtpAlgorithm::Sha256 => "SHA256",
+            OtpAlgorithm::Sha512 => "SHA512",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_uppercase().as_str() {
+            "SHA1" => Some(OtpAlgorithm::Sha1),
+            "SHA256" => Some(OtpAlgorithm::Sha256),
+            "SHA512" => Some(OtpAlgorithm::Sha512),
+            _ => None,
+        }
+    }
+
+    fn hmac(&self, key: &[u8], message: &[u8]) -> Vec<u8> {
+        fn run<M: Mac + KeyInit>(key: &[u8], message: &[u8]) -> Vec<u8> {
+            let mut mac = <M as Mac>::new_from_slice(key).expect("HMAC accepts keys of any length");
+            mac.update(message);
+            mac.finalize().into_bytes().to_vec()
+        }
+        match self {
+            OtpAlgorithm::Sha1 => run::<Hmac<sha1::Sha1>>(key, message),
+            OtpAlgorithm::Sha256 => run::<Hmac<sha2::Sha256>>(key, message),
+            OtpAlgorithm::Sha512 => run::<Hmac<sha2::Sha512>>(key, message),
+        }
+    }
+}
+
+/// `DataType::Otp`: a 2FA seed with its RFC 6238/4226 parameters, stored as an
+/// `otpauth://` URI.
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+pub struct OtpConfig {
+    pub kind: OtpKind,
+    pub secret: Vec<u8>,
+    pub digits: u8,
+    /// TOTP step in seconds.
+    pub period: u64,
+    pub algorithm: OtpAlgorithm,
+    /// Next HOTP counter value.
+    pub counter: u64,
+    pub issuer: Option<String>,
+    pub account: Option<String>,
+}
+
+/// A generated code.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OtpCode {
+    /// Returned as the entry `data`.
+    #[serde(skip)]
+    pub code: String,
+    pub kind: OtpKind,
+    /// Seconds until a TOTP code expires.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remaining: Option<u64>,
+    /// Counter an HOTP code was generated with.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub counter: Option<u64>,
+}
+
+impl OtpConfig {
+    pub fn new(kind: OtpKind, secret: Vec<u8>) -> Self {
+        Self {
+            kind,
+            secret,
+            digits: 6,
+            period: 30,
+            algorithm: OtpAlgorithm::Sha1,
+            counter: 0,
+            issuer: None,
+            account: None,
+        }
+    }
+
+    /// Checks the parameters the code generator relies on.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.secret.is_empty() {
+            return Err("OTP secret is empty".to_string());
+        }
+        if !(6..=10).contains(&self.digits) {
+            return Err(format!("OTP digits must be 6-10, got {}", self.digits));
+        }
+        if self.period == 0 {
+            return Err("OTP period must be positive".to_string());
+        }
+        Ok(())
+    }
+
+    /// Decodes a base32 secret as shown by authenticator apps (case, spaces
+    /// and padding are ignored).
+    pub fn decode_base32_secret(secret: &str) -> Option<Vec<u8>> {
+        let secret = secret
+            .chars()
+            .filter(|c| !c.is_whitespace() && *c != '-')
+            .collect::<String>()
+            .to_ascii_uppercase();
+        let secret = secret.trim_end_matches('=');
+        if secret.is_empty() {
+            return None;
+        }
+        base32::decode(BASE32, secret)
+    }
+
+    /// Parses `otpauth://totp/Issuer:account?secret=...&digits=6&period=30&algorithm=SHA1`.
+    pub fn from_uri(uri: &str) -> Result<Self, String> {
+        let url = Url::parse(uri.trim()).map_err(|err| format!("Invalid otpauth URI: {err}"))?;
+        if url.scheme() != OTPAUTH_SCHEME {
+            return Err("Expected an otpauth:// URI".to_string());
+        }
+        let kind = match url.host_str().map(|h| h.to_ascii_lowercase()).as_deref() {
+            Some("totp") => OtpKind::Totp,
+            Some("hotp") => OtpKind::Hotp,
+            _ => return Err("Expected otpauth://totp/ or otpauth://hotp/".to_string()),
+        };
+
+        let label = percent_decode_str(url.path().trim_start_matches('/')).decode_utf8_lossy();
+        let (label_issuer, account) = match label.split_once(':') {
+            Some((issuer, account)) => (Some(issuer.trim().to_string()), account.trim()),
+            None => (None, label.trim()),
+        };
+
+        let mut config = OtpConfig::new(kind, Vec::new());
+        config.account = (!account.is_empty()).then(|| account.to_string());
+        config.issuer = label_issuer.filter(|issuer| !issuer.is_empty());
+
+        let mut has_counter = false;
+        for (key, value) in url.query_pairs() {
+            match key.to_ascii_lowercase().as_str() {
+                "secret" => {
+                    config.secret = Self::decode_base32_secret(&value)
+                        .ok_or_else(|| "OTP secret is not valid base32".to_string())?;
+                }
+                "issuer" if !value.is_empty() => config.issuer = Some(value.to_string()),
+                "algorithm" => {
+                    config.algorithm = OtpAlgorithm::parse(&value)
+                        .ok_or_else(|| format!("Unsupported OTP algorithm '{value}'"))?;
+                }
+                "digits" => {
+                    config.digits = value
+                        .parse()
+                        .map_err(|_| format!("Invalid OTP digits '{value}'"))?;
+                }
+                "period" => {
+                    config.period = value
+                        .parse()
+                        .map_err(|_| format!("Invalid OTP period '{value}'"))?;
+                }
+                "counter" => {
+                    config.counter = value
+                        .parse()
+                        .map_err(|_| format!("Invalid OTP counter '{value}'"))?;
+                    has_counter = true;
+                }
+                _ => {}
+            }
+        }
+
+        if kind == OtpKind::Hotp && !has_counter {
+            return Err("HOTP URI requires a counter".to_string());
+        }
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn to_uri(&self) -> String {
+        let kind = match self.kind {
+            OtpKind::Totp => "totp",
+            OtpKind::Hotp => "hotp",
+        };
+        let label = match (&self.issuer, &self.account) {
+            (Some(issuer), Some(account)) => format!("{issuer}:{account}"),
+            (Some(issuer), None) => format!("{issuer}:"),
+            (None, Some(account)) => account.clone(),
+            (None, None) => String::new(),
+        };
+
+        let mut url = Url::parse(&format!("{OTPAUTH_SCHEME}://{kind}/"))
+            .expect("Failed to build otpauth URI");
+        url.set_path(&label);
+        {
+            let mut query = url.query_pairs_mut();
+            query.append_pair("secret", &base32::encode(BASE32, &self.secret));
+            if let Some(issuer) = &self.issuer {
+                query.append_pair("issuer", issuer);
+            }
+            query.append_pair("algorithm", self.algorithm.as_str());
+            query.append_pair("digits", &self.digits.to_string());
+            match self.kind {
+                OtpKind::Totp => query.append_pair("period", &self.period.to_string()),
+                OtpKind::Hotp => query.append_pair("counter", &self.counter.to_string()),
+            };
+        }
+        url.to_string()
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Result<Self, DataError> {
+        let uri = std::str::from_utf8(data).map_err(|_| DataError::InvalidData)?;
+        Self::from_uri(uri).map_err(|_| DataError::InvalidData)
+    }
+
+    /// RFC 4226 HOTP value for `counter`.
+    pub fn hotp(&self, counter: u64) -> String {
+        let hash = self.algorithm.hmac(&self.secret, &counter.to_be_bytes());
+        let offset = (hash[hash.len() - 1] & 0x0f) as usize;
+        let binary = u32::from_be_bytes([
+            hash[offset] & 0x7f,
+            hash[offset + 1],
+            hash[offset + 2],
+            hash[offset + 3],
+        ]);
+        let code = u64::from(binary) % 10u64.pow(u32::from(self.digits));
+        format!("{:0width$}", code, width = self.digits as usize)
+    }
+
+    /// The current code at `now` (Unix seconds). For HOTP the caller stores
+    /// the config back with `counter` advanced.
+    pub fn generate(&self, now: u64) -> OtpCode {
+        match self.kind {
+            OtpKind::Totp => OtpCode {
+                code: self.hotp(now / self.period),
+                kind: self.kind,
+                remaining: Some(self.period - now % self.period),
+                counter: None,
+            },
+            OtpKind::Hotp => OtpCode {
+                code: self.hotp(self.counter),
+                kind: self.kind,
+                remaining: None,
+                counter: Some(self.counter),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rfc_config(algorithm: OtpAlgorithm, secret: &[u8]) -> OtpConfig {
+        OtpConfig {
+            digits: 8,
+            algorithm,
+            ..OtpConfig::new(OtpKind::Totp, secret.to_vec())
+        }
+    }
+
+    #[test]
+    fn rfc_test_vectors() {
+        // RFC 4226 appendix D.
+        let hotp = OtpConfig::new(OtpKind::Hotp, b"12345678901234567890".to_vec());
+        assert_eq!(hotp.hotp(0), "755224");
+        assert_eq!(hotp.hotp(9), "520489");
+
+        // RFC 6238 appendix B.
+        let sha1 = rfc_config(OtpAlgorithm::Sha1, b"12345678901234567890");
+        let sha256 = rfc_config(OtpAlgorithm::Sha256, b"12345678901234567890123456789012");
+        let sha512 = rfc_config(
+            OtpAlgorithm::Sha512,
+            b"1234567890123456789012345678901234567890123456789012345678901234",
+        );
+        assert_eq!(sha1.generate(59).code, "94287082");
+        assert_eq!(sha256.generate(59).code, "46119246");
+        assert_eq!(sha512.generate(59).code, "90693936");
+        assert_eq!(sha1.generate(1111111109).code, "07081804");
+        assert_eq!(sha1.generate(59).remaining, Some(1));
+    }
+
+    #[test]
+    fn otpauth_uri_roundtrip() {
+        let config = OtpConfig::from_uri(
+            "otpauth://totp/ACME%20Co:john@example.com?secret=JBSWY3DPEHPK3PXP&issuer=ACME%20Co&algorithm=SHA256&digits=8&period=60",
+        )
+        .unwrap();
+        assert_eq!(config.kind, OtpKind::Totp);
+        assert_eq!(config.secret, b"Hello!\xde\xad\xbe\xef".to_vec());
+        assert_eq!(config.issuer.as_deref(), Some("ACME Co"));
+        assert_eq!(config.account.as_deref(), Some("john@example.com"));
+        assert_eq!(config.algorithm, OtpAlgorithm::Sha256);
+        assert_eq!((config.digits, config.period), (8, 60));
+
+        assert_eq!(OtpConfig::from_uri(&config.to_uri()).unwrap(), config);
+
+        let hotp = OtpConfig::from_uri("otpauth://hotp/x?secret=jbsw y3dp&counter=7").unwrap();
+        assert_eq!((hotp.kind, hotp.counter), (OtpKind::Hotp, 7));
+
+        assert!(OtpConfig::from_uri("otpauth://hotp/x?secret=JBSWY3DP").is_err());
+        assert!(OtpConfig::from_uri("otpauth://totp/x?secret=JBSWY3DP&digits=4").is_err());
+        assert!(OtpConfig::from_uri("otpauth://totp/x?secret=0189").is_err());
+        assert!(OtpConfig::from_uri("https://totp/x?secret=JBSWY3DP").is_err());
+    }
+}
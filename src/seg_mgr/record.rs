@@ -0,0 +1,109 @@
+use std::collections::BTreeMap;
+
+use borsh_derive::{BorshDeserialize, BorshSerialize};
+use serde::{Deserialize, Serialize};
+
+use crate::seg_mgr::DataError;
+
+/// Fields every login has; listed first, custom fields follow by name.
+pub const RECORD_STANDARD_FIELDS: [&str; 4] = ["username", "password", "url", "notes"];
+
+/// `DataType::Record`: named text fields stored (and encrypted) as one JSON object.
+#[derive(
+    Debug, Default, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize, Serialize, Deserialize,
+)]
+#[serde(transparent)]
+pub struct Record(pub BTreeMap<String, String>);
+
+impl Record {
+    pub fn from_bytes(data: &[u8]) -> Result<Self, DataError> {
+        serde_json::from_slice(data).map_err(|_| DataError::InvalidData)
+    }
+
+    pub fn from_json(data: &str) -> Result<Self, DataError> {
+        Self::from_bytes(data.as_bytes())
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&self.0).expect("Failed to serialize record")
+    }
+
+    pub fn get(&self, field: &str) -> Option<&str> {
+        self.0.get(field).map(String::as_str)
+    }
+
+    /// An empty value removes the field.
+    pub fn set(&mut self, field: &str, value: &str) {
+        if value.is_empty() {
+            self.0.remove(field);
+        } else {
+            self.0.insert(field.to_string(), value.to_string());
+        }
+    }
+
+    /// Field names in display order.
+    pub fn field_names(&self) -> Vec<&str> {
+        let standard = RECORD_STANDARD_FIELDS
+            .iter()
+            .copied()
+            .filter(|field| self.0.contains_key(*field));
+        let custom = self
+            .0
+            .keys()
+            .map(String::as_str)
+            .filter(|field| !RECORD_STANDARD_FIELDS.contains(field));
+        standard.chain(custom).collect()
+    }
+
+    /// Parses `key=value` (`key=` clears the field).
+    pub fn parse_assignment(assignment: &str) -> Result<(String, String), String> {
+        let Some((field, value)) = assignment.split_once('=') else {
+            return Err(format!("Expected key=value, got '{assignment}'"));
+        };
+        let field = field.trim();
+        if field.is_empty() {
+            return Err(format!("Empty field name in '{assignment}'"));
+        }
+        Ok((field.to_string(), value.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_fields_roundtrip_in_display_order() {
+        let mut record = Record::default();
+        record.set("pin", "1234");
+        record.set("password", "hunter2");
+        record.set("username", "alice");
+        record.set("notes", "");
+
+        assert_eq!(record.field_names(), vec!["username", "password", "pin"]);
+
+        let parsed = Record::from_json(&record.to_json()).unwrap();
+        assert_eq!(parsed, record);
+        assert_eq!(parsed.get("password"), Some("hunter2"));
+
+        record.set("pin", "");
+        assert_eq!(record.get("pin"), None);
+
+        assert!(Record::from_json("[1, 2]").is_err());
+        assert!(Record::from_json(r#"{"a": 1}"#).is_err());
+    }
+
+    #[test]
+    fn field_assignments() {
+        assert_eq!(
+            Record::parse_assignment("url=https://a.b/?x=1").unwrap(),
+            ("url".to_string(), "https://a.b/?x=1".to_string())
+        );
+        assert_eq!(
+            Record::parse_assignment("notes=").unwrap(),
+            ("notes".to_string(), String::new())
+        );
+        assert!(Record::parse_assignment("password").is_err());
+        assert!(Record::parse_assignment("=x").is_err());
+    }
+}
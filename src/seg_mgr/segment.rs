@@ -83,6 +83,37 @@ impl SegmentManager {
             old_seg.remove()?;
         }
 
+        self.insert_segment(address, name, data, data_type, password_fingerprint)
+    }
+
+    /// Like `set_segment`, but for `DataType::Internal` records, which live in
+    /// their own namespace and never clash with user entries of the same name.
+    pub fn set_internal_segment<'a>(
+        &'a mut self,
+        name: &str,
+        data: &[u8],
+    ) -> Result<Option<&'a Segment>, SegmentError> {
+        let address = if let Some(address) = self.find_new_segment_address(data.len() as u32) {
+            address
+        } else {
+            return Ok(None);
+        };
+
+        if let Some(old_seg) = self.find_internal_segment(name) {
+            old_seg.remove()?;
+        }
+
+        self.insert_segment(address, name, data, DataType::Internal, None)
+    }
+
+    fn insert_segment<'a>(
+        &'a mut self,
+        address: u32,
+        name: &str,
+        data: &[u8],
+        data_type: DataType,
+        password_fingerprint: Option<[u8; DATA_FINGERPRINT_SIZE]>,
+    ) -> Result<Option<&'a Segment>, SegmentError> {
         let info = DataInfo::new(
             name,
             address,
@@ -107,7 +138,15 @@ impl SegmentManager {
     }
 
     pub fn find_segment_by_name<'a>(&'a mut self, name: &str) -> Option<&'a mut Segment> {
-        self.segments.iter_mut().find(|seg| seg.get_name() == name)
+        self.segments
+            .iter_mut()
+            .find(|seg| !seg.info.data_type.is_internal() && seg.get_name() == name)
+    }
+
+    pub fn find_internal_segment<'a>(&'a mut self, name: &str) -> Option<&'a mut Segment> {
+        self.segments
+            .iter_mut()
+            .find(|seg| seg.info.data_type.is_internal() && seg.get_name() == name)
     }
 }
 
@@ -145,7 +184,12 @@ impl Segment {
             .map_err(SegmentError::UpdateInfoError)
     }
 
+    /// Internal records keep their type: retyping an entry to or from
+    /// `Internal` would let it stand in for vault bookkeeping.
     pub fn set_type(&mut self, data_type: DataType) -> Result<(), SegmentError> {
+        if data_type.is_internal() || self.info.data_type.is_internal() {
+            return Err(SegmentError::DataError(DataError::InvalidData));
+        }
         self.info.data_type = data_type;
         self.update_meta()
     }
@@ -231,4 +275,23 @@ mod tests {
         let wiped_meta = data_mgr.read_data(meta_address, SEGMENT_INFO_SIZE).unwrap();
         assert_eq!(wiped_meta, vec![0u8; SEGMENT_INFO_SIZE]);
     }
+
+    #[test]
+    fn internal_segments_do_not_clash_with_user_entries() {
+        let mut mgr = setup_mgr();
+        mgr.set_internal_segment("a", b"internal").unwrap();
+        mgr.set_segment("a", b"user", DataType::Plain, None)
+            .unwrap();
+        mgr.set_internal_segment("a", b"updated").unwrap();
+
+        let user = mgr.find_segment_by_name("a").unwrap();
+        assert_eq!(user.read_data().unwrap(), Data::Plain("user".to_string()));
+
+        let internal = mgr.find_internal_segment("a").unwrap();
+        assert_eq!(
+            internal.read_data().unwrap(),
+            Data::Binary(b"updated".to_vec())
+        );
+        assert_eq!(mgr.get_active_segments().len(), 2);
+    }
 }
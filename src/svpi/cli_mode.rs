@@ -2,10 +2,12 @@ use std::{
     fs,
     io::{ErrorKind, IsTerminal, Read, Write},
     path::Path,
+    time::{SystemTime, UNIX_EPOCH},
 };
 
 use arboard::Clipboard;
-use clap::Parser;
+use base58::FromBase58;
+use clap::{Parser, ValueEnum};
 use serde_json::{json, Value};
 use sha2::{Digest, Sha256};
 
@@ -13,13 +15,26 @@ use crate::{
     cli,
     config::CONFIG_FILE_NAME,
     data_mgr::DataInterfaceType,
-    pass_mgr::{EncryptionKeySource, PasswordManager},
-    protocol::segments::SegmentSummary,
-    seg_mgr::{Data, DataType, EncryptionLevel, FormattedData, DATA_FINGERPRINT_SIZE},
+    pass_mgr::{
+        expiry_state, format_date, normalize_folder, normalize_tag, parse_expiry, plan_renames,
+        url_host, AuditOptions, BreachDb, BulkAction, BulkChange, EncryptionKeySource,
+        ExpiryWarning, PasswordManager, PasswordManagerError, RenameRule, TokenScope,
+        DEFAULT_EXPIRY_WARNING_DAYS,
+    },
+    protocol::{
+        api,
+        segments::{ListFilter, SegmentSummary},
+    },
+    seg_mgr::{
+        Data, DataError, DataType, EncryptionLevel, FormattedData, OtpConfig, OtpKind, Record,
+        SegmentError, DATA_FINGERPRINT_SIZE,
+    },
     utils::{
         dump,
+        generator::{self, CharClass, PassphrasePolicy, PasswordPolicy},
+        native_host,
         response::{OutputFormat, SvpiResponse},
-        terminal,
+        terminal, tls,
     },
 };
 
@@ -98,6 +113,484 @@ fn confirm_default_key_usage(
     confirm_or_require_confirm(confirm, output_mode, cmd_out, prompt, action, details)
 }
 
+/// The response to return instead of going on if a master password is set and
+/// not given correctly; vaults without one are not gated.
+fn require_master_password_if_set(
+    pass_mgr: &PasswordManager,
+    master_password: Option<String>,
+    output_mode: OutputFormat,
+    cmd_out: Option<String>,
+    action: &str,
+) -> Option<(SvpiResponse, i32)> {
+    if !pass_mgr.is_master_password_set() {
+        return None;
+    }
+
+    let mut master_password = master_password.filter(|p| !p.is_empty());
+    if master_password.is_none() && output_mode == OutputFormat::Cli {
+        master_password = terminal::get_password(Some("master password"));
+    }
+    let Some(master_password) = master_password else {
+        if output_mode == OutputFormat::Cli {
+            return Some(SvpiResponse::cancelled(cmd_out, action, json!({})).with_exit_code());
+        }
+        return Some(
+            SvpiResponse::missing_argument(cmd_out, "master_password (--master-password=...)")
+                .with_exit_code(),
+        );
+    };
+
+    if !pass_mgr.check_master_password(&master_password) {
+        return Some(
+            SvpiResponse::err(
+                cmd_out,
+                "master_password_invalid",
+                "Master password is invalid",
+                None,
+            )
+            .with_exit_code(),
+        );
+    }
+
+    None
+}
+
+/// The response to return instead of changing `name` if it is locked: the
+/// master password overrides the lock (asked for in CLI mode), vaults without
+/// one need `unlock` first.
+fn entry_locked_response(
+    pass_mgr: &mut PasswordManager,
+    name: &str,
+    master_password: Option<String>,
+    output_mode: OutputFormat,
+    cmd_out: Option<String>,
+    action: &str,
+) -> Option<(SvpiResponse, i32)> {
+    let err = match pass_mgr.ensure_unlocked([name]) {
+        Ok(()) => return None,
+        Err(err) => err,
+    };
+    let supplied = master_password.as_deref().is_some_and(|p| !p.is_empty());
+    if !matches!(err, PasswordManagerError::EntryLocked(_))
+        || !pass_mgr.is_master_password_set()
+        || (!supplied && output_mode != OutputFormat::Cli)
+    {
+        return Some(SvpiResponse::password_manager_error(cmd_out, err).with_exit_code());
+    }
+    require_master_password_if_set(pass_mgr, master_password, output_mode, cmd_out, action)
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Stores `expires` for an entry that was just saved (`None` keeps the old one).
+fn save_entry_expiry(
+    pass_mgr: &mut PasswordManager,
+    name: &str,
+    expires: Option<u64>,
+    cmd_out: Option<String>,
+) -> Option<(SvpiResponse, i32)> {
+    let expires = expires?;
+    match pass_mgr.set_entry_expiry(name, Some(expires)) {
+        Ok(true) => None,
+        Ok(false) => Some(
+            SvpiResponse::err(
+                cmd_out,
+                "not_enough_memory",
+                "Not enough memory to store the expiry date",
+                None,
+            )
+            .with_exit_code(),
+        ),
+        Err(err) => Some(SvpiResponse::password_manager_error(cmd_out, err).with_exit_code()),
+    }
+}
+
+/// `lock` and `unlock` of an existing entry.
+fn set_entry_lock(
+    pass_mgr: &mut PasswordManager,
+    name: String,
+    locked: bool,
+    cmd_out: Option<String>,
+) -> (SvpiResponse, i32) {
+    if pass_mgr
+        .get_data_manager()
+        .find_segment_by_name(&name)
+        .is_none()
+    {
+        return SvpiResponse::err(
+            cmd_out,
+            "data_not_found",
+            format!("Data '{name}' not found"),
+            None,
+        )
+        .with_exit_code();
+    }
+
+    match pass_mgr.set_entry_locked(&name, locked) {
+        Ok(true) => (
+            SvpiResponse::ok(cmd_out, json!({ "name": name, "locked": locked })),
+            0,
+        ),
+        Ok(false) => SvpiResponse::err(cmd_out, "not_enough_memory", "Not enough memory", None)
+            .with_exit_code(),
+        Err(err) => SvpiResponse::password_manager_error(cmd_out, err).with_exit_code(),
+    }
+}
+
+/// `tag`, `untag` and `mv`: applies `update` to an existing entry and reports
+/// its tags and folder afterwards.
+fn update_entry_grouping<F>(
+    interface_type: &DataInterfaceType,
+    name: String,
+    cmd_out: Option<String>,
+    update: F,
+) -> (SvpiResponse, i32)
+where
+    F: FnOnce(&mut PasswordManager) -> Result<bool, PasswordManagerError>,
+{
+    let mut pass_mgr = match load_mgr(interface_type, cmd_out.clone()) {
+        Ok(mgr) => mgr,
+        Err(err) => return err,
+    };
+
+    let exists = pass_mgr
+        .get_data_manager()
+        .find_segment_by_name(&name)
+        .is_some();
+    if !exists {
+        return SvpiResponse::err(
+            cmd_out,
+            "data_not_found",
+            format!("Data '{name}' not found"),
+            None,
+        )
+        .with_exit_code();
+    }
+
+    match update(&mut pass_mgr) {
+        Ok(true) => {}
+        Ok(false) => {
+            return SvpiResponse::err(cmd_out, "not_enough_memory", "Not enough memory", None)
+                .with_exit_code()
+        }
+        Err(err) => return SvpiResponse::password_manager_error(cmd_out, err).with_exit_code(),
+    }
+
+    let attrs = match pass_mgr.load_entry_attributes() {
+        Ok(mut attributes) => attributes.remove(&name).unwrap_or_default(),
+        Err(err) => return SvpiResponse::password_manager_error(cmd_out, err).with_exit_code(),
+    };
+    (
+        SvpiResponse::ok(
+            cmd_out,
+            json!({ "name": name, "tags": attrs.tags, "folder": attrs.folder }),
+        ),
+        0,
+    )
+}
+
+fn bulk_change_json(change: &BulkChange) -> Value {
+    match &change.action {
+        BulkAction::Remove => json!({ "name": change.name }),
+        BulkAction::Rename(new_name) => json!({ "name": change.name, "new_name": new_name }),
+        BulkAction::Move(folder) => json!({ "name": change.name, "folder": folder }),
+        BulkAction::ChangeType(data_type) => {
+            json!({ "name": change.name, "data_type": data_type.to_string() })
+        }
+    }
+}
+
+/// Names of the entries (no keys or internal records) that match `glob`.
+fn entries_matching(
+    pass_mgr: &mut PasswordManager,
+    glob: &str,
+) -> Result<Vec<String>, (&'static str, String)> {
+    let filter = ListFilter {
+        pattern: Some(glob.to_string()),
+        ..Default::default()
+    };
+    let entries = filter.apply(api::list_entries(pass_mgr), &[])?;
+    Ok(entries.into_iter().map(|entry| entry.name).collect())
+}
+
+/// `--match`/`--regex` variants of remove, rename, mv and change-data-type.
+/// `plan` lists the changes for `selector` (errors carry the name of the
+/// offending argument); they are only listed with `dry_run`, otherwise
+/// confirmed and applied as one transaction.
+fn run_bulk<F>(
+    interface_type: &DataInterfaceType,
+    selector: &str,
+    dry_run: bool,
+    confirm: bool,
+    output_mode: OutputFormat,
+    cmd_out: Option<String>,
+    plan: F,
+) -> (SvpiResponse, i32)
+where
+    F: FnOnce(&mut PasswordManager) -> Result<Vec<BulkChange>, (&'static str, String)>,
+{
+    let mut pass_mgr = match load_mgr(interface_type, cmd_out.clone()) {
+        Ok(mgr) => mgr,
+        Err(err) => return err,
+    };
+    let changes = match plan(&mut pass_mgr) {
+        Ok(v) if v.is_empty() => {
+            return SvpiResponse::err(
+                cmd_out,
+                "data_not_found",
+                format!("No entries match '{selector}'"),
+                None,
+            )
+            .with_exit_code()
+        }
+        Ok(v) => v,
+        Err((name, message)) => {
+            return SvpiResponse::invalid_argument(cmd_out, name, message).with_exit_code()
+        }
+    };
+    // Bulk changes never override a lock, whatever the action; locked entries
+    // must be unlocked first.
+    let guarded = changes.iter().map(|change| change.name.as_str());
+    if let Err(err) = pass_mgr.ensure_unlocked(guarded) {
+        return SvpiResponse::password_manager_error(cmd_out, err).with_exit_code();
+    }
+    let changes_json = changes.iter().map(bulk_change_json).collect::<Vec<_>>();
+
+    if dry_run {
+        return (
+            SvpiResponse::ok(
+                cmd_out,
+                json!({ "dry_run": true, "count": changes.len(), "changes": changes_json }),
+            ),
+            0,
+        );
+    }
+
+    let action = cmd_out.clone().unwrap_or_default();
+    if output_mode == OutputFormat::Cli && !confirm {
+        for change in &changes {
+            match &change.action {
+                BulkAction::Remove => println!("  {}", change.name),
+                BulkAction::Rename(new_name) => println!("  {} -> {new_name}", change.name),
+                BulkAction::Move(folder) => {
+                    println!("  {} -> {}/", change.name, folder.as_deref().unwrap_or(""))
+                }
+                BulkAction::ChangeType(data_type) => {
+                    println!("  {} -> {}", change.name, data_type.to_string())
+                }
+            }
+        }
+    }
+    let prompt = format!("Apply '{action}' to {} entries?", changes.len());
+    if let Err(err) = confirm_or_require_confirm(
+        confirm,
+        output_mode,
+        cmd_out.clone(),
+        &prompt,
+        &action,
+        json!({ "count": changes.len(), "changes": changes_json }),
+    ) {
+        return err;
+    }
+
+    match pass_mgr.apply_bulk(&changes) {
+        Ok(true) => {}
+        Ok(false) => {
+            return SvpiResponse::err(
+                cmd_out,
+                "not_enough_memory",
+                "Not enough memory (nothing was changed)",
+                None,
+            )
+            .with_exit_code()
+        }
+        Err(err) => return SvpiResponse::password_manager_error(cmd_out, err).with_exit_code(),
+    }
+
+    let mut result = json!({ "dry_run": false, "count": changes.len(), "changes": changes_json });
+    let removed = changes
+        .iter()
+        .filter(|change| change.action == BulkAction::Remove)
+        .map(|change| change.name.as_str())
+        .collect::<Vec<_>>();
+    let dangling = pass_mgr
+        .aliases()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|(_, target)| removed.contains(&target.as_str()))
+        .map(|(alias, _)| alias)
+        .collect::<Vec<_>>();
+    if !dangling.is_empty() {
+        result["dangling_aliases"] = json!(dangling);
+    }
+    (SvpiResponse::ok(cmd_out, result), 0)
+}
+
+/// Glob-selected changes for `remove`, `mv` and `change-data-type --match`.
+fn plan_for_matching(
+    pass_mgr: &mut PasswordManager,
+    glob: &str,
+    action: BulkAction,
+) -> Result<Vec<BulkChange>, (&'static str, String)> {
+    let names = entries_matching(pass_mgr, glob).map_err(|(_, message)| ("name", message))?;
+    Ok(names
+        .into_iter()
+        .map(|name| BulkChange {
+            name,
+            action: action.clone(),
+        })
+        .collect())
+}
+
+/// `set <name> --field=k=v ...`: edits a record in place, keeping its password.
+fn set_record_fields(
+    interface_type: &DataInterfaceType,
+    args: cli::SetArgs,
+    confirm: bool,
+    output_mode: OutputFormat,
+    cmd_out: Option<String>,
+) -> (SvpiResponse, i32) {
+    let name = args.name;
+    let mut assignments = Vec::with_capacity(args.fields.len());
+    for field in &args.fields {
+        match Record::parse_assignment(field) {
+            Ok(v) => assignments.push(v),
+            Err(msg) => {
+                return SvpiResponse::invalid_argument(cmd_out, "field", msg).with_exit_code()
+            }
+        }
+    }
+    let expires = match args.expires.as_deref().map(parse_expiry).transpose() {
+        Ok(v) => v,
+        Err(message) => {
+            return SvpiResponse::invalid_argument(cmd_out, "expires", message).with_exit_code()
+        }
+    };
+
+    let mut pass_mgr = match load_mgr(interface_type, cmd_out.clone()) {
+        Ok(mgr) => mgr,
+        Err(err) => return err,
+    };
+
+    if let Some(err) = entry_locked_response(
+        &mut pass_mgr,
+        &name,
+        args.master_password,
+        output_mode,
+        cmd_out.clone(),
+        "set",
+    ) {
+        return err;
+    }
+
+    let existing = pass_mgr
+        .get_data_manager()
+        .find_segment_by_name(&name)
+        .map(|seg| (seg.info.password_fingerprint.is_some(), seg.info.data_type));
+    let mut password = args.password.filter(|p| !p.is_empty());
+
+    let mut record = match existing {
+        Some((_, data_type)) if data_type != DataType::Record => {
+            return SvpiResponse::invalid_argument(
+                cmd_out,
+                "field",
+                format!(
+                    "Data '{name}' is not a record (type {})",
+                    data_type.to_string()
+                ),
+            )
+            .with_exit_code()
+        }
+        Some((encrypted, _)) => {
+            if encrypted && password.is_none() && output_mode == OutputFormat::Cli {
+                password = terminal::get_password(None);
+                if password.is_none() {
+                    return SvpiResponse::cancelled(cmd_out, "set", json!({ "name": name }))
+                        .with_exit_code();
+                }
+            }
+            if encrypted && password.is_none() {
+                return SvpiResponse::err(
+                    cmd_out,
+                    "password_required",
+                    "Password required for decryption (pass --password=...)",
+                    Some(json!({ "name": name })),
+                )
+                .with_exit_code();
+            }
+            match pass_mgr.read_data(&name, || password.clone().unwrap_or_default()) {
+                Ok(Data::Record(record)) => record,
+                Ok(_) => Record::default(),
+                Err(err) => {
+                    return SvpiResponse::password_manager_error(cmd_out, err).with_exit_code()
+                }
+            }
+        }
+        None => {
+            if password.is_none() && output_mode == OutputFormat::Cli {
+                password = terminal::get_password_confirmed(None);
+            }
+            if let Some(ref password) = password {
+                if let Err(err) = confirm_default_key_usage(
+                    &mut pass_mgr,
+                    password,
+                    None,
+                    confirm,
+                    output_mode,
+                    cmd_out.clone(),
+                    "Encryption key not found. Use provided password as default key?",
+                    "default_key_fallback",
+                    json!({ "name": name }),
+                ) {
+                    return err;
+                }
+            }
+            Record::default()
+        }
+    };
+
+    for (field, value) in &assignments {
+        record.set(field, value);
+    }
+    let fields = record
+        .field_names()
+        .into_iter()
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+
+    let saved = match pass_mgr.save_data(&name, Data::Record(record), password.clone()) {
+        Ok(v) => v,
+        Err(err) => return SvpiResponse::password_manager_error(cmd_out, err).with_exit_code(),
+    };
+    if !saved {
+        return SvpiResponse::err(cmd_out, "not_enough_memory", "Not enough memory", None)
+            .with_exit_code();
+    }
+    if let Some(err) = save_entry_expiry(&mut pass_mgr, &name, expires, cmd_out.clone()) {
+        return err;
+    }
+
+    (
+        SvpiResponse::ok(
+            cmd_out,
+            json!({
+                "saved": true,
+                "name": name,
+                "data_type": DataType::Record.to_string(),
+                "encrypted": password.is_some(),
+                "fields": fields,
+                "expires": expires,
+            }),
+        ),
+        0,
+    )
+}
+
 fn sha256_file_hex(path: &Path) -> std::io::Result<String> {
     let mut file = fs::File::open(path)?;
 
@@ -190,9 +683,16 @@ fn run_repl(cli: &cli::CliArgs) -> i32 {
             }
             _ => None,
         };
-        let is_sensitive = matches!(cmd, cli::Command::Set(_) | cli::Command::Get(_));
+        let is_sensitive = matches!(
+            cmd,
+            cli::Command::Set(_)
+                | cli::Command::Get(_)
+                | cli::Command::SetOtp(_)
+                | cli::Command::Token(cli::TokenCommand::Create(_))
+        );
+        let list_format = list_format(&cmd);
         let (resp, _code) = execute_with_output(cmd, OutputFormat::Cli, &interface_type, confirm);
-        resp.print(OutputFormat::Cli);
+        print_response(&resp, OutputFormat::Cli, list_format);
         if is_sensitive {
             terminal::wait_enter_and_clear();
         }
@@ -207,6 +707,26 @@ fn run_repl(cli: &cli::CliArgs) -> i32 {
     0
 }
 
+fn list_format(cmd: &cli::Command) -> Option<cli::ListFormat> {
+    match cmd {
+        cli::Command::List(args) => Some(args.format),
+        _ => None,
+    }
+}
+
+/// `list --format` only changes what the CLI prints; JSON modes always print JSON.
+fn print_response(
+    resp: &SvpiResponse,
+    output_mode: OutputFormat,
+    list_format: Option<cli::ListFormat>,
+) {
+    match (output_mode, list_format) {
+        (OutputFormat::Cli, Some(cli::ListFormat::Json)) => resp.print_json(),
+        (OutputFormat::Cli, Some(cli::ListFormat::Csv)) => resp.print_csv(),
+        _ => resp.print(output_mode),
+    }
+}
+
 pub fn run_with_cli(cli: &cli::CliArgs) -> i32 {
     let output_mode = cli.output_format();
     let interface_type = cli.interface_type();
@@ -218,21 +738,27 @@ pub fn run_with_cli(cli: &cli::CliArgs) -> i32 {
 
     let command = cli.command.clone().unwrap_or(cli::Command::Help);
     if output_mode == OutputFormat::Cli {
-        if matches!(command, cli::Command::Set(_)) {
-            let cmd_out = Some(command_name(&command).to_string());
+        if matches!(command, cli::Command::Set(_) | cli::Command::SetOtp(_)) {
+            let name = command_name(&command);
             let resp = SvpiResponse::err(
-                cmd_out,
+                Some(name.to_string()),
                 "invalid_argument",
-                "Use interactive mode for `set` command (run `svpi` without a subcommand)",
+                format!(
+                    "Use interactive mode for `{name}` command (run `svpi` without a subcommand)"
+                ),
                 None,
             );
             resp.print(output_mode);
             return 2;
         }
     }
-    let is_sensitive = matches!(command, cli::Command::Get(_));
+    let is_sensitive = matches!(
+        command,
+        cli::Command::Get(_) | cli::Command::Token(cli::TokenCommand::Create(_))
+    );
+    let list_format = list_format(&command);
     let (resp, code) = execute_with_output(command, output_mode, &interface_type, confirm);
-    resp.print(output_mode);
+    print_response(&resp, output_mode, list_format);
     if is_sensitive && output_mode == OutputFormat::Cli {
         terminal::wait_enter_and_clear();
     }
@@ -240,13 +766,15 @@ pub fn run_with_cli(cli: &cli::CliArgs) -> i32 {
     code
 }
 
-fn command_name(cmd: &cli::Command) -> &'static str {
+pub(super) fn command_name(cmd: &cli::Command) -> &'static str {
     match cmd {
         cli::Command::Help => "help",
         cli::Command::Version => "version",
         cli::Command::SelfHash => "self-hash",
         cli::Command::Config => "config",
         cli::Command::SetFile { .. } => "set-file",
+        cli::Command::SetCorsOrigins { .. } => "set-cors-origins",
+        cli::Command::SetDefaultType { .. } => "set-default-type",
         cli::Command::Init { .. } => "init",
         cli::Command::Check => "check",
         cli::Command::Format => "format",
@@ -262,17 +790,35 @@ fn command_name(cmd: &cli::Command) -> &'static str {
         cli::Command::AddEncryptionKey(_) => "add-encryption-key",
         cli::Command::LinkKey(_) => "link-key",
         cli::Command::SyncKeys(_) => "sync-keys",
-        cli::Command::List => "list",
+        cli::Command::List(_) => "list",
         cli::Command::Set(_) => "set",
+        cli::Command::SetAlias { .. } => "set-alias",
+        cli::Command::SetExpiry { .. } => "set-expiry",
         cli::Command::Get(_) => "get",
+        cli::Command::SetOtp(_) => "set-otp",
+        cli::Command::Generate(_) => "generate",
         cli::Command::Remove { .. } => "remove",
         cli::Command::Rename { .. } => "rename",
         cli::Command::ChangeDataType(_) => "change-data-type",
         cli::Command::ChangePassword(_) => "change-password",
+        cli::Command::SetUrls { .. } => "set-urls",
+        cli::Command::Tag { .. } => "tag",
+        cli::Command::Untag { .. } => "untag",
+        cli::Command::Mv { .. } => "mv",
+        cli::Command::Lock { .. } => "lock",
+        cli::Command::Unlock { .. } => "unlock",
+        cli::Command::FindByUrl { .. } => "find-by-url",
+        cli::Command::Audit(_) => "audit",
+        cli::Command::GenCert(_) => "gen-cert",
+        cli::Command::InstallNativeHost(_) => "install-native-host",
+        cli::Command::UninstallNativeHost(_) => "uninstall-native-host",
+        cli::Command::Token(cli::TokenCommand::Create(_)) => "token-create",
+        cli::Command::Token(cli::TokenCommand::List) => "token-list",
+        cli::Command::Token(cli::TokenCommand::Revoke(_)) => "token-revoke",
     }
 }
 
-fn execute_with_output(
+pub(super) fn execute_with_output(
     cmd: cli::Command,
     output_mode: OutputFormat,
     interface_type: &DataInterfaceType,
@@ -401,17 +947,19 @@ fn execute_with_output(
 
             let valid = exists && cfg.is_some();
 
-            let (mode_code, mode_name, file) = if let Some(cfg) = cfg {
+            let plain_by_default = cfg.as_ref().is_some_and(|cfg| cfg.plain_by_default);
+            let (mode_code, mode_name, file, cors_origins) = if let Some(cfg) = cfg {
                 let mode_name = match cfg.mode {
                     0 => "cli",
                     1 => "json",
                     2 => "server",
                     3 => "chrome",
+                    4 => "json-lines",
                     _ => "unknown",
                 };
-                (Some(cfg.mode), Some(mode_name), cfg.file)
+                (Some(cfg.mode), Some(mode_name), cfg.file, cfg.cors_origins)
             } else {
-                (None, None, None)
+                (None, None, None, Vec::new())
             };
 
             (
@@ -424,6 +972,8 @@ fn execute_with_output(
                         "mode": mode_code,
                         "mode_name": mode_name,
                         "file": file,
+                        "cors_origins": cors_origins,
+                        "default_data_type": if plain_by_default { "plain" } else { "infer" },
                     }),
                 ),
                 0,
@@ -462,6 +1012,68 @@ fn execute_with_output(
                 0,
             )
         }
+        cli::Command::SetCorsOrigins { origins } => {
+            for origin in &origins {
+                if let Err(message) = crate::api::server::validate_origin(origin) {
+                    return SvpiResponse::invalid_argument(cmd_out, "origin", message)
+                        .with_exit_code();
+                }
+            }
+
+            let mut cfg = crate::config::SvpiConfig::load_from_cwd()
+                .ok()
+                .flatten()
+                .unwrap_or_default();
+            cfg.cors_origins = origins.clone();
+
+            if let Err(err) = cfg.save_to_cwd() {
+                return SvpiResponse::err(
+                    cmd_out.clone(),
+                    "device_error",
+                    format!("Failed to write {CONFIG_FILE_NAME} config: {err}"),
+                    None,
+                )
+                .with_exit_code();
+            }
+
+            (
+                SvpiResponse::ok(
+                    cmd_out,
+                    json!({ "cors_origins": origins, "config_file": CONFIG_FILE_NAME }),
+                ),
+                0,
+            )
+        }
+        cli::Command::SetDefaultType { data_type } => {
+            let mut cfg = crate::config::SvpiConfig::load_from_cwd()
+                .ok()
+                .flatten()
+                .unwrap_or_default();
+            cfg.plain_by_default = data_type == cli::DefaultDataTypeArg::Plain;
+
+            if let Err(err) = cfg.save_to_cwd() {
+                return SvpiResponse::err(
+                    cmd_out.clone(),
+                    "device_error",
+                    format!("Failed to write {CONFIG_FILE_NAME} config: {err}"),
+                    None,
+                )
+                .with_exit_code();
+            }
+
+            let data_type = if cfg.plain_by_default {
+                "plain"
+            } else {
+                "infer"
+            };
+            (
+                SvpiResponse::ok(
+                    cmd_out,
+                    json!({ "default_data_type": data_type, "config_file": CONFIG_FILE_NAME }),
+                ),
+                0,
+            )
+        }
 
         cli::Command::Init {
             memory_size,
@@ -541,13 +1153,21 @@ fn execute_with_output(
                 Err(_) => false,
             };
 
-            (
-                SvpiResponse::ok(
-                    cmd_out,
-                    json!({ "initialized": initialized, "architecture_ok": architecture_ok }),
-                ),
-                0,
-            )
+            let mut result =
+                json!({ "initialized": initialized, "architecture_ok": architecture_ok });
+            let loaded = initialized
+                && architecture_ok
+                && pass_mgr.get_data_manager().load_metadata().is_ok()
+                && pass_mgr.get_data_manager().load_segments().is_ok();
+            if loaded {
+                let warnings = pass_mgr
+                    .expiry_warnings(unix_now(), DEFAULT_EXPIRY_WARNING_DAYS)
+                    .unwrap_or_default();
+                if !warnings.is_empty() {
+                    result["expiry_warnings"] = json!(warnings);
+                }
+            }
+            (SvpiResponse::ok(cmd_out, result), 0)
         }
 
         cli::Command::Format => {
@@ -658,13 +1278,22 @@ fn execute_with_output(
                 Ok(mgr) => mgr,
                 Err(err) => return err,
             };
+            let mut attributes = match pass_mgr.load_entry_attributes() {
+                Ok(v) => v,
+                Err(err) => {
+                    return SvpiResponse::password_manager_error(cmd_out, err).with_exit_code()
+                }
+            };
 
             let mut list = Vec::new();
             for seg in pass_mgr
                 .get_data_manager()
                 .get_active_segments_mut()
                 .into_iter()
-                .filter(|seg| seg.info.data_type != DataType::EncryptionKey)
+                .filter(|seg| {
+                    seg.info.data_type != DataType::EncryptionKey
+                        && !seg.info.data_type.is_internal()
+                })
             {
                 let data = match seg.read_data() {
                     Ok(data) => data,
@@ -673,6 +1302,7 @@ fn execute_with_output(
                             .with_exit_code();
                     }
                 };
+                let attrs = attributes.remove(&seg.get_name()).unwrap_or_default();
 
                 let formatted = match FormattedData::new(
                     seg.get_name(),
@@ -680,6 +1310,7 @@ fn execute_with_output(
                     seg.info.data_type,
                     seg.info.password_fingerprint,
                 )
+                .with_grouping(attrs.tags, attrs.folder)
                 .encode()
                 {
                     Ok(v) => v,
@@ -721,10 +1352,19 @@ fn execute_with_output(
                 }
             };
 
-            let seg_mgr = pass_mgr.get_data_manager();
-            let mut imported = 0usize;
-            for line in text.lines().map(str::trim).filter(|l| !l.is_empty()) {
-                let formatted = match FormattedData::decode(line) {
+            // Locked entries are never overwritten by an import.
+            let locked = match pass_mgr.locked_entries() {
+                Ok(v) => v,
+                Err(err) => {
+                    return SvpiResponse::password_manager_error(cmd_out, err).with_exit_code()
+                }
+            };
+            let seg_mgr = pass_mgr.get_data_manager();
+            let mut imported = 0usize;
+            let mut skipped_locked = Vec::new();
+            let mut grouping = Vec::new();
+            for line in text.lines().map(str::trim).filter(|l| !l.is_empty()) {
+                let formatted = match FormattedData::decode(line) {
                     Ok(v) => v,
                     Err(err) => {
                         return SvpiResponse::err(
@@ -736,6 +1376,33 @@ fn execute_with_output(
                         .with_exit_code();
                     }
                 };
+                if locked.contains(&formatted.name) {
+                    skipped_locked.push(formatted.name);
+                    continue;
+                }
+
+                let tags = formatted
+                    .tags
+                    .iter()
+                    .map(|tag| normalize_tag(tag))
+                    .collect::<Result<Vec<_>, _>>();
+                let folder = formatted
+                    .folder
+                    .as_deref()
+                    .map(normalize_folder)
+                    .transpose();
+                let (tags, folder) = match (tags, folder) {
+                    (Ok(tags), Ok(folder)) => (tags, folder.flatten()),
+                    (Err(message), _) | (_, Err(message)) => {
+                        return SvpiResponse::err(
+                            cmd_out,
+                            "invalid_argument",
+                            message,
+                            Some(json!({ "line": line })),
+                        )
+                        .with_exit_code();
+                    }
+                };
 
                 let data = match formatted.data.to_bytes() {
                     Ok(v) => v,
@@ -773,16 +1440,44 @@ fn execute_with_output(
                     .with_exit_code();
                 }
 
+                if !tags.is_empty() || folder.is_some() {
+                    grouping.push((formatted.name, tags, folder));
+                }
                 imported += 1;
             }
 
-            (
-                SvpiResponse::ok(
-                    cmd_out,
-                    json!({ "imported": true, "file": file_path, "segments_imported": imported }),
-                ),
-                0,
-            )
+            if !grouping.is_empty() {
+                let saved = pass_mgr.load_entry_attributes().and_then(|mut attributes| {
+                    for (name, tags, folder) in grouping {
+                        let attrs = attributes.entry(name).or_default();
+                        attrs.tags = tags;
+                        attrs.folder = folder;
+                    }
+                    pass_mgr.save_entry_attributes(&attributes)
+                });
+                match saved {
+                    Ok(true) => {}
+                    Ok(false) => {
+                        return SvpiResponse::err(
+                            cmd_out,
+                            "not_enough_memory",
+                            "Not enough memory (try `svpi optimize`)".to_string(),
+                            Some(json!({ "imported": imported })),
+                        )
+                        .with_exit_code()
+                    }
+                    Err(err) => {
+                        return SvpiResponse::password_manager_error(cmd_out, err).with_exit_code()
+                    }
+                }
+            }
+
+            let mut result =
+                json!({ "imported": true, "file": file_path, "segments_imported": imported });
+            if !skipped_locked.is_empty() {
+                result["skipped_locked"] = json!(skipped_locked);
+            }
+            (SvpiResponse::ok(cmd_out, result), 0)
         }
 
         cli::Command::Dump {
@@ -1276,34 +1971,88 @@ fn execute_with_output(
             (SvpiResponse::ok(cmd_out, json!({ "synced": true })), 0)
         }
 
-        cli::Command::List => {
+        cli::Command::List(args) => {
             let mut pass_mgr = match load_mgr(interface_type, cmd_out.clone()) {
                 Ok(mgr) => mgr,
                 Err(err) => return err,
             };
+            let attributes = match pass_mgr.load_entry_attributes() {
+                Ok(v) => v,
+                Err(err) => {
+                    return SvpiResponse::password_manager_error(cmd_out, err).with_exit_code()
+                }
+            };
+            let aliases = match pass_mgr.aliases() {
+                Ok(v) => v,
+                Err(err) => {
+                    return SvpiResponse::password_manager_error(cmd_out, err).with_exit_code()
+                }
+            };
             let seg_mgr = pass_mgr.get_data_manager();
 
             let segments = seg_mgr
                 .get_active_segments()
                 .into_iter()
-                .map(|seg| SegmentSummary::from_segment(&seg))
+                .filter(|seg| !seg.info.data_type.is_internal())
+                .map(|seg| {
+                    SegmentSummary::from_segment(&seg)
+                        .with_attributes(attributes.get(&seg.get_name()))
+                        .with_alias_target(aliases.get(&seg.get_name()))
+                })
+                .collect::<Vec<_>>();
+            let keys = segments
+                .iter()
+                .filter(|seg| seg.data_type == DataType::EncryptionKey.to_string())
+                .cloned()
                 .collect::<Vec<_>>();
+            let segments = match args.filter().apply(segments, &keys) {
+                Ok(v) => v,
+                Err((name, message)) => {
+                    return SvpiResponse::invalid_argument(cmd_out, name, message).with_exit_code()
+                }
+            };
 
-            (
-                SvpiResponse::ok(
-                    cmd_out,
-                    json!({
-                        "memory_total": seg_mgr.metadata.memory_size,
-                        "memory_free": seg_mgr.free_memory_size(),
-                        "segments": segments,
-                    }),
-                ),
-                0,
-            )
+            let now = unix_now();
+            let mut expiry_warnings = segments
+                .iter()
+                .filter_map(|seg| {
+                    let expires = seg.expires?;
+                    let state = expiry_state(expires, now, DEFAULT_EXPIRY_WARNING_DAYS)?;
+                    Some(ExpiryWarning {
+                        name: seg.name.clone(),
+                        expires,
+                        state,
+                    })
+                })
+                .collect::<Vec<_>>();
+            expiry_warnings.sort_by_key(|warning| warning.expires);
+
+            let mut result = json!({
+                "memory_total": seg_mgr.metadata.memory_size,
+                "memory_free": seg_mgr.free_memory_size(),
+                "segments": segments,
+            });
+            if !expiry_warnings.is_empty() {
+                result["expiry_warnings"] = json!(expiry_warnings);
+            }
+            (SvpiResponse::ok(cmd_out, result), 0)
+        }
+
+        cli::Command::Set(args) if !args.fields.is_empty() => {
+            set_record_fields(interface_type, args, confirm, output_mode, cmd_out)
         }
 
         cli::Command::Set(args) => {
             let name = args.name;
+            let expires = match args.expires.as_deref().map(parse_expiry).transpose() {
+                Ok(v) => v,
+                Err(message) => {
+                    return SvpiResponse::invalid_argument(cmd_out, "expires", message)
+                        .with_exit_code()
+                }
+            };
+            // Files are read as hex (their bytes), unless they hold a JSON string.
+            let mut from_file = false;
             let data = match args.data {
                 Some(data) if output_mode == OutputFormat::Cli => {
                     let path = Path::new(&data);
@@ -1347,10 +2096,14 @@ fn execute_with_output(
                                         };
                                         bytes.push(byte);
                                     }
+                                    from_file = true;
                                     hex::encode(bytes)
                                 }
                                 Ok(Value::String(value)) => value,
-                                _ => hex::encode(data),
+                                _ => {
+                                    from_file = true;
+                                    hex::encode(data)
+                                }
                             }
                         }
                         Err(err) if err.kind() == ErrorKind::NotFound => data,
@@ -1397,6 +2150,33 @@ fn execute_with_output(
                 }
                 None => return SvpiResponse::missing_argument(cmd_out, "data").with_exit_code(),
             };
+            // `None`: inferred from the text by `save_password`.
+            let typed = match (args.data_type.map(DataType::from), from_file) {
+                (Some(data_type), true) => Some(
+                    hex::decode(&data)
+                        .map_err(DataError::HexDecodeError)
+                        .and_then(|bytes| data_type.from_bytes(&bytes))
+                        .map_err(|err| (data_type, err)),
+                ),
+                (Some(data_type), false) => {
+                    Some(Data::from_str_typed(&data, data_type).map_err(|err| (data_type, err)))
+                }
+                (None, false) if crate::config::SvpiConfig::plain_by_default_in_cwd() => {
+                    Some(Ok(Data::Plain(data.clone())))
+                }
+                (None, _) => None,
+            };
+            let typed = match typed.transpose() {
+                Ok(v) => v,
+                Err((data_type, err)) => {
+                    return SvpiResponse::invalid_argument(
+                        cmd_out,
+                        "data",
+                        format!("Data is not valid {}: {err}", data_type.to_string()),
+                    )
+                    .with_exit_code()
+                }
+            };
             let encryption_key = args.password.filter(|p| !p.is_empty()).or_else(|| {
                 if output_mode == OutputFormat::Cli {
                     terminal::get_password_confirmed(None)
@@ -1410,6 +2190,17 @@ fn execute_with_output(
                 Err(err) => return err,
             };
 
+            if let Some(err) = entry_locked_response(
+                &mut pass_mgr,
+                &name,
+                args.master_password,
+                output_mode,
+                cmd_out.clone(),
+                "set",
+            ) {
+                return err;
+            }
+
             if let Some(ref password) = encryption_key {
                 if let Err(err) = confirm_default_key_usage(
                     &mut pass_mgr,
@@ -1426,7 +2217,14 @@ fn execute_with_output(
                 }
             }
 
-            let saved = match pass_mgr.save_password(&name, &data, encryption_key.clone()) {
+            let data_type = typed
+                .as_ref()
+                .map_or_else(|| Data::from_str_infer(&data).get_type(), Data::get_type);
+            let saved = match typed {
+                Some(typed) => pass_mgr.save_data(&name, typed, encryption_key.clone()),
+                None => pass_mgr.save_password(&name, &data, encryption_key.clone()),
+            };
+            let saved = match saved {
                 Ok(v) => v,
                 Err(err) => {
                     return SvpiResponse::password_manager_error(cmd_out, err).with_exit_code()
@@ -1437,24 +2235,85 @@ fn execute_with_output(
                 return SvpiResponse::err(cmd_out, "not_enough_memory", "Not enough memory", None)
                     .with_exit_code();
             }
+            if let Some(err) = save_entry_expiry(&mut pass_mgr, &name, expires, cmd_out.clone()) {
+                return err;
+            }
 
-            let inferred = Data::from_str_infer(&data);
             (
                 SvpiResponse::ok(
                     cmd_out,
                     json!({
                         "saved": true,
                         "name": name,
-                        "data_type": inferred.get_type().to_string(),
+                        "data_type": data_type.to_string(),
                         "encrypted": encryption_key.is_some(),
+                        "expires": expires,
                     }),
                 ),
                 0,
             )
         }
 
+        cli::Command::SetAlias {
+            name,
+            target,
+            master_password,
+        } => {
+            let mut pass_mgr = match load_mgr(interface_type, cmd_out.clone()) {
+                Ok(mgr) => mgr,
+                Err(err) => return err,
+            };
+
+            if let Some(err) = entry_locked_response(
+                &mut pass_mgr,
+                &name,
+                master_password,
+                output_mode,
+                cmd_out.clone(),
+                "set-alias",
+            ) {
+                return err;
+            }
+
+            // Not an entry name: try it as an entry fingerprint.
+            let target = if pass_mgr
+                .get_data_manager()
+                .find_segment_by_name(&target)
+                .is_some()
+            {
+                target
+            } else {
+                api::list_entries(&mut pass_mgr)
+                    .into_iter()
+                    .find(|entry| entry.fingerprint.eq_ignore_ascii_case(&target))
+                    .map(|entry| entry.name)
+                    .unwrap_or(target)
+            };
+
+            match pass_mgr.save_alias(&name, &target) {
+                Ok(true) => {}
+                Ok(false) => {
+                    return SvpiResponse::err(
+                        cmd_out,
+                        "not_enough_memory",
+                        "Not enough memory",
+                        None,
+                    )
+                    .with_exit_code()
+                }
+                Err(err) => {
+                    return SvpiResponse::password_manager_error(cmd_out, err).with_exit_code()
+                }
+            }
+
+            (
+                SvpiResponse::ok(cmd_out, json!({ "name": name, "alias_of": target })),
+                0,
+            )
+        }
+
         cli::Command::Get(args) => {
-            let name = args.name;
+            let alias = args.name;
             let mut password = args.password.filter(|p| !p.is_empty());
             let to_clipboard = args.clipboard;
 
@@ -1463,6 +2322,16 @@ fn execute_with_output(
                 Err(err) => return err,
             };
 
+            let name = match pass_mgr.resolve_alias(&alias) {
+                Ok(name) => name,
+                Err(PasswordManagerError::ReadPasswordError(SegmentError::NotFound(_))) => {
+                    alias.clone()
+                }
+                Err(err) => {
+                    return SvpiResponse::password_manager_error(cmd_out, err).with_exit_code()
+                }
+            };
+
             let (encrypted, data_type) = {
                 let seg = pass_mgr.get_data_manager().find_segment_by_name(&name);
                 let Some(seg) = seg else {
@@ -1477,6 +2346,25 @@ fn execute_with_output(
                 (seg.info.password_fingerprint.is_some(), seg.info.data_type)
             };
 
+            let expires = match pass_mgr.entry_expiry(&name) {
+                Ok(v) => v,
+                Err(err) => {
+                    return SvpiResponse::password_manager_error(cmd_out, err).with_exit_code()
+                }
+            };
+            if let Some(expires) = expires.filter(|&e| unix_now() >= e && !args.allow_expired) {
+                return SvpiResponse::err(
+                    cmd_out,
+                    "entry_expired",
+                    format!(
+                        "Entry '{name}' expired on {} (pass --allow-expired to read it)",
+                        format_date(expires)
+                    ),
+                    Some(json!({ "name": name, "expires": expires })),
+                )
+                .with_exit_code();
+            }
+
             if encrypted && password.is_none() && output_mode == OutputFormat::Cli {
                 password = terminal::get_password(None);
                 if password.is_none() {
@@ -1494,14 +2382,73 @@ fn execute_with_output(
                 .with_exit_code();
             }
 
-            let data = match pass_mgr.read_password(&name, || password.clone().unwrap_or_default())
-            {
+            let data = match pass_mgr.read_data(&name, || password.clone().unwrap_or_default()) {
                 Ok(v) => v,
                 Err(err) => {
                     return SvpiResponse::password_manager_error(cmd_out, err).with_exit_code()
                 }
             };
 
+            let mut otp = None;
+            let (data, json_data) = match (data, args.field) {
+                (Data::Record(record), Some(field)) => match record.get(&field) {
+                    Some(value) => (value.to_string(), json!(value)),
+                    None => {
+                        return SvpiResponse::err(
+                            cmd_out,
+                            "field_not_found",
+                            format!("Field '{field}' not found in '{name}'"),
+                            Some(json!({ "name": name, "field": field })),
+                        )
+                        .with_exit_code()
+                    }
+                },
+                (Data::Record(record), None) => (record.to_json(), json!(record)),
+                (_, Some(_)) => {
+                    return SvpiResponse::invalid_argument(
+                        cmd_out,
+                        "field",
+                        format!("Data '{name}' is not a record"),
+                    )
+                    .with_exit_code()
+                }
+                (Data::Otp(config), None) => {
+                    let now = SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .map(|d| d.as_secs())
+                        .unwrap_or(0);
+                    let code = match pass_mgr.otp_code(&name, config, password.clone(), now) {
+                        Ok(Some(code)) => code,
+                        Ok(None) => {
+                            return SvpiResponse::err(
+                                cmd_out,
+                                "not_enough_memory",
+                                "Not enough memory to store the HOTP counter",
+                                None,
+                            )
+                            .with_exit_code()
+                        }
+                        Err(err) => {
+                            return SvpiResponse::password_manager_error(cmd_out, err)
+                                .with_exit_code()
+                        }
+                    };
+                    let data = code.code.clone();
+                    otp = Some(code);
+                    (data.clone(), json!(data))
+                }
+                (data, None) => match data.to_string() {
+                    Ok(v) => (v.clone(), json!(v)),
+                    Err(err) => {
+                        return SvpiResponse::password_manager_error(
+                            cmd_out,
+                            PasswordManagerError::DataError(err),
+                        )
+                        .with_exit_code()
+                    }
+                },
+            };
+
             if to_clipboard {
                 let mut clipboard = match Clipboard::new() {
                     Ok(v) => v,
@@ -1519,77 +2466,388 @@ fn execute_with_output(
                     return SvpiResponse::err(cmd_out, "clipboard_error", err.to_string(), None)
                         .with_exit_code();
                 }
-                return (
-                    SvpiResponse::ok(
-                        cmd_out,
-                        json!({ "name": name, "copied_to_clipboard": true, "data_type": data_type.to_string(), "encrypted": encrypted }),
-                    ),
-                    0,
-                );
+                let mut result = json!({ "name": alias, "copied_to_clipboard": true, "data_type": data_type.to_string(), "encrypted": encrypted });
+                if alias != name {
+                    result["alias_of"] = json!(name);
+                }
+                if let Some(expires) = expires {
+                    result["expires"] = json!(expires);
+                }
+                if let Some(otp) = otp {
+                    result["otp"] = json!(otp);
+                }
+                return (SvpiResponse::ok(cmd_out, result), 0);
             }
 
-            (
-                SvpiResponse::ok(
-                    cmd_out,
-                    json!({ "name": name, "data": data, "data_type": data_type.to_string(), "encrypted": encrypted }),
-                ),
-                0,
-            )
+            let mut result = json!({ "name": alias, "data": json_data, "data_type": data_type.to_string(), "encrypted": encrypted });
+            if alias != name {
+                result["alias_of"] = json!(name);
+            }
+            if let Some(expires) = expires {
+                result["expires"] = json!(expires);
+            }
+            if let Some(otp) = otp {
+                result["otp"] = json!(otp);
+            }
+            (SvpiResponse::ok(cmd_out, result), 0)
         }
 
-        cli::Command::Remove { name } => {
-            let mut pass_mgr = match load_mgr(interface_type, cmd_out.clone()) {
-                Ok(mgr) => mgr,
-                Err(err) => return err,
+        cli::Command::SetOtp(args) => {
+            let name = args.name;
+            let mut config = if args.secret.trim_start().starts_with("otpauth:") {
+                match OtpConfig::from_uri(&args.secret) {
+                    Ok(v) => v,
+                    Err(msg) => {
+                        return SvpiResponse::invalid_argument(cmd_out, "secret", msg)
+                            .with_exit_code()
+                    }
+                }
+            } else {
+                let secret = args.secret.trim();
+                let secret = match args.encoding {
+                    cli::OtpSecretEncoding::Base32 => OtpConfig::decode_base32_secret(secret),
+                    cli::OtpSecretEncoding::Base58 => secret.from_base58().ok(),
+                    cli::OtpSecretEncoding::Hex => hex::decode(secret).ok(),
+                };
+                let Some(secret) = secret else {
+                    return SvpiResponse::invalid_argument(
+                        cmd_out,
+                        "secret",
+                        "Secret does not match --encoding",
+                    )
+                    .with_exit_code();
+                };
+                OtpConfig::new(OtpKind::Totp, secret)
             };
 
-            let exists = pass_mgr
-                .get_data_manager()
-                .find_segment_by_name(&name)
-                .is_some();
-            if !exists {
-                return SvpiResponse::err(
-                    cmd_out,
-                    "data_not_found",
-                    format!("Data '{name}' not found"),
-                    None,
-                )
-                .with_exit_code();
+            if args.hotp || args.counter.is_some() {
+                config.kind = OtpKind::Hotp;
             }
-
-            let prompt = format!("Are you sure you want to remove '{name}'?");
-            if let Err(err) = confirm_or_require_confirm(
-                confirm,
-                output_mode,
-                cmd_out.clone(),
-                &prompt,
-                "remove",
-                json!({ "name": name }),
-            ) {
-                return err;
+            if let Some(counter) = args.counter {
+                config.counter = counter;
             }
-
-            if let Err(err) = pass_mgr.remove_password(&name) {
-                return SvpiResponse::password_manager_error(cmd_out, err).with_exit_code();
+            if let Some(digits) = args.digits {
+                config.digits = digits;
+            }
+            if let Some(period) = args.period {
+                config.period = period;
+            }
+            if let Some(algorithm) = args.algorithm {
+                config.algorithm = algorithm.into();
+            }
+            if let Some(issuer) = args.issuer.filter(|v| !v.is_empty()) {
+                config.issuer = Some(issuer);
+            }
+            if let Some(account) = args.account.filter(|v| !v.is_empty()) {
+                config.account = Some(account);
+            }
+            if let Err(msg) = config.validate() {
+                return SvpiResponse::invalid_argument(cmd_out, "secret", msg).with_exit_code();
             }
 
-            (
-                SvpiResponse::ok(cmd_out, json!({ "removed": true, "name": name })),
-                0,
-            )
-        }
+            let encryption_key = args.password.filter(|p| !p.is_empty()).or_else(|| {
+                if output_mode == OutputFormat::Cli {
+                    terminal::get_password_confirmed(None)
+                } else {
+                    None
+                }
+            });
 
-        cli::Command::Rename { old_name, new_name } => {
             let mut pass_mgr = match load_mgr(interface_type, cmd_out.clone()) {
                 Ok(mgr) => mgr,
                 Err(err) => return err,
             };
 
-            let exists = pass_mgr
-                .get_data_manager()
-                .find_segment_by_name(&old_name)
-                .is_some();
-            if !exists {
+            if let Some(err) = entry_locked_response(
+                &mut pass_mgr,
+                &name,
+                args.master_password,
+                output_mode,
+                cmd_out.clone(),
+                "set-otp",
+            ) {
+                return err;
+            }
+
+            if let Some(ref password) = encryption_key {
+                if let Err(err) = confirm_default_key_usage(
+                    &mut pass_mgr,
+                    password,
+                    None,
+                    confirm,
+                    output_mode,
+                    cmd_out.clone(),
+                    "Encryption key not found. Use provided password as default key?",
+                    "default_key_fallback",
+                    json!({ "name": name }),
+                ) {
+                    return err;
+                }
+            }
+
+            let kind = config.kind;
+            let saved = match pass_mgr.save_data(&name, Data::Otp(config), encryption_key.clone()) {
+                Ok(v) => v,
+                Err(err) => {
+                    return SvpiResponse::password_manager_error(cmd_out, err).with_exit_code()
+                }
+            };
+            if !saved {
+                return SvpiResponse::err(cmd_out, "not_enough_memory", "Not enough memory", None)
+                    .with_exit_code();
+            }
+
+            (
+                SvpiResponse::ok(
+                    cmd_out,
+                    json!({
+                        "saved": true,
+                        "name": name,
+                        "data_type": DataType::Otp.to_string(),
+                        "encrypted": encryption_key.is_some(),
+                        "kind": kind,
+                    }),
+                ),
+                0,
+            )
+        }
+
+        cli::Command::Generate(args) => {
+            let name = args.name;
+            let generated = match args.words {
+                Some(words) => generator::generate_passphrase(&PassphrasePolicy {
+                    words,
+                    separator: args.separator,
+                    capitalize: args.capitalize,
+                }),
+                None => {
+                    let classes = if args.classes.is_empty() {
+                        CharClass::value_variants().to_vec()
+                    } else {
+                        args.classes
+                    };
+                    generator::generate_password(&PasswordPolicy {
+                        length: args.length,
+                        classes,
+                        exclude_ambiguous: args.no_ambiguous,
+                    })
+                }
+            };
+            let generated = match generated {
+                Ok(v) => v,
+                Err(msg) => {
+                    let arg = if args.words.is_some() {
+                        "words"
+                    } else {
+                        "length"
+                    };
+                    return SvpiResponse::invalid_argument(cmd_out, arg, msg).with_exit_code();
+                }
+            };
+
+            let encryption_key = args.password.filter(|p| !p.is_empty()).or_else(|| {
+                if output_mode == OutputFormat::Cli {
+                    terminal::get_password_confirmed(None)
+                } else {
+                    None
+                }
+            });
+
+            let mut pass_mgr = match load_mgr(interface_type, cmd_out.clone()) {
+                Ok(mgr) => mgr,
+                Err(err) => return err,
+            };
+
+            if let Some(err) = entry_locked_response(
+                &mut pass_mgr,
+                &name,
+                args.master_password,
+                output_mode,
+                cmd_out.clone(),
+                "generate",
+            ) {
+                return err;
+            }
+
+            if let Some(ref password) = encryption_key {
+                if let Err(err) = confirm_default_key_usage(
+                    &mut pass_mgr,
+                    password,
+                    None,
+                    confirm,
+                    output_mode,
+                    cmd_out.clone(),
+                    "Encryption key not found. Use provided password as default key?",
+                    "default_key_fallback",
+                    json!({ "name": name }),
+                ) {
+                    return err;
+                }
+            }
+
+            // Stored as plain text: type inference could mistake a generated
+            // password for hex or base58.
+            let length = generated.value.chars().count();
+            let saved = match pass_mgr.save_data(
+                &name,
+                Data::Plain(generated.value),
+                encryption_key.clone(),
+            ) {
+                Ok(v) => v,
+                Err(err) => {
+                    return SvpiResponse::password_manager_error(cmd_out, err).with_exit_code()
+                }
+            };
+            if !saved {
+                return SvpiResponse::err(cmd_out, "not_enough_memory", "Not enough memory", None)
+                    .with_exit_code();
+            }
+
+            (
+                SvpiResponse::ok(
+                    cmd_out,
+                    json!({
+                        "saved": true,
+                        "name": name,
+                        "data_type": DataType::Plain.to_string(),
+                        "encrypted": encryption_key.is_some(),
+                        "kind": generated.kind,
+                        "length": length,
+                        "entropy_bits": (generated.entropy_bits * 10.0).round() / 10.0,
+                    }),
+                ),
+                0,
+            )
+        }
+
+        cli::Command::Remove {
+            name,
+            matching: true,
+            dry_run,
+            ..
+        } => run_bulk(
+            interface_type,
+            &name,
+            dry_run,
+            confirm,
+            output_mode,
+            cmd_out,
+            |pass_mgr| plan_for_matching(pass_mgr, &name, BulkAction::Remove),
+        ),
+
+        cli::Command::Remove {
+            name,
+            master_password,
+            ..
+        } => {
+            let mut pass_mgr = match load_mgr(interface_type, cmd_out.clone()) {
+                Ok(mgr) => mgr,
+                Err(err) => return err,
+            };
+
+            if let Some(err) = entry_locked_response(
+                &mut pass_mgr,
+                &name,
+                master_password,
+                output_mode,
+                cmd_out.clone(),
+                "remove",
+            ) {
+                return err;
+            }
+
+            let exists = pass_mgr
+                .get_data_manager()
+                .find_segment_by_name(&name)
+                .is_some();
+            if !exists {
+                return SvpiResponse::err(
+                    cmd_out,
+                    "data_not_found",
+                    format!("Data '{name}' not found"),
+                    None,
+                )
+                .with_exit_code();
+            }
+
+            let prompt = format!("Are you sure you want to remove '{name}'?");
+            if let Err(err) = confirm_or_require_confirm(
+                confirm,
+                output_mode,
+                cmd_out.clone(),
+                &prompt,
+                "remove",
+                json!({ "name": name }),
+            ) {
+                return err;
+            }
+
+            if let Err(err) = pass_mgr.remove_password(&name) {
+                return SvpiResponse::password_manager_error(cmd_out, err).with_exit_code();
+            }
+
+            let mut result = json!({ "removed": true, "name": name });
+            let dangling = pass_mgr.aliases_of(&name).unwrap_or_default();
+            if !dangling.is_empty() {
+                result["dangling_aliases"] = json!(dangling);
+            }
+            (SvpiResponse::ok(cmd_out, result), 0)
+        }
+
+        cli::Command::Rename {
+            old_name,
+            regex: true,
+            dry_run,
+            ..
+        } => {
+            let rule = match RenameRule::parse(&old_name) {
+                Ok(v) => v,
+                Err(message) => {
+                    return SvpiResponse::invalid_argument(cmd_out, "old_name", message)
+                        .with_exit_code()
+                }
+            };
+            run_bulk(
+                interface_type,
+                &old_name,
+                dry_run,
+                confirm,
+                output_mode,
+                cmd_out,
+                |pass_mgr| {
+                    let names = api::list_entries(pass_mgr)
+                        .into_iter()
+                        .map(|entry| entry.name)
+                        .collect::<Vec<_>>();
+                    let segments = pass_mgr.get_data_manager();
+                    plan_renames(&names, &rule, |name| {
+                        segments.find_segment_by_name(name).is_some()
+                    })
+                    .map_err(|message| ("old_name", message))
+                },
+            )
+        }
+
+        cli::Command::Rename {
+            old_name,
+            new_name,
+            master_password,
+            ..
+        } => {
+            let Some(new_name) = new_name else {
+                return SvpiResponse::missing_argument(cmd_out, "new_name").with_exit_code();
+            };
+
+            let mut pass_mgr = match load_mgr(interface_type, cmd_out.clone()) {
+                Ok(mgr) => mgr,
+                Err(err) => return err,
+            };
+
+            let exists = pass_mgr
+                .get_data_manager()
+                .find_segment_by_name(&old_name)
+                .is_some();
+            if !exists {
                 return SvpiResponse::err(
                     cmd_out,
                     "data_not_found",
@@ -1598,6 +2856,18 @@ fn execute_with_output(
                 )
                 .with_exit_code();
             }
+            for name in [&old_name, &new_name] {
+                if let Some(err) = entry_locked_response(
+                    &mut pass_mgr,
+                    name,
+                    master_password.clone(),
+                    output_mode,
+                    cmd_out.clone(),
+                    "rename",
+                ) {
+                    return err;
+                }
+            }
 
             if let Err(err) = pass_mgr.rename_password(&old_name, &new_name) {
                 return SvpiResponse::password_manager_error(cmd_out, err).with_exit_code();
@@ -1612,6 +2882,21 @@ fn execute_with_output(
             )
         }
 
+        cli::Command::ChangeDataType(args) if args.matching => {
+            let new_type: DataType = args.new_data_type.into();
+            run_bulk(
+                interface_type,
+                &args.name,
+                args.dry_run,
+                confirm,
+                output_mode,
+                cmd_out,
+                |pass_mgr| {
+                    plan_for_matching(pass_mgr, &args.name, BulkAction::ChangeType(new_type))
+                },
+            )
+        }
+
         cli::Command::ChangeDataType(args) => {
             let name = args.name;
             let new_type: DataType = args.new_data_type.into();
@@ -1635,6 +2920,17 @@ fn execute_with_output(
                 .with_exit_code();
             }
 
+            if let Some(err) = entry_locked_response(
+                &mut pass_mgr,
+                &name,
+                args.master_password,
+                output_mode,
+                cmd_out.clone(),
+                "change-data-type",
+            ) {
+                return err;
+            }
+
             if let Err(err) = pass_mgr.change_data_type(&name, new_type) {
                 return SvpiResponse::password_manager_error(cmd_out, err).with_exit_code();
             }
@@ -1659,6 +2955,17 @@ fn execute_with_output(
                 Err(err) => return err,
             };
 
+            if let Some(err) = entry_locked_response(
+                &mut pass_mgr,
+                &name,
+                args.master_password,
+                output_mode,
+                cmd_out.clone(),
+                "change-password",
+            ) {
+                return err;
+            }
+
             let encrypted = {
                 let seg = pass_mgr.get_data_manager().find_segment_by_name(&name);
                 let Some(seg) = seg else {
@@ -1698,15 +3005,15 @@ fn execute_with_output(
                 new_password = terminal::get_password_confirmed(Some("new password"));
             }
 
-            let data =
-                match pass_mgr.read_password(&name, || old_password.clone().unwrap_or_default()) {
-                    Ok(v) => v,
-                    Err(err) => {
-                        return SvpiResponse::password_manager_error(cmd_out, err).with_exit_code()
-                    }
-                };
+            let data = match pass_mgr.read_data(&name, || old_password.clone().unwrap_or_default())
+            {
+                Ok(v) => v,
+                Err(err) => {
+                    return SvpiResponse::password_manager_error(cmd_out, err).with_exit_code()
+                }
+            };
 
-            let saved = match pass_mgr.save_password(&name, &data, new_password.clone()) {
+            let saved = match pass_mgr.save_data(&name, data, new_password.clone()) {
                 Ok(v) => v,
                 Err(err) => {
                     return SvpiResponse::password_manager_error(cmd_out, err).with_exit_code()
@@ -1725,5 +3032,557 @@ fn execute_with_output(
                 0,
             )
         }
+        cli::Command::SetUrls { name, urls } => {
+            if let Some(url) = urls.iter().find(|url| url_host(url).is_none()) {
+                return SvpiResponse::invalid_argument(
+                    cmd_out,
+                    "url",
+                    format!("Invalid URL '{url}'"),
+                )
+                .with_exit_code();
+            }
+
+            let mut pass_mgr = match load_mgr(interface_type, cmd_out.clone()) {
+                Ok(mgr) => mgr,
+                Err(err) => return err,
+            };
+
+            let exists = pass_mgr
+                .get_data_manager()
+                .find_segment_by_name(&name)
+                .is_some();
+            if !exists {
+                return SvpiResponse::err(
+                    cmd_out,
+                    "data_not_found",
+                    format!("Data '{name}' not found"),
+                    None,
+                )
+                .with_exit_code();
+            }
+
+            match pass_mgr.set_entry_urls(&name, urls.clone()) {
+                Ok(true) => {}
+                Ok(false) => {
+                    return SvpiResponse::err(
+                        cmd_out,
+                        "not_enough_memory",
+                        "Not enough memory",
+                        None,
+                    )
+                    .with_exit_code()
+                }
+                Err(err) => {
+                    return SvpiResponse::password_manager_error(cmd_out, err).with_exit_code()
+                }
+            }
+
+            (
+                SvpiResponse::ok(cmd_out, json!({ "name": name, "urls": urls })),
+                0,
+            )
+        }
+
+        cli::Command::Tag { name, tags } => {
+            let tags = match tags
+                .iter()
+                .map(|tag| normalize_tag(tag))
+                .collect::<Result<Vec<_>, _>>()
+            {
+                Ok(v) => v,
+                Err(message) => {
+                    return SvpiResponse::invalid_argument(cmd_out, "tag", message).with_exit_code()
+                }
+            };
+            update_entry_grouping(interface_type, name.clone(), cmd_out, |pass_mgr| {
+                pass_mgr.add_entry_tags(&name, &tags)
+            })
+        }
+
+        cli::Command::Untag { name, tags } => {
+            let tags = tags
+                .iter()
+                .map(|tag| tag.trim().to_string())
+                .collect::<Vec<_>>();
+            update_entry_grouping(interface_type, name.clone(), cmd_out, |pass_mgr| {
+                pass_mgr.remove_entry_tags(&name, &tags)
+            })
+        }
+
+        cli::Command::Mv {
+            name,
+            folder,
+            matching,
+            dry_run,
+        } => {
+            let folder = match normalize_folder(&folder) {
+                Ok(v) => v,
+                Err(message) => {
+                    return SvpiResponse::invalid_argument(cmd_out, "folder", message)
+                        .with_exit_code()
+                }
+            };
+            if matching {
+                return run_bulk(
+                    interface_type,
+                    &name,
+                    dry_run,
+                    confirm,
+                    output_mode,
+                    cmd_out,
+                    |pass_mgr| plan_for_matching(pass_mgr, &name, BulkAction::Move(folder)),
+                );
+            }
+            update_entry_grouping(interface_type, name.clone(), cmd_out, |pass_mgr| {
+                pass_mgr.set_entry_folder(&name, folder)
+            })
+        }
+
+        cli::Command::SetExpiry { name, expires } => {
+            let expires = if expires.trim().eq_ignore_ascii_case("never") {
+                None
+            } else {
+                match parse_expiry(&expires) {
+                    Ok(v) => Some(v),
+                    Err(message) => {
+                        return SvpiResponse::invalid_argument(cmd_out, "expires", message)
+                            .with_exit_code()
+                    }
+                }
+            };
+
+            let mut pass_mgr = match load_mgr(interface_type, cmd_out.clone()) {
+                Ok(mgr) => mgr,
+                Err(err) => return err,
+            };
+            if pass_mgr
+                .get_data_manager()
+                .find_segment_by_name(&name)
+                .is_none()
+            {
+                return SvpiResponse::err(
+                    cmd_out,
+                    "data_not_found",
+                    format!("Data '{name}' not found"),
+                    None,
+                )
+                .with_exit_code();
+            }
+
+            match pass_mgr.set_entry_expiry(&name, expires) {
+                Ok(true) => (
+                    SvpiResponse::ok(cmd_out, json!({ "name": name, "expires": expires })),
+                    0,
+                ),
+                Ok(false) => {
+                    SvpiResponse::err(cmd_out, "not_enough_memory", "Not enough memory", None)
+                        .with_exit_code()
+                }
+                Err(err) => SvpiResponse::password_manager_error(cmd_out, err).with_exit_code(),
+            }
+        }
+
+        cli::Command::Lock { name } => {
+            let mut pass_mgr = match load_mgr(interface_type, cmd_out.clone()) {
+                Ok(mgr) => mgr,
+                Err(err) => return err,
+            };
+            set_entry_lock(&mut pass_mgr, name, true, cmd_out)
+        }
+
+        cli::Command::Unlock {
+            name,
+            master_password,
+        } => {
+            let mut pass_mgr = match load_mgr(interface_type, cmd_out.clone()) {
+                Ok(mgr) => mgr,
+                Err(err) => return err,
+            };
+            if let Some(err) = require_master_password_if_set(
+                &pass_mgr,
+                master_password,
+                output_mode,
+                cmd_out.clone(),
+                "unlock",
+            ) {
+                return err;
+            }
+            set_entry_lock(&mut pass_mgr, name, false, cmd_out)
+        }
+
+        cli::Command::FindByUrl { url } => {
+            let mut pass_mgr = match load_mgr(interface_type, cmd_out.clone()) {
+                Ok(mgr) => mgr,
+                Err(err) => return err,
+            };
+
+            let candidates = match pass_mgr.find_by_url(&url) {
+                Ok(Some(v)) => v,
+                Ok(None) => {
+                    return SvpiResponse::invalid_argument(
+                        cmd_out,
+                        "url",
+                        format!("Invalid URL '{url}'"),
+                    )
+                    .with_exit_code()
+                }
+                Err(err) => {
+                    return SvpiResponse::password_manager_error(cmd_out, err).with_exit_code()
+                }
+            };
+
+            (
+                SvpiResponse::ok(cmd_out, json!({ "url": url, "candidates": candidates })),
+                0,
+            )
+        }
+
+        cli::Command::Audit(args) => {
+            let breach_db = match args
+                .breach_db
+                .as_deref()
+                .map(|path| BreachDb::open(Path::new(path)))
+            {
+                Some(Ok(db)) => Some(db),
+                Some(Err(err)) => {
+                    return SvpiResponse::err(
+                        cmd_out,
+                        "io_error",
+                        err.to_string(),
+                        Some(json!({ "file": args.breach_db })),
+                    )
+                    .with_exit_code()
+                }
+                None => None,
+            };
+
+            let mut pass_mgr = match load_mgr(interface_type, cmd_out.clone()) {
+                Ok(mgr) => mgr,
+                Err(err) => return err,
+            };
+
+            let mut passwords = args.password;
+            let has_encrypted = pass_mgr
+                .get_data_manager()
+                .get_active_segments()
+                .into_iter()
+                .any(|seg| {
+                    seg.info.password_fingerprint.is_some()
+                        && seg.info.data_type != DataType::EncryptionKey
+                });
+            if passwords.is_empty() && has_encrypted && output_mode == OutputFormat::Cli {
+                while let Some(password) =
+                    terminal::get_password(Some("password to unlock entries (empty to finish)"))
+                {
+                    passwords.push(password);
+                }
+            }
+
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0);
+            let report = match pass_mgr.audit(&AuditOptions {
+                passwords,
+                stale_days: args.stale_days,
+                expiry_days: args.expiry_days,
+                now,
+                breach_db,
+            }) {
+                Ok(v) => v,
+                Err(err) => {
+                    return SvpiResponse::password_manager_error(cmd_out, err).with_exit_code()
+                }
+            };
+
+            (SvpiResponse::ok(cmd_out, json!(report)), 0)
+        }
+
+        cli::Command::GenCert(args) => {
+            let cert_path = Path::new(&args.cert_file);
+            let key_path = Path::new(&args.key_file);
+
+            let existing = [cert_path, key_path]
+                .into_iter()
+                .filter(|p| p.exists())
+                .map(|p| p.display().to_string())
+                .collect::<Vec<_>>();
+            if !existing.is_empty() {
+                let prompt = format!("Overwrite {}?", existing.join(", "));
+                if let Err(err) = confirm_or_require_confirm(
+                    confirm,
+                    output_mode,
+                    cmd_out.clone(),
+                    &prompt,
+                    "overwrite",
+                    json!({ "files": existing }),
+                ) {
+                    return err;
+                }
+            }
+
+            let cert = match tls::generate_self_signed(&args.host) {
+                Ok(v) => v,
+                Err(err) => {
+                    return SvpiResponse::err(cmd_out, "internal_error", err.to_string(), None)
+                        .with_exit_code()
+                }
+            };
+
+            if let Err(err) = fs::write(cert_path, &cert.cert_pem) {
+                return SvpiResponse::err(
+                    cmd_out,
+                    "io_error",
+                    err.to_string(),
+                    Some(json!({ "file": args.cert_file })),
+                )
+                .with_exit_code();
+            }
+            if let Err(err) = tls::write_private_key(key_path, &cert.key_pem) {
+                return SvpiResponse::err(
+                    cmd_out,
+                    "io_error",
+                    err.to_string(),
+                    Some(json!({ "file": args.key_file })),
+                )
+                .with_exit_code();
+            }
+
+            let hosts = if args.host.is_empty() {
+                tls::DEFAULT_CERT_HOSTS
+                    .iter()
+                    .map(|h| h.to_string())
+                    .collect()
+            } else {
+                args.host
+            };
+
+            (
+                SvpiResponse::ok(
+                    cmd_out,
+                    json!({
+                        "generated": true,
+                        "cert_file": args.cert_file,
+                        "key_file": args.key_file,
+                        "hosts": hosts,
+                    }),
+                ),
+                0,
+            )
+        }
+
+        cli::Command::InstallNativeHost(args) => {
+            for id in &args.extension_id {
+                if let Err(err) = native_host::validate_extension_id(args.browser, id) {
+                    return SvpiResponse::invalid_argument(cmd_out, "extension-id", err)
+                        .with_exit_code();
+                }
+            }
+
+            let exe = match std::env::current_exe() {
+                Ok(v) => v,
+                Err(err) => {
+                    return SvpiResponse::err(cmd_out, "io_error", err.to_string(), None)
+                        .with_exit_code()
+                }
+            };
+            // The browser starts the host in its own working directory.
+            let file = match interface_type {
+                DataInterfaceType::FileSystem(path) => match std::path::absolute(path) {
+                    Ok(v) => Some(v),
+                    Err(err) => {
+                        return SvpiResponse::err(
+                            cmd_out,
+                            "io_error",
+                            err.to_string(),
+                            Some(json!({ "file": path })),
+                        )
+                        .with_exit_code()
+                    }
+                },
+                _ => None,
+            };
+
+            let files =
+                match native_host::install(args.browser, &args.extension_id, &exe, file.as_deref())
+                {
+                    Ok(v) => v,
+                    Err(err) => {
+                        return SvpiResponse::err(cmd_out, "io_error", err.to_string(), None)
+                            .with_exit_code()
+                    }
+                };
+
+            (
+                SvpiResponse::ok(
+                    cmd_out,
+                    json!({
+                        "installed": true,
+                        "browser": args.browser.as_str(),
+                        "host_name": native_host::HOST_NAME,
+                        "manifest": files.manifest.display().to_string(),
+                        "wrapper": files.wrapper.display().to_string(),
+                        "extension_ids": args.extension_id,
+                        "file": file.map(|f| f.display().to_string()),
+                    }),
+                ),
+                0,
+            )
+        }
+        cli::Command::UninstallNativeHost(args) => {
+            let removed = match native_host::uninstall(args.browser) {
+                Ok(v) => v,
+                Err(err) => {
+                    return SvpiResponse::err(cmd_out, "io_error", err.to_string(), None)
+                        .with_exit_code()
+                }
+            };
+
+            (
+                SvpiResponse::ok(
+                    cmd_out,
+                    json!({
+                        "uninstalled": !removed.is_empty(),
+                        "browser": args.browser.as_str(),
+                        "removed": removed
+                            .iter()
+                            .map(|p| p.display().to_string())
+                            .collect::<Vec<_>>(),
+                    }),
+                ),
+                0,
+            )
+        }
+
+        cli::Command::Token(cli::TokenCommand::Create(args)) => {
+            let name = args.name;
+            if name.trim().is_empty() {
+                return SvpiResponse::missing_argument(cmd_out, "name").with_exit_code();
+            }
+
+            let mut scopes = Vec::with_capacity(args.scope.len());
+            for scope in &args.scope {
+                match scope.parse::<TokenScope>() {
+                    Ok(v) => scopes.push(v),
+                    Err(message) => {
+                        return SvpiResponse::invalid_argument(cmd_out, "scope", message)
+                            .with_exit_code()
+                    }
+                }
+            }
+            if scopes.is_empty() {
+                return SvpiResponse::missing_argument(cmd_out, "scope (--scope=...)")
+                    .with_exit_code();
+            }
+
+            let mut pass_mgr = match load_mgr(interface_type, cmd_out.clone()) {
+                Ok(mgr) => mgr,
+                Err(err) => return err,
+            };
+
+            if let Some(err) = require_master_password_if_set(
+                &pass_mgr,
+                args.master_password,
+                output_mode,
+                cmd_out.clone(),
+                "token-create",
+            ) {
+                return err;
+            }
+
+            match pass_mgr.create_api_token(&name, scopes) {
+                Ok(Some((record, token))) => (
+                    SvpiResponse::ok(
+                        cmd_out,
+                        json!({
+                            "created": true,
+                            "id": record.id,
+                            "name": record.name,
+                            "scopes": record.scopes,
+                            "token": token,
+                        }),
+                    ),
+                    0,
+                ),
+                Ok(None) => {
+                    SvpiResponse::err(cmd_out, "not_enough_memory", "Not enough memory", None)
+                        .with_exit_code()
+                }
+                Err(err) => SvpiResponse::password_manager_error(cmd_out, err).with_exit_code(),
+            }
+        }
+
+        cli::Command::Token(cli::TokenCommand::List) => {
+            let mut pass_mgr = match load_mgr(interface_type, cmd_out.clone()) {
+                Ok(mgr) => mgr,
+                Err(err) => return err,
+            };
+
+            let tokens = match pass_mgr.load_api_tokens() {
+                Ok(v) => v,
+                Err(err) => {
+                    return SvpiResponse::password_manager_error(cmd_out, err).with_exit_code()
+                }
+            };
+
+            let tokens = tokens
+                .into_iter()
+                .map(|token| {
+                    json!({
+                        "id": token.id,
+                        "name": token.name,
+                        "scopes": token.scopes,
+                        "created_at": token.created_at,
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            (SvpiResponse::ok(cmd_out, json!({ "tokens": tokens })), 0)
+        }
+
+        cli::Command::Token(cli::TokenCommand::Revoke(args)) => {
+            let id = args.id;
+
+            let mut pass_mgr = match load_mgr(interface_type, cmd_out.clone()) {
+                Ok(mgr) => mgr,
+                Err(err) => return err,
+            };
+
+            if let Some(err) = require_master_password_if_set(
+                &pass_mgr,
+                args.master_password,
+                output_mode,
+                cmd_out.clone(),
+                "token-revoke",
+            ) {
+                return err;
+            }
+
+            let revoked = match pass_mgr.revoke_api_token(&id) {
+                Ok(v) => v,
+                Err(err) => {
+                    return SvpiResponse::password_manager_error(cmd_out, err).with_exit_code()
+                }
+            };
+            if revoked.is_empty() {
+                return SvpiResponse::err(
+                    cmd_out,
+                    "data_not_found",
+                    format!("Token '{id}' not found"),
+                    None,
+                )
+                .with_exit_code();
+            }
+
+            (
+                SvpiResponse::ok(
+                    cmd_out,
+                    json!({
+                        "revoked": revoked.iter().map(|t| t.id.clone()).collect::<Vec<_>>(),
+                    }),
+                ),
+                0,
+            )
+        }
     }
 }
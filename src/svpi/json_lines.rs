@@ -0,0 +1,348 @@
+use std::io::{self, BufRead, Write};
+
+use serde::Deserialize;
+
+use crate::{
+    cli,
+    data_mgr::DataInterfaceType,
+    svpi::cli_mode::execute_with_output,
+    utils::response::{OutputFormat, SvpiResponse},
+};
+
+pub const MAX_JSON_LINE_SIZE: usize = 1024 * 1024;
+
+/// One line of `--mode=json-lines` input, e.g. `{"get": {"name": "x"}}` or
+/// `{"remove": {"name": "x"}, "confirm": true}`.
+#[derive(Debug, Deserialize)]
+pub struct JsonLineRequest {
+    #[serde(flatten)]
+    pub command: cli::Command,
+    #[serde(default)]
+    pub confirm: bool,
+}
+
+pub fn parse_request(line: &str) -> Result<JsonLineRequest, Box<SvpiResponse>> {
+    if line.len() > MAX_JSON_LINE_SIZE {
+        return Err(Box::new(SvpiResponse::invalid_argument(
+            None,
+            "request",
+            format!("Request too large ({} bytes)", line.len()),
+        )));
+    }
+
+    serde_json::from_str(line).map_err(|err| {
+        Box::new(SvpiResponse::invalid_argument(
+            None,
+            "request",
+            err.to_string(),
+        ))
+    })
+}
+
+pub fn handle_line(
+    line: &str,
+    interface_type: &mut DataInterfaceType,
+    confirm: bool,
+) -> Option<SvpiResponse> {
+    let line = line.trim();
+    if line.is_empty() {
+        return None;
+    }
+
+    let request = match parse_request(line) {
+        Ok(v) => v,
+        Err(resp) => return Some(*resp),
+    };
+
+    let update_default_interface = match &request.command {
+        cli::Command::SetFile { file_name } => {
+            Some(DataInterfaceType::FileSystem(file_name.clone()))
+        }
+        _ => None,
+    };
+
+    let (resp, _code) = execute_with_output(
+        request.command,
+        OutputFormat::Json,
+        interface_type,
+        confirm || request.confirm,
+    );
+
+    if resp.ok {
+        if let Some(v) = update_default_interface {
+            *interface_type = v;
+        }
+    }
+
+    Some(resp)
+}
+
+pub fn run_json_lines(cli: &cli::CliArgs) -> i32 {
+    let mut interface_type = cli.interface_type();
+    let confirm = cli.confirm;
+
+    let stdin = io::stdin();
+    let mut stdout = io::stdout();
+
+    for line in stdin.lock().lines() {
+        let line = match line {
+            Ok(v) => v,
+            Err(err) => {
+                let resp = SvpiResponse::err(
+                    None,
+                    "io_error",
+                    format!("Failed to read request: {err}"),
+                    None,
+                );
+                let _ = writeln!(stdout, "{}", resp.to_json_string());
+                return 1;
+            }
+        };
+
+        let Some(resp) = handle_line(&line, &mut interface_type, confirm) else {
+            continue;
+        };
+
+        if writeln!(stdout, "{}", resp.to_json_string()).is_err() || stdout.flush().is_err() {
+            return 1;
+        }
+    }
+
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{pass_mgr::PasswordManager, seg_mgr::EncryptionLevel};
+
+    fn seeded_dump() -> Vec<u8> {
+        let mut pass_mgr =
+            PasswordManager::from_device_type(DataInterfaceType::Memory(vec![])).expect("init mgr");
+        pass_mgr
+            .get_data_manager()
+            .init_device(1024, EncryptionLevel::Low)
+            .expect("init device");
+        pass_mgr
+            .save_password("plain", "alpha", None)
+            .expect("save plain");
+        pass_mgr
+            .save_password("secret", "bravo", Some("pw".to_string()))
+            .expect("save encrypted");
+        pass_mgr.get_data_manager().get_dump().expect("dump")
+    }
+
+    #[test]
+    fn parse_request_accepts_commands_and_confirm() {
+        let req = parse_request(r#"{"list": null}"#).expect("list");
+        assert!(matches!(req.command, cli::Command::List(_)));
+        assert!(!req.confirm);
+
+        let req = parse_request(r#"{"remove": {"name": "a"}, "confirm": true}"#).expect("remove");
+        assert!(
+            matches!(req.command, cli::Command::Remove { ref name, matching: false, .. } if name == "a")
+        );
+        assert!(req.confirm);
+
+        let req = parse_request(r#"{"dump": {"file_name": "out.bin"}}"#).expect("dump");
+        assert!(matches!(
+            req.command,
+            cli::Command::Dump {
+                protection: cli::EncryptionLevelArg::Medium,
+                ..
+            }
+        ));
+    }
+
+    /// The wire format is the clap argument names (see docs/api.md), and unknown
+    /// fields are ignored, so a renamed flag would silently drop a client's
+    /// value. Renaming any of these fields breaks this test on purpose.
+    #[test]
+    fn wire_format_field_names_are_pinned() {
+        let req = parse_request(
+            r#"{"set": {"name": "pin", "data": "1234", "password": "pw", "fields": ["user=a"],
+                "type": "plain", "expires": "30d", "master_password": "m"}}"#,
+        )
+        .expect("set");
+        let cli::Command::Set(args) = req.command else {
+            panic!("expected set");
+        };
+        assert_eq!(args.name, "pin");
+        assert_eq!(args.data.as_deref(), Some("1234"));
+        assert_eq!(args.password.as_deref(), Some("pw"));
+        assert_eq!(args.fields, ["user=a"]);
+        assert_eq!(args.data_type, Some(cli::DataTypeArg::Plain));
+        assert_eq!(args.expires.as_deref(), Some("30d"));
+        assert_eq!(args.master_password.as_deref(), Some("m"));
+
+        let req = parse_request(
+            r#"{"get": {"name": "pin", "password": "pw", "clipboard": true, "field": "user",
+                "allow_expired": true}}"#,
+        )
+        .expect("get");
+        let cli::Command::Get(args) = req.command else {
+            panic!("expected get");
+        };
+        assert_eq!(args.name, "pin");
+        assert_eq!(args.password.as_deref(), Some("pw"));
+        assert!(args.clipboard);
+        assert_eq!(args.field.as_deref(), Some("user"));
+        assert!(args.allow_expired);
+
+        let req = parse_request(
+            r#"{"remove": {"name": "tmp/*", "match": true, "dry_run": true,
+                "master_password": "m"}}"#,
+        )
+        .expect("remove");
+        let cli::Command::Remove {
+            name,
+            matching,
+            dry_run,
+            master_password,
+        } = req.command
+        else {
+            panic!("expected remove");
+        };
+        assert_eq!(name, "tmp/*");
+        assert!(matching && dry_run);
+        assert_eq!(master_password.as_deref(), Some("m"));
+
+        let req = parse_request(
+            r#"{"rename": {"old_name": "a", "new_name": "b", "dry_run": true,
+                "master_password": "m"}}"#,
+        )
+        .expect("rename");
+        let cli::Command::Rename {
+            old_name,
+            new_name,
+            regex,
+            dry_run,
+            master_password,
+        } = req.command
+        else {
+            panic!("expected rename");
+        };
+        assert_eq!(old_name, "a");
+        assert_eq!(new_name.as_deref(), Some("b"));
+        assert!(!regex && dry_run);
+        assert_eq!(master_password.as_deref(), Some("m"));
+        let req = parse_request(r#"{"rename": {"old_name": "s/a/b/", "regex": true}}"#)
+            .expect("rename --regex");
+        assert!(matches!(
+            req.command,
+            cli::Command::Rename { regex: true, .. }
+        ));
+
+        let req = parse_request(
+            r#"{"change_data_type": {"name": "a*", "new_data_type": "hex", "match": true,
+                "dry_run": true}}"#,
+        )
+        .expect("change_data_type");
+        let cli::Command::ChangeDataType(args) = req.command else {
+            panic!("expected change_data_type");
+        };
+        assert_eq!(args.name, "a*");
+        assert_eq!(args.new_data_type, cli::DataTypeArg::Hex);
+        assert!(args.matching && args.dry_run);
+
+        let req = parse_request(
+            r#"{"change_password": {"name": "a", "old_password": "o", "new_password": "n",
+                "master_password": "m"}}"#,
+        )
+        .expect("change_password");
+        let cli::Command::ChangePassword(args) = req.command else {
+            panic!("expected change_password");
+        };
+        assert_eq!(args.name, "a");
+        assert_eq!(args.old_password.as_deref(), Some("o"));
+        assert_eq!(args.new_password.as_deref(), Some("n"));
+        assert_eq!(args.master_password.as_deref(), Some("m"));
+
+        let req = parse_request(
+            r#"{"mv": {"name": "a*", "folder": "work", "match": true, "dry_run": true}}"#,
+        )
+        .expect("mv");
+        let cli::Command::Mv {
+            name,
+            folder,
+            matching,
+            dry_run,
+            ..
+        } = req.command
+        else {
+            panic!("expected mv");
+        };
+        assert_eq!(name, "a*");
+        assert_eq!(folder, "work");
+        assert!(matching && dry_run);
+
+        let req = parse_request(
+            r#"{"dump": {"file_name": "out.bin", "protection": "strong", "password": "pw"}}"#,
+        )
+        .expect("dump");
+        let cli::Command::Dump {
+            file_name,
+            protection,
+            password,
+        } = req.command
+        else {
+            panic!("expected dump");
+        };
+        assert_eq!(file_name, "out.bin");
+        assert_eq!(protection, cli::EncryptionLevelArg::Strong);
+        assert_eq!(password.as_deref(), Some("pw"));
+    }
+
+    #[test]
+    fn parse_request_rejects_unknown_command() {
+        let resp = parse_request(r#"{"explode": {}}"#).unwrap_err();
+        assert_eq!(resp.error.as_ref().unwrap().code, "invalid_argument");
+    }
+
+    #[test]
+    fn handle_line_skips_blank_lines() {
+        let mut interface_type = DataInterfaceType::Memory(seeded_dump());
+        assert!(handle_line("   ", &mut interface_type, false).is_none());
+    }
+
+    #[test]
+    fn handle_line_executes_get() {
+        let mut interface_type = DataInterfaceType::Memory(seeded_dump());
+
+        let resp = handle_line(
+            r#"{"get": {"name": "secret", "password": "pw"}}"#,
+            &mut interface_type,
+            false,
+        )
+        .expect("response");
+        assert!(resp.ok);
+        assert_eq!(resp.command.as_deref(), Some("get"));
+        assert_eq!(resp.result.as_ref().unwrap()["data"], "bravo");
+
+        let resp = handle_line(r#"{"get": {"name": "secret"}}"#, &mut interface_type, false)
+            .expect("response");
+        assert!(!resp.ok);
+        assert_eq!(resp.error.as_ref().unwrap().code, "password_required");
+    }
+
+    #[test]
+    fn handle_line_requires_confirm_for_destructive_commands() {
+        let mut interface_type = DataInterfaceType::Memory(seeded_dump());
+
+        let resp = handle_line(
+            r#"{"remove": {"name": "plain"}}"#,
+            &mut interface_type,
+            false,
+        )
+        .expect("response");
+        assert_eq!(resp.error.as_ref().unwrap().code, "confirmation_required");
+
+        let resp = handle_line(
+            r#"{"remove": {"name": "plain"}, "confirm": true}"#,
+            &mut interface_type,
+            false,
+        )
+        .expect("response");
+        assert!(resp.ok);
+    }
+}
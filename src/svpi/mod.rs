@@ -1,4 +1,5 @@
 pub mod cli_mode;
+pub mod json_lines;
 
 pub const HELP_COMMANDS: &[(&str, &str)] = &[
     ("svpi", "Start interactive command prompt (CLI mode)"),
@@ -7,6 +8,14 @@ pub const HELP_COMMANDS: &[(&str, &str)] = &[
         "svpi set-file / sf <file_name>",
         "Set default --file via .svpi",
     ),
+    (
+        "svpi set-cors-origins [origin]...",
+        "Set server CORS origins via .svpi (none clears)",
+    ),
+    (
+        "svpi set-default-type <infer|plain>",
+        "How `set` stores text without --type, via .svpi",
+    ),
     (
         "svpi init / i <memory_size> [low|medium|strong|hardened]",
         "Initialize the device for the desired architecture",
@@ -49,22 +58,104 @@ pub const HELP_COMMANDS: &[(&str, &str)] = &[
         "svpi sync-keys / sync",
         "Synchronize encryption keys fingerprints",
     ),
-    ("svpi list / l", "Print all data list"),
+    (
+        "svpi list / l [pattern] [--tag=<tag>...] [--folder=<folder>]",
+        "Print all data list (glob on names)",
+    ),
     (
         "svpi set / s <name> <data>",
         "Set data (string, JSON byte array or binary from file)",
     ),
+    (
+        "svpi set-expiry <name> <YYYY-MM-DD|never>",
+        "Set when an entry expires; get then needs --allow-expired",
+    ),
+    (
+        "svpi set-alias <name> <target>",
+        "Store a name that reads another entry (by name or fingerprint)",
+    ),
     ("svpi get / g <name>", "Get data"),
+    (
+        "svpi generate / gen <name>",
+        "Generate and store a password or passphrase (value is not printed)",
+    ),
+    (
+        "svpi set-otp <name> <secret|otpauth-uri>",
+        "Store a TOTP/HOTP seed; get returns the current code",
+    ),
     ("svpi remove / r <name>", "Remove data"),
+    (
+        "svpi remove --match <glob> [--dry-run]",
+        "Remove every entry whose name matches",
+    ),
     ("svpi rename / rn <old_name> <new_name>", "Rename data"),
+    (
+        "svpi lock <name>",
+        "Refuse set/remove/rename/change-password without --master-password",
+    ),
+    (
+        "svpi unlock <name> [--master-password=..]",
+        "Remove the lock (master password required if set)",
+    ),
+    (
+        "svpi rename --regex s/<pattern>/<replacement>/[gi] [--dry-run]",
+        "Rename every entry the expression matches",
+    ),
     (
         "svpi change-data-type / cdt <name> <new_data_type>",
         "Change data type",
     ),
+    (
+        "svpi change-data-type --match <glob> <new_data_type> [--dry-run]",
+        "Change the data type of every matching entry",
+    ),
     (
         "svpi change-password / cp <name>",
         "Change data password (omit new password to remove encryption)",
     ),
+    (
+        "svpi set-urls <name> [url]...",
+        "Set the sites an entry belongs to (none clears)",
+    ),
+    ("svpi tag <name> <tag>...", "Add tags to an entry"),
+    (
+        "svpi untag <name> [tag]...",
+        "Remove tags from an entry (none removes all)",
+    ),
+    (
+        "svpi mv <name> <folder>",
+        "Move an entry to a folder (/ is the top level)",
+    ),
+    (
+        "svpi mv --match <glob> <folder> [--dry-run]",
+        "Move every matching entry to a folder",
+    ),
+    (
+        "svpi find-by-url <url>",
+        "List entries that belong to a site",
+    ),
+    (
+        "svpi audit",
+        "Report weak, reused and stale secrets and risky entries",
+    ),
+    (
+        "svpi gen-cert <cert_file> <key_file> [--host=<host>...]",
+        "Generate a self-signed TLS certificate for server mode",
+    ),
+    (
+        "svpi install-native-host --browser=<chrome|chromium|firefox|brave> --extension-id=<id>...",
+        "Register the Chrome app as the extension's native messaging host",
+    ),
+    (
+        "svpi uninstall-native-host --browser=<browser>",
+        "Remove the native messaging host registration",
+    ),
+    (
+        "svpi token create <name> --scope=<scope>...",
+        "Create an HTTP API token (list, read[:pattern], write[:pattern])",
+    ),
+    ("svpi token list", "List HTTP API tokens"),
+    ("svpi token revoke <id|name>", "Revoke HTTP API tokens"),
     (
         "svpi self-hash / hash",
         "Print SHA-256 of the running executable",
@@ -73,11 +164,15 @@ pub const HELP_COMMANDS: &[(&str, &str)] = &[
     ("svpi help / h", "Print this help message"),
     ("svpi --mode=server", "Start the API server"),
     ("svpi --mode=chrome", "Start the Chrome app"),
+    (
+        "svpi --mode=json-lines",
+        "Read JSON requests from stdin (one per line)",
+    ),
 ];
 
 pub const HELP_FLAGS: &[(&str, &str)] = &[
     (
-        "svpi --mode=<cli|json|server|chrome>",
+        "svpi --mode=<cli|json|json-lines|server|chrome>",
         "Select application mode (default: cli)",
     ),
     (
@@ -93,6 +188,54 @@ pub const HELP_FLAGS: &[(&str, &str)] = &[
         "svpi set <name> <value> --password=<password>",
         "Provide password via command line",
     ),
+    (
+        "svpi set <name> --field=<key>=<value>",
+        "Create or edit a record field (repeatable; <key>= removes it)",
+    ),
+    (
+        "svpi get <name> --field=<key>",
+        "Print (or copy) a single record field",
+    ),
+    (
+        "svpi list [pattern] --regex --type=<type> --encrypted|--unencrypted --key=<name|fp>",
+        "Filter the list (also --min-size, --max-size)",
+    ),
+    (
+        "svpi list --sort=<name|size|type> --desc --format=<table|json|csv>",
+        "Sort the list and choose the output format",
+    ),
+    (
+        "svpi generate <name> --length=<n> --classes=<lower,upper,digits,symbols> --no-ambiguous",
+        "Password policy (default: 24 characters, all classes)",
+    ),
+    (
+        "svpi generate <name> --words=<n> --separator=<sep> --capitalize",
+        "Generate a passphrase from the embedded wordlist",
+    ),
+    (
+        "svpi audit --password=<password>... --stale-days=<n>",
+        "Passwords that unlock entries for the audit; stale threshold (default: 365 days)",
+    ),
+    (
+        "svpi audit --expiry-days=<n>",
+        "Report entries expiring within n days (default: 30)",
+    ),
+    (
+        "svpi set <name> <data> --type=<plain|hex|base58|base64|binary>",
+        "Store the data as this type instead of guessing it from the text",
+    ),
+    (
+        "svpi set <name> <data> --expires=<YYYY-MM-DD>",
+        "Store the entry with an expiry date (list and check warn 30 days ahead)",
+    ),
+    (
+        "svpi audit --breach-db=<path>",
+        "Also check passwords against an offline HIBP SHA-1 file or range directory",
+    ),
+    (
+        "svpi set-otp <name> <secret> --encoding=<base32|base58|hex> --hotp --counter=<n>",
+        "Seed encoding and HOTP mode (also --digits, --period, --algorithm, --issuer, --account)",
+    ),
     (
         "svpi dump <file_name> [low|medium|strong|hardened] --password=<password>",
         "Encrypt dump with a password",
@@ -121,6 +264,10 @@ pub const HELP_FLAGS: &[(&str, &str)] = &[
         "svpi --mode=server --auto-exit",
         "Automatically exit the API server after device disconnection",
     ),
+    (
+        "svpi --mode=server --device-events",
+        "Print device connect/disconnect events as JSON lines",
+    ),
     (
         "svpi --mode=server --bind=<ip> --port=<port>",
         "Configure API server bind/port (default: 127.0.0.1:3333)",
@@ -129,6 +276,30 @@ pub const HELP_FLAGS: &[(&str, &str)] = &[
         "svpi --mode=server --cors=<none|allow-all>",
         "Configure API server CORS (default: none)",
     ),
+    (
+        "svpi --mode=server --cors-origin=<origin>",
+        "Allow one exact CORS origin (repeatable)",
+    ),
+    (
+        "svpi --mode=server --tls-cert=<file> --tls-key=<file>",
+        "Serve HTTPS (required for non-loopback --bind)",
+    ),
+    (
+        "svpi --mode=server --bind=<ip> --insecure",
+        "Allow plain HTTP on a non-loopback bind",
+    ),
+    (
+        "svpi --mode=server --listen=unix:<path> [--allowed-uid=<uid>]",
+        "Serve the API on a Unix socket (0600)",
+    ),
+    (
+        "svpi --mode=server --lockout-after=<n> --lockout-secs=<secs>",
+        "Failed password attempts before lockout, and its duration (also chrome mode)",
+    ),
+    (
+        "svpi --mode=server --post-only",
+        "Disable GET routes (use POST /api with a JSON body)",
+    ),
     (
         "svpi <command> --file=<file_name>",
         "Open a file password storage",
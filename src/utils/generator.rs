@@ -0,0 +1,218 @@
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+/// BIP-39 English wordlist (2048 words, 11 bits each).
+const WORDLIST: &str = include_str!("wordlist.txt");
+
+const LOWER: &str = "abcdefghijklmnopqrstuvwxyz";
+const UPPER: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const DIGITS: &str = "0123456789";
+const SYMBOLS: &str = "!#$%&()*+,-./:;<=>?@[]^_{}~";
+/// Characters that are easy to confuse when read or typed by hand.
+const AMBIGUOUS: &str = "Il1O0o|";
+
+pub const MIN_PASSWORD_LENGTH: usize = 4;
+pub const MAX_PASSWORD_LENGTH: usize = 1024;
+pub const MAX_PASSPHRASE_WORDS: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CharClass {
+    Lower,
+    Upper,
+    Digits,
+    Symbols,
+}
+
+impl CharClass {
+    fn chars(self) -> &'static str {
+        match self {
+            CharClass::Lower => LOWER,
+            CharClass::Upper => UPPER,
+            CharClass::Digits => DIGITS,
+            CharClass::Symbols => SYMBOLS,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PasswordPolicy {
+    pub length: usize,
+    /// Every class appears at least once.
+    pub classes: Vec<CharClass>,
+    pub exclude_ambiguous: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct PassphrasePolicy {
+    pub words: usize,
+    pub separator: String,
+    pub capitalize: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GeneratedKind {
+    Password,
+    Passphrase,
+}
+
+pub struct Generated {
+    pub value: String,
+    pub kind: GeneratedKind,
+    /// Estimated entropy in bits, assuming the policy is known to an attacker.
+    pub entropy_bits: f64,
+}
+
+/// Uniform index in `0..n` (rejection sampling, no modulo bias).
+fn random_index(n: usize) -> usize {
+    let n = n as u64;
+    let zone = u64::MAX - u64::MAX % n;
+    loop {
+        let v = OsRng.next_u64();
+        if v < zone {
+            return (v % n) as usize;
+        }
+    }
+}
+
+fn pick(chars: &[char]) -> char {
+    chars[random_index(chars.len())]
+}
+
+pub fn generate_password(policy: &PasswordPolicy) -> Result<Generated, String> {
+    if !(MIN_PASSWORD_LENGTH..=MAX_PASSWORD_LENGTH).contains(&policy.length) {
+        return Err(format!(
+            "Length must be {MIN_PASSWORD_LENGTH}-{MAX_PASSWORD_LENGTH}"
+        ));
+    }
+
+    let mut classes = policy.classes.clone();
+    classes.sort_by_key(|class| *class as u8);
+    classes.dedup();
+    if classes.is_empty() {
+        return Err("At least one character class is required".to_string());
+    }
+    if classes.len() > policy.length {
+        return Err("Length is shorter than the number of character classes".to_string());
+    }
+
+    let class_chars = classes
+        .iter()
+        .map(|class| {
+            class
+                .chars()
+                .chars()
+                .filter(|c| !(policy.exclude_ambiguous && AMBIGUOUS.contains(*c)))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let pool = class_chars.concat();
+
+    let mut chars = Vec::with_capacity(policy.length);
+    for class in &class_chars {
+        chars.push(pick(class));
+    }
+    while chars.len() < policy.length {
+        chars.push(pick(&pool));
+    }
+    for i in (1..chars.len()).rev() {
+        chars.swap(i, random_index(i + 1));
+    }
+
+    Ok(Generated {
+        value: chars.iter().collect(),
+        kind: GeneratedKind::Password,
+        entropy_bits: policy.length as f64 * (pool.len() as f64).log2(),
+    })
+}
+
+pub fn generate_passphrase(policy: &PassphrasePolicy) -> Result<Generated, String> {
+    if !(1..=MAX_PASSPHRASE_WORDS).contains(&policy.words) {
+        return Err(format!("Words must be 1-{MAX_PASSPHRASE_WORDS}"));
+    }
+
+    let wordlist = WORDLIST.lines().collect::<Vec<_>>();
+    let words = (0..policy.words)
+        .map(|_| {
+            let word = wordlist[random_index(wordlist.len())];
+            if policy.capitalize {
+                let mut chars = word.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_uppercase().chain(chars).collect())
+                    .unwrap_or_default()
+            } else {
+                word.to_string()
+            }
+        })
+        .collect::<Vec<String>>();
+
+    Ok(Generated {
+        value: words.join(&policy.separator),
+        kind: GeneratedKind::Passphrase,
+        entropy_bits: policy.words as f64 * (wordlist.len() as f64).log2(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wordlist_is_complete() {
+        let words = WORDLIST.lines().collect::<Vec<_>>();
+        assert_eq!(words.len(), 2048);
+        assert!(words.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn password_follows_policy() {
+        let policy = PasswordPolicy {
+            length: 12,
+            classes: vec![CharClass::Digits, CharClass::Upper, CharClass::Digits],
+            exclude_ambiguous: true,
+        };
+        for _ in 0..50 {
+            let generated = generate_password(&policy).unwrap();
+            assert_eq!(generated.value.chars().count(), 12);
+            assert!(generated.value.chars().any(|c| c.is_ascii_digit()));
+            assert!(generated.value.chars().any(|c| c.is_ascii_uppercase()));
+            assert!(generated
+                .value
+                .chars()
+                .all(|c| (c.is_ascii_digit() || c.is_ascii_uppercase()) && !AMBIGUOUS.contains(c)));
+        }
+        // 24 letters + 8 digits.
+        let entropy = generate_password(&policy).unwrap().entropy_bits;
+        assert!((entropy - 60.0).abs() < 1e-9);
+
+        let too_short = PasswordPolicy {
+            length: 3,
+            ..policy.clone()
+        };
+        assert!(generate_password(&too_short).is_err());
+        let no_classes = PasswordPolicy {
+            classes: vec![],
+            ..policy
+        };
+        assert!(generate_password(&no_classes).is_err());
+    }
+
+    #[test]
+    fn passphrase_follows_policy() {
+        let generated = generate_passphrase(&PassphrasePolicy {
+            words: 5,
+            separator: "-".to_string(),
+            capitalize: true,
+        })
+        .unwrap();
+        let words = generated.value.split('-').collect::<Vec<_>>();
+        assert_eq!(words.len(), 5);
+        assert!(words
+            .iter()
+            .all(|w| w.chars().next().unwrap().is_ascii_uppercase()));
+        assert!((generated.entropy_bits - 55.0).abs() < 1e-9);
+    }
+}
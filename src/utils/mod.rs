@@ -1,4 +1,7 @@
 pub mod crypto;
 pub mod dump;
+pub mod generator;
+pub mod native_host;
 pub mod response;
 pub mod terminal;
+pub mod tls;
@@ -0,0 +1,317 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use clap::ValueEnum;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+/// Native messaging host name used by the extension (`connectNative` / `sendNativeMessage`).
+pub const HOST_NAME: &str = "com.binqbit.svpi_chrome_app";
+
+#[cfg(windows)]
+const WRAPPER_FILE: &str = "svpi-native-host.bat";
+#[cfg(not(windows))]
+const WRAPPER_FILE: &str = "svpi-native-host.sh";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Browser {
+    Chrome,
+    Chromium,
+    Firefox,
+    Brave,
+}
+
+impl Browser {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Browser::Chrome => "chrome",
+            Browser::Chromium => "chromium",
+            Browser::Firefox => "firefox",
+            Browser::Brave => "brave",
+        }
+    }
+
+    /// Per-user manifest directory, relative to `$HOME` (`%LOCALAPPDATA%` on Windows,
+    /// where the browser finds the manifest through the registry instead).
+    fn manifest_dir(self) -> &'static str {
+        #[cfg(target_os = "macos")]
+        return match self {
+            Browser::Chrome => "Library/Application Support/Google/Chrome/NativeMessagingHosts",
+            Browser::Chromium => "Library/Application Support/Chromium/NativeMessagingHosts",
+            Browser::Firefox => "Library/Application Support/Mozilla/NativeMessagingHosts",
+            Browser::Brave => {
+                "Library/Application Support/BraveSoftware/Brave-Browser/NativeMessagingHosts"
+            }
+        };
+
+        #[cfg(windows)]
+        return match self {
+            Browser::Chrome => "svpi/NativeMessagingHosts/chrome",
+            Browser::Chromium => "svpi/NativeMessagingHosts/chromium",
+            Browser::Firefox => "svpi/NativeMessagingHosts/firefox",
+            Browser::Brave => "svpi/NativeMessagingHosts/brave",
+        };
+
+        #[cfg(not(any(target_os = "macos", windows)))]
+        return match self {
+            Browser::Chrome => ".config/google-chrome/NativeMessagingHosts",
+            Browser::Chromium => ".config/chromium/NativeMessagingHosts",
+            Browser::Firefox => ".mozilla/native-messaging-hosts",
+            Browser::Brave => ".config/BraveSoftware/Brave-Browser/NativeMessagingHosts",
+        };
+    }
+
+    #[cfg(windows)]
+    fn registry_key(self) -> String {
+        let vendor = match self {
+            Browser::Chrome => "Google\\Chrome",
+            Browser::Chromium => "Chromium",
+            Browser::Firefox => "Mozilla",
+            Browser::Brave => "BraveSoftware\\Brave-Browser",
+        };
+        format!("HKCU\\Software\\{vendor}\\NativeMessagingHosts\\{HOST_NAME}")
+    }
+}
+
+pub struct NativeHostFiles {
+    pub manifest: PathBuf,
+    pub wrapper: PathBuf,
+}
+
+fn base_dir() -> io::Result<PathBuf> {
+    let var = if cfg!(windows) {
+        "LOCALAPPDATA"
+    } else {
+        "HOME"
+    };
+    std::env::var_os(var)
+        .map(PathBuf::from)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("${var} is not set")))
+}
+
+fn files_in(base: &Path, browser: Browser) -> NativeHostFiles {
+    let dir = base.join(browser.manifest_dir());
+    NativeHostFiles {
+        manifest: dir.join(format!("{HOST_NAME}.json")),
+        wrapper: dir.join(WRAPPER_FILE),
+    }
+}
+
+/// Chrome-family IDs are 32 letters `a`-`p`; Firefox IDs are the add-on ID
+/// (`name@domain` or `{uuid}`).
+pub fn validate_extension_id(browser: Browser, id: &str) -> Result<(), String> {
+    let valid = match browser {
+        Browser::Firefox => !id.is_empty() && !id.contains(|c: char| c.is_whitespace() || c == '/'),
+        Browser::Chrome | Browser::Chromium | Browser::Brave => {
+            id.len() == 32 && id.chars().all(|c| ('a'..='p').contains(&c))
+        }
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("invalid {} extension id '{id}'", browser.as_str()))
+    }
+}
+
+pub fn manifest_json(browser: Browser, wrapper: &Path, extension_ids: &[String]) -> Value {
+    let mut manifest = json!({
+        "name": HOST_NAME,
+        "description": "SVPI native messaging host",
+        "path": wrapper.to_string_lossy(),
+        "type": "stdio",
+    });
+    if browser == Browser::Firefox {
+        manifest["allowed_extensions"] = json!(extension_ids);
+    } else {
+        let origins: Vec<String> = extension_ids
+            .iter()
+            .map(|id| format!("chrome-extension://{id}/"))
+            .collect();
+        manifest["allowed_origins"] = json!(origins);
+    }
+    manifest
+}
+
+/// Browsers pass their own arguments (origin, parent window, manifest path),
+/// which `svpi` would reject; the wrapper drops them.
+#[cfg(not(windows))]
+pub fn wrapper_script(exe: &Path, file: Option<&Path>) -> String {
+    fn quote(s: &str) -> String {
+        format!("'{}'", s.replace('\'', "'\\''"))
+    }
+
+    let mut command = format!("exec {} --mode=chrome", quote(&exe.to_string_lossy()));
+    if let Some(file) = file {
+        command.push(' ');
+        command.push_str(&quote(&format!("--file={}", file.to_string_lossy())));
+    }
+    format!("#!/bin/sh\n{command}\n")
+}
+
+#[cfg(windows)]
+pub fn wrapper_script(exe: &Path, file: Option<&Path>) -> String {
+    let escape = |s: &str| s.replace('%', "%%");
+
+    let mut command = format!("\"{}\" --mode=chrome", escape(&exe.to_string_lossy()));
+    if let Some(file) = file {
+        command.push_str(&format!(" \"--file={}\"", escape(&file.to_string_lossy())));
+    }
+    format!("@echo off\r\n{command}\r\n")
+}
+
+fn install_in(
+    base: &Path,
+    browser: Browser,
+    extension_ids: &[String],
+    exe: &Path,
+    file: Option<&Path>,
+) -> io::Result<NativeHostFiles> {
+    let files = files_in(base, browser);
+    if let Some(dir) = files.manifest.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    fs::write(&files.wrapper, wrapper_script(exe, file))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&files.wrapper, fs::Permissions::from_mode(0o755))?;
+    }
+
+    let manifest = manifest_json(browser, &files.wrapper, extension_ids);
+    let data = serde_json::to_vec_pretty(&manifest)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    fs::write(&files.manifest, data)?;
+
+    Ok(files)
+}
+
+fn uninstall_in(base: &Path, browser: Browser) -> io::Result<Vec<PathBuf>> {
+    let files = files_in(base, browser);
+    let mut removed = Vec::new();
+    for path in [files.manifest, files.wrapper] {
+        match fs::remove_file(&path) {
+            Ok(()) => removed.push(path),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(removed)
+}
+
+#[cfg(windows)]
+fn reg(args: &[&str]) -> io::Result<bool> {
+    std::process::Command::new("reg")
+        .args(args)
+        .output()
+        .map(|out| out.status.success())
+}
+
+/// Writes the manifest and wrapper script for the current user (and registers
+/// the manifest on Windows).
+pub fn install(
+    browser: Browser,
+    extension_ids: &[String],
+    exe: &Path,
+    file: Option<&Path>,
+) -> io::Result<NativeHostFiles> {
+    let files = install_in(&base_dir()?, browser, extension_ids, exe, file)?;
+
+    #[cfg(windows)]
+    {
+        let manifest = files.manifest.to_string_lossy().to_string();
+        let key = browser.registry_key();
+        if !reg(&["add", &key, "/ve", "/t", "REG_SZ", "/d", &manifest, "/f"])? {
+            return Err(io::Error::other(format!(
+                "failed to write registry key {key}"
+            )));
+        }
+    }
+
+    Ok(files)
+}
+
+/// Removes what `install` wrote; returns the removed files.
+pub fn uninstall(browser: Browser) -> io::Result<Vec<PathBuf>> {
+    #[cfg(windows)]
+    {
+        // A missing key is fine.
+        reg(&["delete", &browser.registry_key(), "/f"])?;
+    }
+
+    uninstall_in(&base_dir()?, browser)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHROME_ID: &str = "abcdefghijklmnopabcdefghijklmnop";
+
+    #[test]
+    fn extension_ids_are_checked_per_browser() {
+        assert!(validate_extension_id(Browser::Chrome, CHROME_ID).is_ok());
+        assert!(validate_extension_id(Browser::Brave, "short").is_err());
+        assert!(validate_extension_id(Browser::Chromium, &CHROME_ID.to_uppercase()).is_err());
+        assert!(validate_extension_id(Browser::Firefox, "svpi@example.com").is_ok());
+        assert!(validate_extension_id(Browser::Firefox, "").is_err());
+    }
+
+    #[test]
+    fn manifest_uses_browser_specific_allow_list() {
+        let wrapper = Path::new("/opt/svpi/host.sh");
+        let ids = vec![CHROME_ID.to_string()];
+
+        let chrome = manifest_json(Browser::Chrome, wrapper, &ids);
+        assert_eq!(
+            chrome["allowed_origins"][0],
+            format!("chrome-extension://{CHROME_ID}/")
+        );
+        assert!(chrome.get("allowed_extensions").is_none());
+        assert_eq!(chrome["name"], HOST_NAME);
+
+        let ids = vec!["svpi@example.com".to_string()];
+        let firefox = manifest_json(Browser::Firefox, wrapper, &ids);
+        assert_eq!(firefox["allowed_extensions"][0], "svpi@example.com");
+        assert!(firefox.get("allowed_origins").is_none());
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn wrapper_quotes_paths_and_drops_browser_arguments() {
+        let script = wrapper_script(Path::new("/opt/s v/svpi"), Some(Path::new("/x/it's.bin")));
+        assert_eq!(
+            script,
+            "#!/bin/sh\nexec '/opt/s v/svpi' --mode=chrome '--file=/x/it'\\''s.bin'\n"
+        );
+    }
+
+    #[test]
+    fn install_and_uninstall_in_home() {
+        let base = std::env::temp_dir().join(format!("svpi-native-host-{}", std::process::id()));
+        let ids = vec![CHROME_ID.to_string()];
+
+        let files = install_in(
+            &base,
+            Browser::Brave,
+            &ids,
+            Path::new("/usr/bin/svpi"),
+            None,
+        )
+        .expect("install");
+        assert!(files
+            .manifest
+            .starts_with(base.join(Browser::Brave.manifest_dir())));
+        let manifest: Value = serde_json::from_slice(&fs::read(&files.manifest).unwrap()).unwrap();
+        assert_eq!(manifest["path"], files.wrapper.to_string_lossy().as_ref());
+
+        let removed = uninstall_in(&base, Browser::Brave).expect("uninstall");
+        assert_eq!(removed.len(), 2);
+        assert!(uninstall_in(&base, Browser::Brave).unwrap().is_empty());
+
+        fs::remove_dir_all(&base).unwrap();
+    }
+}
@@ -1,7 +1,10 @@
 use serde::Serialize;
 use serde_json::{json, Value};
 
-use crate::seg_mgr::ARCHITECTURE_VERSION;
+use crate::{
+    pass_mgr::format_date,
+    seg_mgr::{Record, ARCHITECTURE_VERSION},
+};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum OutputFormat {
@@ -22,6 +25,50 @@ fn dump_protection_label(code: u64) -> &'static str {
     }
 }
 
+/// Warns about aliases whose target was just removed.
+fn print_dangling_aliases(result: &Value) {
+    let Some(aliases) = result.get("dangling_aliases").and_then(|v| v.as_array()) else {
+        return;
+    };
+    let aliases = aliases
+        .iter()
+        .filter_map(|v| v.as_str())
+        .collect::<Vec<_>>();
+    println!(
+        "Warning: these aliases now point at nothing: {}",
+        aliases.join(", ")
+    );
+}
+
+/// Lists expired and soon-to-expire entries of `list` and `check`.
+fn print_expiry_warnings(result: &Value) {
+    let Some(warnings) = result.get("expiry_warnings").and_then(|v| v.as_array()) else {
+        return;
+    };
+    for warning in warnings {
+        let name = warning.get("name").and_then(|v| v.as_str()).unwrap_or("-");
+        let date = warning
+            .get("expires")
+            .and_then(|v| v.as_u64())
+            .map(format_date)
+            .unwrap_or_default();
+        if warning.get("state").and_then(|v| v.as_str()) == Some("expired") {
+            println!("Warning: '{name}' expired on {date}.");
+        } else {
+            println!("Warning: '{name}' expires on {date}.");
+        }
+    }
+}
+
+/// Quotes a CSV field when it contains a separator, quote or line break.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct SvpiMeta {
     pub app_version: &'static str,
@@ -120,6 +167,50 @@ impl SvpiResponse {
         })
     }
 
+    /// Prints the `segments` of a listing as CSV (errors as in CLI mode).
+    pub fn print_csv(&self) {
+        let segments = self
+            .result
+            .as_ref()
+            .and_then(|result| result.get("segments"))
+            .and_then(|v| v.as_array());
+        let Some(segments) = segments.filter(|_| self.ok) else {
+            return self.print_cli();
+        };
+
+        let columns = [
+            "name",
+            "data_type",
+            "size",
+            "fingerprint",
+            "password_fingerprint",
+            "folder",
+            "tags",
+            "alias_of",
+            "locked",
+            "expires",
+        ];
+        println!("{}", columns.join(","));
+        for segment in segments {
+            let row = columns
+                .iter()
+                .map(|column| match segment.get(*column) {
+                    Some(Value::String(v)) => csv_field(v),
+                    Some(Value::Null) | None => String::new(),
+                    Some(Value::Array(items)) => csv_field(
+                        &items
+                            .iter()
+                            .filter_map(|v| v.as_str())
+                            .collect::<Vec<_>>()
+                            .join(" "),
+                    ),
+                    Some(v) => v.to_string(),
+                })
+                .collect::<Vec<_>>();
+            println!("{}", row.join(","));
+        }
+    }
+
     pub fn print_cli(&self) {
         if !self.ok {
             let (code, message, details) = self
@@ -255,6 +346,21 @@ impl SvpiResponse {
                 } else {
                     println!("file: (not set)");
                 }
+                let origins: Vec<&str> = result
+                    .get("cors_origins")
+                    .and_then(|v| v.as_array())
+                    .map(|a| a.iter().filter_map(|v| v.as_str()).collect())
+                    .unwrap_or_default();
+                if origins.is_empty() {
+                    println!("cors origins: (not set)");
+                } else {
+                    println!("cors origins: {}", origins.join(", "));
+                }
+                let data_type = result
+                    .get("default_data_type")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("infer");
+                println!("default data type: {data_type}");
             }
             "init" => {
                 let memory_size = result
@@ -289,6 +395,7 @@ impl SvpiResponse {
                 if !architecture_ok {
                     eprintln!("Warning: architecture mismatch.");
                 }
+                print_expiry_warnings(result);
             }
             "format" => println!("Formatted."),
             "optimize" => {
@@ -344,6 +451,13 @@ impl SvpiResponse {
                     .and_then(|v| v.as_u64())
                     .unwrap_or(0);
                 println!("Imported {segments} segments from '{file}'.");
+                if let Some(skipped) = result.get("skipped_locked").and_then(|v| v.as_array()) {
+                    let skipped = skipped
+                        .iter()
+                        .filter_map(|v| v.as_str())
+                        .collect::<Vec<_>>();
+                    println!("Skipped locked entries: {}", skipped.join(", "));
+                }
             }
             "dump" => {
                 let file = result.get("file").and_then(|v| v.as_str()).unwrap_or("-");
@@ -451,7 +565,41 @@ impl SvpiResponse {
                     .unwrap_or(".svpi");
                 println!("Default vault file set to '{file}' (saved in {cfg}).");
             }
-            "set" => {
+            "set-default-type" => {
+                let cfg = result
+                    .get("config_file")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or(".svpi");
+                let data_type = result
+                    .get("default_data_type")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("infer");
+                if data_type == "plain" {
+                    println!("`set` now stores text as plain (saved in {cfg}).");
+                } else {
+                    println!("`set` now infers the data type (saved in {cfg}).");
+                }
+            }
+            "set-cors-origins" => {
+                let cfg = result
+                    .get("config_file")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or(".svpi");
+                let origins: Vec<&str> = result
+                    .get("cors_origins")
+                    .and_then(|v| v.as_array())
+                    .map(|a| a.iter().filter_map(|v| v.as_str()).collect())
+                    .unwrap_or_default();
+                if origins.is_empty() {
+                    println!("CORS origins cleared (saved in {cfg}).");
+                } else {
+                    println!(
+                        "CORS origins set to {} (saved in {cfg}).",
+                        origins.join(", ")
+                    );
+                }
+            }
+            "set" | "set-otp" => {
                 let name = result.get("name").and_then(|v| v.as_str()).unwrap_or("-");
                 let data_type = result
                     .get("data_type")
@@ -466,14 +614,52 @@ impl SvpiResponse {
                 } else {
                     println!("Saved '{name}' ({data_type}).");
                 }
+                if let Some(expires) = result.get("expires").and_then(|v| v.as_u64()) {
+                    println!("Expires on {}.", format_date(expires));
+                }
+            }
+            "generate" => {
+                let name = result.get("name").and_then(|v| v.as_str()).unwrap_or("-");
+                let kind = result.get("kind").and_then(|v| v.as_str()).unwrap_or("-");
+                let entropy = result
+                    .get("entropy_bits")
+                    .and_then(|v| v.as_f64())
+                    .unwrap_or(0.0);
+                let encrypted = result
+                    .get("encrypted")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
+                let encrypted = if encrypted { ", encrypted" } else { "" };
+                println!("Saved generated {kind} as '{name}'{encrypted}.");
+                println!("Estimated entropy: {entropy:.1} bits");
             }
             "get" => {
                 let copied = result
                     .get("copied_to_clipboard")
                     .and_then(|v| v.as_bool())
                     .unwrap_or(false);
+                let otp = result.get("otp");
+                let otp_validity = || {
+                    let remaining = otp
+                        .and_then(|o| o.get("remaining"))
+                        .and_then(|v| v.as_u64());
+                    let counter = otp.and_then(|o| o.get("counter")).and_then(|v| v.as_u64());
+                    match (remaining, counter) {
+                        (Some(remaining), _) => println!("Valid for: {remaining}s"),
+                        (None, Some(counter)) => println!("Counter: {counter}"),
+                        (None, None) => {}
+                    }
+                };
                 if copied {
                     println!("Data copied to clipboard!");
+                    otp_validity();
+                    return;
+                }
+
+                if otp.is_some() {
+                    let code = result.get("data").and_then(|v| v.as_str()).unwrap_or("-");
+                    println!("Code: {code}");
+                    otp_validity();
                     return;
                 }
 
@@ -482,6 +668,18 @@ impl SvpiResponse {
                     return;
                 }
 
+                if let Some(record) = result
+                    .get("data")
+                    .and_then(|v| serde_json::from_value::<Record>(v.clone()).ok())
+                {
+                    let names = record.field_names();
+                    let width = names.iter().map(|n| n.len()).max().unwrap_or(0);
+                    for field in names {
+                        println!("{field:width$} : {}", record.get(field).unwrap_or_default());
+                    }
+                    return;
+                }
+
                 println!("OK");
             }
             "list" => {
@@ -537,12 +735,95 @@ impl SvpiResponse {
                         "| {:32} | {:15} | {:10} | {:10} | {:10} |",
                         name, data_type, size, hash, pass_hash
                     );
+
+                    let mut grouping = Vec::new();
+                    if segment.get("locked").and_then(|v| v.as_bool()) == Some(true) {
+                        grouping.push("[locked]".to_string());
+                    }
+                    if let Some(target) = segment.get("alias_of").and_then(|v| v.as_str()) {
+                        grouping.push(format!("-> {target}"));
+                    }
+                    if let Some(expires) = segment.get("expires").and_then(|v| v.as_u64()) {
+                        grouping.push(format!("expires {}", format_date(expires)));
+                    }
+                    if let Some(folder) = segment.get("folder").and_then(|v| v.as_str()) {
+                        grouping.push(format!("{folder}/"));
+                    }
+                    if let Some(tags) = segment.get("tags").and_then(|v| v.as_array()) {
+                        grouping.extend(
+                            tags.iter()
+                                .filter_map(|t| t.as_str())
+                                .map(|t| format!("#{t}")),
+                        );
+                    }
+                    if !grouping.is_empty() {
+                        println!("|   {:87} |", grouping.join(" "));
+                    }
                     println!("{}", "-".repeat(93));
                 }
+                print_expiry_warnings(result);
+            }
+            "remove" | "rename" | "mv" | "change-data-type" if result.get("changes").is_some() => {
+                let dry_run = result
+                    .get("dry_run")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
+                let changes = result
+                    .get("changes")
+                    .and_then(|v| v.as_array())
+                    .cloned()
+                    .unwrap_or_default();
+                for change in &changes {
+                    let name = change.get("name").and_then(|v| v.as_str()).unwrap_or("-");
+                    let target = if let Some(new_name) = change.get("new_name") {
+                        new_name.as_str().map(str::to_string)
+                    } else if let Some(folder) = change.get("folder") {
+                        Some(format!("{}/", folder.as_str().unwrap_or("")))
+                    } else {
+                        change
+                            .get("data_type")
+                            .and_then(|v| v.as_str())
+                            .map(str::to_string)
+                    };
+                    match target {
+                        Some(target) => println!("  {name} -> {target}"),
+                        None => println!("  {name}"),
+                    }
+                }
+                if dry_run {
+                    println!("{} entries would be changed (dry run).", changes.len());
+                } else {
+                    println!("{} entries changed.", changes.len());
+                }
+                print_dangling_aliases(result);
             }
             "remove" => {
                 let name = result.get("name").and_then(|v| v.as_str()).unwrap_or("-");
                 println!("Removed '{name}'.");
+                print_dangling_aliases(result);
+            }
+            "lock" | "unlock" => {
+                let name = result.get("name").and_then(|v| v.as_str()).unwrap_or("-");
+                if result.get("locked").and_then(|v| v.as_bool()) == Some(true) {
+                    println!("'{name}' is locked.");
+                } else {
+                    println!("'{name}' is unlocked.");
+                }
+            }
+            "set-expiry" => {
+                let name = result.get("name").and_then(|v| v.as_str()).unwrap_or("-");
+                match result.get("expires").and_then(|v| v.as_u64()) {
+                    Some(expires) => println!("'{name}' expires on {}.", format_date(expires)),
+                    None => println!("'{name}' no longer expires."),
+                }
+            }
+            "set-alias" => {
+                let name = result.get("name").and_then(|v| v.as_str()).unwrap_or("-");
+                let target = result
+                    .get("alias_of")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("-");
+                println!("'{name}' is now an alias of '{target}'.");
             }
             "rename" => {
                 let from = result.get("from").and_then(|v| v.as_str()).unwrap_or("-");
@@ -569,6 +850,208 @@ impl SvpiResponse {
                     println!("Encryption removed for '{name}'.");
                 }
             }
+            "set-urls" => {
+                let name = result.get("name").and_then(|v| v.as_str()).unwrap_or("-");
+                let urls = result
+                    .get("urls")
+                    .and_then(|v| v.as_array())
+                    .map(|v| v.iter().filter_map(|u| u.as_str()).collect::<Vec<_>>())
+                    .unwrap_or_default();
+                if urls.is_empty() {
+                    println!("URLs cleared for '{name}'.");
+                } else {
+                    println!("URLs for '{name}': {}", urls.join(", "));
+                }
+            }
+            "tag" | "untag" | "mv" => {
+                let name = result.get("name").and_then(|v| v.as_str()).unwrap_or("-");
+                let folder = result.get("folder").and_then(|v| v.as_str()).unwrap_or("/");
+                let tags = result
+                    .get("tags")
+                    .and_then(|v| v.as_array())
+                    .map(|v| v.iter().filter_map(|t| t.as_str()).collect::<Vec<_>>())
+                    .unwrap_or_default();
+                println!("Folder of '{name}': {folder}");
+                if tags.is_empty() {
+                    println!("Tags: -");
+                } else {
+                    println!("Tags: {}", tags.join(", "));
+                }
+            }
+            "find-by-url" => {
+                let candidates = result
+                    .get("candidates")
+                    .and_then(|v| v.as_array())
+                    .cloned()
+                    .unwrap_or_default();
+                if candidates.is_empty() {
+                    println!("No entries for this site.");
+                    return;
+                }
+
+                println!("{}", "=".repeat(80));
+                println!("| {:32} | {:9} | {:29} |", "Name", "Match", "URL");
+                println!("{}", "=".repeat(80));
+                for candidate in candidates {
+                    let name = candidate
+                        .get("name")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("-");
+                    let matched = candidate
+                        .get("match")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("-");
+                    let url = candidate.get("url").and_then(|v| v.as_str()).unwrap_or("-");
+                    println!("| {name:32} | {matched:9} | {url:29} |");
+                }
+                println!("{}", "=".repeat(80));
+            }
+            "audit" => {
+                let findings = result
+                    .get("findings")
+                    .and_then(|v| v.as_array())
+                    .cloned()
+                    .unwrap_or_default();
+                let entries = result.get("entries").and_then(|v| v.as_u64()).unwrap_or(0);
+                let checked = result.get("checked").and_then(|v| v.as_u64()).unwrap_or(0);
+                println!("Audited {entries} entries ({checked} read).");
+                let locked = result
+                    .get("locked")
+                    .and_then(|v| v.as_array())
+                    .map(|v| v.iter().filter_map(|n| n.as_str()).collect::<Vec<_>>())
+                    .unwrap_or_default();
+                if !locked.is_empty() {
+                    println!("Not unlocked: {}", locked.join(", "));
+                }
+                if let Some(breached) = result.get("breached").and_then(|v| v.as_u64()) {
+                    println!("Found in breached-password list: {breached}");
+                }
+                if findings.is_empty() {
+                    println!("No findings.");
+                    return;
+                }
+
+                for finding in findings {
+                    let severity = finding
+                        .get("severity")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("-");
+                    let name = finding.get("name").and_then(|v| v.as_str()).unwrap_or("-");
+                    let mut message = finding
+                        .get("message")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("-")
+                        .to_string();
+                    if let Some(reused_with) = finding.get("reused_with").and_then(|v| v.as_array())
+                    {
+                        let names = reused_with
+                            .iter()
+                            .filter_map(|n| n.as_str())
+                            .collect::<Vec<_>>();
+                        message = format!("{message}: {}", names.join(", "));
+                    }
+                    println!("[{severity:6}] {name}: {message}");
+                }
+            }
+            "gen-cert" => {
+                let cert = result
+                    .get("cert_file")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("-");
+                let key = result
+                    .get("key_file")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("-");
+                println!("Certificate written to '{cert}', private key to '{key}'.");
+                println!("Start the server with --tls-cert={cert} --tls-key={key}");
+            }
+            "install-native-host" => {
+                let browser = result
+                    .get("browser")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("-");
+                let manifest = result
+                    .get("manifest")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("-");
+                let wrapper = result
+                    .get("wrapper")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("-");
+                println!("Native messaging host installed for {browser}.");
+                println!("Manifest: {manifest}");
+                println!("Wrapper: {wrapper}");
+                match result.get("file").and_then(|v| v.as_str()) {
+                    Some(file) => println!("Vault file: {file}"),
+                    None => println!("Vault: serial device"),
+                }
+            }
+            "uninstall-native-host" => {
+                let browser = result
+                    .get("browser")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("-");
+                let removed = result
+                    .get("removed")
+                    .and_then(|v| v.as_array())
+                    .cloned()
+                    .unwrap_or_default();
+                if removed.is_empty() {
+                    println!("No native messaging host installed for {browser}.");
+                } else {
+                    println!("Native messaging host removed for {browser}.");
+                    for path in removed.iter().filter_map(|v| v.as_str()) {
+                        println!("Removed: {path}");
+                    }
+                }
+            }
+            "token-create" => {
+                let id = result.get("id").and_then(|v| v.as_str()).unwrap_or("-");
+                let name = result.get("name").and_then(|v| v.as_str()).unwrap_or("-");
+                let token = result.get("token").and_then(|v| v.as_str()).unwrap_or("-");
+                println!("Token '{name}' created (id: {id}).");
+                println!("Token: {token}");
+                println!("Store it now; it cannot be shown again.");
+            }
+            "token-list" => {
+                let tokens = result
+                    .get("tokens")
+                    .and_then(|v| v.as_array())
+                    .cloned()
+                    .unwrap_or_default();
+                if tokens.is_empty() {
+                    println!("No API tokens.");
+                    return;
+                }
+
+                println!("{}", "=".repeat(80));
+                println!("| {:8} | {:20} | {:42} |", "Id", "Name", "Scopes");
+                println!("{}", "=".repeat(80));
+                for token in tokens {
+                    let id = token.get("id").and_then(|v| v.as_str()).unwrap_or("-");
+                    let name = token.get("name").and_then(|v| v.as_str()).unwrap_or("-");
+                    let scopes = token
+                        .get("scopes")
+                        .and_then(|v| v.as_array())
+                        .map(|v| {
+                            v.iter()
+                                .filter_map(|s| s.as_str())
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        })
+                        .unwrap_or_default();
+                    println!("| {:8} | {:20} | {:42} |", id, name, scopes);
+                    println!("{}", "-".repeat(80));
+                }
+            }
+            "token-revoke" => {
+                let revoked = result
+                    .get("revoked")
+                    .and_then(|v| v.as_array())
+                    .map(|v| v.len())
+                    .unwrap_or(0);
+                println!("Revoked {revoked} token(s).");
+            }
             _ => {
                 if let Some(data) = result.get("data").and_then(|v| v.as_str()) {
                     println!("{data}");
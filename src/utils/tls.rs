@@ -0,0 +1,55 @@
+use std::{fs, io, path::Path};
+
+pub const DEFAULT_CERT_HOSTS: &[&str] = &["localhost", "127.0.0.1", "::1"];
+
+pub struct SelfSignedCert {
+    pub cert_pem: String,
+    pub key_pem: String,
+}
+
+pub fn generate_self_signed(hosts: &[String]) -> Result<SelfSignedCert, rcgen::Error> {
+    let hosts = if hosts.is_empty() {
+        DEFAULT_CERT_HOSTS.iter().map(|h| h.to_string()).collect()
+    } else {
+        hosts.to_vec()
+    };
+
+    let certified = rcgen::generate_simple_self_signed(hosts)?;
+    Ok(SelfSignedCert {
+        cert_pem: certified.cert.pem(),
+        key_pem: certified.key_pair.serialize_pem(),
+    })
+}
+
+/// Writes a private key readable by the owner only (on Unix).
+pub fn write_private_key(path: &Path, pem: &str) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::{io::Write, os::unix::fs::OpenOptionsExt};
+
+        let mut file = fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(path)?;
+        file.write_all(pem.as_bytes())
+    }
+
+    #[cfg(not(unix))]
+    {
+        fs::write(path, pem)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generates_pem_pair() {
+        let cert = generate_self_signed(&[]).expect("generate");
+        assert!(cert.cert_pem.starts_with("-----BEGIN CERTIFICATE-----"));
+        assert!(cert.key_pem.contains("PRIVATE KEY-----"));
+    }
+}